@@ -0,0 +1,13 @@
+-- Hierarchical pages: each page may have a parent, an explicit position among
+-- its siblings, and a materialised `path` (slugs of all ancestors joined with
+-- '/') so nested URLs like `guides/install/linux` resolve with a single lookup.
+-- Slugs stay globally unique, so `path` is unique by construction.
+ALTER TABLE pages ADD COLUMN parent_id TEXT REFERENCES pages(id) ON DELETE SET NULL;
+ALTER TABLE pages ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pages ADD COLUMN path TEXT NOT NULL DEFAULT '';
+
+-- Every existing page is top-level, so its path is just its slug.
+UPDATE pages SET path = slug;
+
+CREATE INDEX pages_parent_id_idx ON pages (parent_id);
+CREATE INDEX pages_path_idx      ON pages (path);
@@ -0,0 +1,22 @@
+-- Slug history: every slug a page or article has given up, so old URLs can be
+-- answered with a permanent redirect to the current slug. The primary key
+-- guarantees a retired slug points at exactly one entity.
+CREATE TABLE slug_history (
+    entity_type TEXT NOT NULL CHECK (entity_type IN ('page', 'article')),
+    slug        TEXT NOT NULL,
+    entity_id   TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    PRIMARY KEY (entity_type, slug)
+);
+
+CREATE INDEX slug_history_entity_idx ON slug_history (entity_type, entity_id);
+
+-- entity_id is polymorphic and cannot carry a foreign key, so clean up the
+-- history rows when content is permanently deleted.
+CREATE TRIGGER pages_slug_history_delete AFTER DELETE ON pages BEGIN
+    DELETE FROM slug_history WHERE entity_type = 'page' AND entity_id = old.id;
+END;
+
+CREATE TRIGGER articles_slug_history_delete AFTER DELETE ON articles BEGIN
+    DELETE FROM slug_history WHERE entity_type = 'article' AND entity_id = old.id;
+END;
@@ -0,0 +1,19 @@
+-- Admin-managed redirect rules, matched by the fallback router before a
+-- request is proxied to the frontend.
+--   • exact  — `source` must equal the request path
+--   • prefix — `source` is a path prefix; the remainder is appended to `target`
+--   • regex  — `source` is a regular expression; `target` may use $1 / ${name}
+-- A 410 rule answers "Gone" and needs no target.
+CREATE TABLE redirects (
+    id          TEXT    PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    match_type  TEXT    NOT NULL CHECK (match_type IN ('exact', 'prefix', 'regex')),
+    source      TEXT    NOT NULL,
+    target      TEXT    NOT NULL DEFAULT '',
+    status_code INTEGER NOT NULL DEFAULT 301 CHECK (status_code IN (301, 302, 410)),
+    -- Usage tracking so dead rules can be spotted and removed.
+    hit_count   INTEGER NOT NULL DEFAULT 0,
+    last_hit_at TEXT,
+    created_at  TEXT    NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    updated_at  TEXT    NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    UNIQUE (match_type, source)
+);
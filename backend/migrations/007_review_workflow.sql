@@ -0,0 +1,128 @@
+-- Editorial review workflow: pages and articles gain an `in_review` status and
+-- a `review_comment` holding the reviewer's note from the last rejection.
+-- Users can be designated reviewers in addition to admins.
+--
+-- SQLite cannot alter a CHECK constraint in place, so both tables are rebuilt.
+-- This relies on migrations running with foreign key enforcement off (see
+-- db::create_pool) — otherwise dropping the old tables would cascade into
+-- revisions and category links. Rowids are copied verbatim because the FTS
+-- indexes reference rows by rowid.
+
+-- ---------------------------------------------------------------------------
+-- Pages
+-- ---------------------------------------------------------------------------
+CREATE TABLE pages_new (
+    id             TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    title          TEXT NOT NULL,
+    slug           TEXT NOT NULL UNIQUE,
+    content        TEXT NOT NULL DEFAULT '',
+    status         TEXT NOT NULL DEFAULT 'draft'
+                       CHECK (status IN ('draft', 'in_review', 'published', 'scheduled', 'trashed')),
+    publish_at     TEXT,
+    author_id      TEXT NOT NULL REFERENCES users(id),
+    created_at     TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    updated_at     TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    trashed_at     TEXT,
+    template       TEXT NOT NULL DEFAULT 'default' CHECK (template IN ('default', 'project')),
+    parent_id      TEXT REFERENCES pages(id) ON DELETE SET NULL,
+    sort_order     INTEGER NOT NULL DEFAULT 0,
+    path           TEXT NOT NULL DEFAULT '',
+    review_comment TEXT
+);
+
+INSERT INTO pages_new
+    (rowid, id, title, slug, content, status, publish_at, author_id, created_at,
+     updated_at, trashed_at, template, parent_id, sort_order, path)
+SELECT rowid, id, title, slug, content, status, publish_at, author_id, created_at,
+       updated_at, trashed_at, template, parent_id, sort_order, path
+FROM pages;
+
+DROP TABLE pages;
+ALTER TABLE pages_new RENAME TO pages;
+
+CREATE INDEX pages_slug_idx      ON pages (slug);
+CREATE INDEX pages_status_idx    ON pages (status);
+CREATE INDEX pages_parent_id_idx ON pages (parent_id);
+CREATE INDEX pages_path_idx      ON pages (path);
+
+CREATE TRIGGER pages_fts_insert AFTER INSERT ON pages BEGIN
+    INSERT INTO pages_fts (rowid, title, content)
+    VALUES (new.rowid, new.title, new.content);
+END;
+
+CREATE TRIGGER pages_fts_delete AFTER DELETE ON pages BEGIN
+    INSERT INTO pages_fts (pages_fts, rowid, title, content)
+    VALUES ('delete', old.rowid, old.title, old.content);
+END;
+
+CREATE TRIGGER pages_fts_update AFTER UPDATE ON pages BEGIN
+    INSERT INTO pages_fts (pages_fts, rowid, title, content)
+    VALUES ('delete', old.rowid, old.title, old.content);
+    INSERT INTO pages_fts (rowid, title, content)
+    VALUES (new.rowid, new.title, new.content);
+END;
+
+CREATE TRIGGER pages_slug_history_delete AFTER DELETE ON pages BEGIN
+    DELETE FROM slug_history WHERE entity_type = 'page' AND entity_id = old.id;
+END;
+
+-- ---------------------------------------------------------------------------
+-- Articles
+-- ---------------------------------------------------------------------------
+CREATE TABLE articles_new (
+    id                   TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    title                TEXT NOT NULL,
+    slug                 TEXT NOT NULL UNIQUE,
+    short_text           TEXT NOT NULL DEFAULT '',
+    content              TEXT NOT NULL DEFAULT '',
+    status               TEXT NOT NULL DEFAULT 'draft'
+                             CHECK (status IN ('draft', 'in_review', 'published', 'scheduled', 'trashed')),
+    publish_at           TEXT,
+    author_id            TEXT NOT NULL REFERENCES users(id),
+    created_at           TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    updated_at           TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    trashed_at           TEXT,
+    cover_image_id       TEXT REFERENCES media(id) ON DELETE SET NULL,
+    reading_time_minutes INTEGER NOT NULL DEFAULT 0,
+    review_comment       TEXT
+);
+
+INSERT INTO articles_new
+    (rowid, id, title, slug, short_text, content, status, publish_at, author_id,
+     created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes)
+SELECT rowid, id, title, slug, short_text, content, status, publish_at, author_id,
+       created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes
+FROM articles;
+
+DROP TABLE articles;
+ALTER TABLE articles_new RENAME TO articles;
+
+CREATE INDEX articles_slug_idx   ON articles (slug);
+CREATE INDEX articles_status_idx ON articles (status);
+
+CREATE TRIGGER articles_fts_insert AFTER INSERT ON articles BEGIN
+    INSERT INTO articles_fts (rowid, title, short_text, content)
+    VALUES (new.rowid, new.title, new.short_text, new.content);
+END;
+
+CREATE TRIGGER articles_fts_delete AFTER DELETE ON articles BEGIN
+    INSERT INTO articles_fts (articles_fts, rowid, title, short_text, content)
+    VALUES ('delete', old.rowid, old.title, old.short_text, old.content);
+END;
+
+CREATE TRIGGER articles_fts_update AFTER UPDATE ON articles BEGIN
+    INSERT INTO articles_fts (articles_fts, rowid, title, short_text, content)
+    VALUES ('delete', old.rowid, old.title, old.short_text, old.content);
+    INSERT INTO articles_fts (rowid, title, short_text, content)
+    VALUES (new.rowid, new.title, new.short_text, new.content);
+END;
+
+CREATE TRIGGER articles_slug_history_delete AFTER DELETE ON articles BEGIN
+    DELETE FROM slug_history WHERE entity_type = 'article' AND entity_id = old.id;
+END;
+
+-- ---------------------------------------------------------------------------
+-- Designated reviewers
+-- ---------------------------------------------------------------------------
+-- Admins can always review; this flag lets an editor approve and reject too.
+ALTER TABLE users ADD COLUMN can_review INTEGER NOT NULL DEFAULT 0;
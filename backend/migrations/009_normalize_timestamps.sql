@@ -0,0 +1,31 @@
+-- Normalise stored schedule timestamps to the canonical UTC form
+-- `YYYY-MM-DDTHH:MM:SSZ` used everywhere else, so the scheduler can compare
+-- them to `strftime('%Y-%m-%dT%H:%M:%SZ', 'now')` as plain strings.
+--
+-- Earlier versions bound chrono values directly, which stored RFC 3339 text
+-- such as `2025-03-01T09:00:00.123+00:00`, while a few writes used
+-- `datetime('now')` (`2025-03-01 09:00:00`). SQLite's date functions accept
+-- both (offsets are converted to UTC); values they cannot parse are left as-is.
+
+UPDATE pages
+SET publish_at = strftime('%Y-%m-%dT%H:%M:%SZ', publish_at)
+WHERE strftime('%Y-%m-%dT%H:%M:%SZ', publish_at) IS NOT NULL;
+
+UPDATE pages
+SET unpublish_at = strftime('%Y-%m-%dT%H:%M:%SZ', unpublish_at)
+WHERE strftime('%Y-%m-%dT%H:%M:%SZ', unpublish_at) IS NOT NULL;
+
+UPDATE articles
+SET publish_at = strftime('%Y-%m-%dT%H:%M:%SZ', publish_at)
+WHERE strftime('%Y-%m-%dT%H:%M:%SZ', publish_at) IS NOT NULL;
+
+UPDATE articles
+SET unpublish_at = strftime('%Y-%m-%dT%H:%M:%SZ', unpublish_at)
+WHERE strftime('%Y-%m-%dT%H:%M:%SZ', unpublish_at) IS NOT NULL;
+
+UPDATE site_settings
+SET updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', updated_at)
+WHERE strftime('%Y-%m-%dT%H:%M:%SZ', updated_at) IS NOT NULL;
+
+-- Timezone used to interpret and display schedule times in the admin UI.
+INSERT OR IGNORE INTO site_settings (key, value) VALUES ('site_timezone', 'UTC');
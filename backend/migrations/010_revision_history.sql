@@ -0,0 +1,24 @@
+-- Revision messages and named versions.
+--
+-- `message` is an optional note supplied with the update that created the
+-- revision. A non-NULL `version_name` pins the revision: it is exempt from the
+-- retention policy that thins old revisions, and names are unique per item.
+
+ALTER TABLE page_revisions ADD COLUMN message TEXT;
+ALTER TABLE page_revisions ADD COLUMN version_name TEXT;
+
+ALTER TABLE article_revisions ADD COLUMN message TEXT;
+ALTER TABLE article_revisions ADD COLUMN version_name TEXT;
+
+CREATE UNIQUE INDEX page_revisions_version_name_idx
+    ON page_revisions (page_id, version_name) WHERE version_name IS NOT NULL;
+CREATE UNIQUE INDEX article_revisions_version_name_idx
+    ON article_revisions (article_id, version_name) WHERE version_name IS NOT NULL;
+
+-- Retention policy defaults: keep every revision for a week, the last one of
+-- each day for a month, the last one of each week for a year, then nothing
+-- (apart from pinned versions and each item's latest revision).
+INSERT OR IGNORE INTO site_settings (key, value) VALUES
+    ('revision_keep_all_days',    '7'),
+    ('revision_keep_daily_days',  '30'),
+    ('revision_keep_weekly_days', '365');
@@ -0,0 +1,24 @@
+-- Draft overlays: pending edits to published content.
+--
+-- While a page or article is published, saves write their content fields here
+-- instead of to the live row, so visitors keep seeing the published version
+-- until the draft is explicitly published (which copies it onto the row and
+-- deletes it) or discarded. At most one draft exists per item.
+
+CREATE TABLE page_drafts (
+    page_id    TEXT PRIMARY KEY REFERENCES pages(id) ON DELETE CASCADE,
+    title      TEXT NOT NULL,
+    content    TEXT NOT NULL DEFAULT '',
+    author_id  TEXT NOT NULL REFERENCES users(id),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE TABLE article_drafts (
+    article_id     TEXT PRIMARY KEY REFERENCES articles(id) ON DELETE CASCADE,
+    title          TEXT NOT NULL,
+    short_text     TEXT NOT NULL DEFAULT '',
+    content        TEXT NOT NULL DEFAULT '',
+    cover_image_id TEXT REFERENCES media(id) ON DELETE SET NULL,
+    author_id      TEXT NOT NULL REFERENCES users(id),
+    updated_at     TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
@@ -0,0 +1,7 @@
+-- Menus get an `updated_at` so concurrent edits can be detected (see
+-- services::concurrency). ALTER TABLE cannot add a column with a non-constant
+-- default, so existing rows are stamped separately.
+
+ALTER TABLE menus ADD COLUMN updated_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+
+UPDATE menus SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now');
@@ -0,0 +1,90 @@
+-- Markdown as a content format. Pages and articles (and their revisions and
+-- drafts) record whether `content` is HTML or Markdown. The server renders it
+-- to sanitized HTML on write and keeps the result in `content_html`, which is
+-- what the public site displays and what full-text search indexes.
+--
+-- Existing content is HTML and is copied to `content_html` as-is; it is
+-- re-rendered on its next save.
+
+ALTER TABLE pages ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html'
+    CHECK (content_format IN ('html', 'markdown'));
+ALTER TABLE pages ADD COLUMN content_html TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE articles ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html'
+    CHECK (content_format IN ('html', 'markdown'));
+ALTER TABLE articles ADD COLUMN content_html TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE page_revisions ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
+ALTER TABLE article_revisions ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
+ALTER TABLE page_drafts ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
+ALTER TABLE article_drafts ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
+
+-- ---------------------------------------------------------------------------
+-- Search indexes now cover the rendered HTML instead of the source. The FTS
+-- tables are external-content tables, so their column names must match the
+-- source columns; they are recreated and rebuilt.
+-- ---------------------------------------------------------------------------
+DROP TRIGGER pages_fts_insert;
+DROP TRIGGER pages_fts_delete;
+DROP TRIGGER pages_fts_update;
+DROP TRIGGER articles_fts_insert;
+DROP TRIGGER articles_fts_delete;
+DROP TRIGGER articles_fts_update;
+
+UPDATE pages SET content_html = content;
+UPDATE articles SET content_html = content;
+
+DROP TABLE pages_fts;
+DROP TABLE articles_fts;
+
+CREATE VIRTUAL TABLE pages_fts USING fts5(
+    title,
+    content_html,
+    content=pages,
+    content_rowid=rowid
+);
+
+CREATE VIRTUAL TABLE articles_fts USING fts5(
+    title,
+    short_text,
+    content_html,
+    content=articles,
+    content_rowid=rowid
+);
+
+INSERT INTO pages_fts (pages_fts) VALUES ('rebuild');
+INSERT INTO articles_fts (articles_fts) VALUES ('rebuild');
+
+CREATE TRIGGER pages_fts_insert AFTER INSERT ON pages BEGIN
+    INSERT INTO pages_fts (rowid, title, content_html)
+    VALUES (new.rowid, new.title, new.content_html);
+END;
+
+CREATE TRIGGER pages_fts_delete AFTER DELETE ON pages BEGIN
+    INSERT INTO pages_fts (pages_fts, rowid, title, content_html)
+    VALUES ('delete', old.rowid, old.title, old.content_html);
+END;
+
+CREATE TRIGGER pages_fts_update AFTER UPDATE ON pages BEGIN
+    INSERT INTO pages_fts (pages_fts, rowid, title, content_html)
+    VALUES ('delete', old.rowid, old.title, old.content_html);
+    INSERT INTO pages_fts (rowid, title, content_html)
+    VALUES (new.rowid, new.title, new.content_html);
+END;
+
+CREATE TRIGGER articles_fts_insert AFTER INSERT ON articles BEGIN
+    INSERT INTO articles_fts (rowid, title, short_text, content_html)
+    VALUES (new.rowid, new.title, new.short_text, new.content_html);
+END;
+
+CREATE TRIGGER articles_fts_delete AFTER DELETE ON articles BEGIN
+    INSERT INTO articles_fts (articles_fts, rowid, title, short_text, content_html)
+    VALUES ('delete', old.rowid, old.title, old.short_text, old.content_html);
+END;
+
+CREATE TRIGGER articles_fts_update AFTER UPDATE ON articles BEGIN
+    INSERT INTO articles_fts (articles_fts, rowid, title, short_text, content_html)
+    VALUES ('delete', old.rowid, old.title, old.short_text, old.content_html);
+    INSERT INTO articles_fts (rowid, title, short_text, content_html)
+    VALUES (new.rowid, new.title, new.short_text, new.content_html);
+END;
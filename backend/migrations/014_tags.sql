@@ -0,0 +1,24 @@
+-- Free-form tags on pages and articles. Unlike categories, which are curated
+-- up front, tags are created the first time an editor assigns them.
+
+CREATE TABLE tags (
+    id         TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    name       TEXT NOT NULL,
+    slug       TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE TABLE page_tags (
+    page_id TEXT NOT NULL REFERENCES pages(id) ON DELETE CASCADE,
+    tag_id  TEXT NOT NULL REFERENCES tags(id)  ON DELETE CASCADE,
+    PRIMARY KEY (page_id, tag_id)
+);
+
+CREATE TABLE article_tags (
+    article_id TEXT NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+    tag_id     TEXT NOT NULL REFERENCES tags(id)     ON DELETE CASCADE,
+    PRIMARY KEY (article_id, tag_id)
+);
+
+CREATE INDEX idx_page_tags_tag ON page_tags(tag_id);
+CREATE INDEX idx_article_tags_tag ON article_tags(tag_id);
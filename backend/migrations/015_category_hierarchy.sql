@@ -0,0 +1,13 @@
+-- Hierarchical categories. A category may sit below a parent, carries an
+-- optional description and cover image, and has an explicit position among
+-- its siblings. Listing content by a category includes its descendants.
+--
+-- Deleting a parent does not take its children with it: the service moves
+-- them up a level first, so ON DELETE SET NULL only matters as a safety net.
+
+ALTER TABLE categories ADD COLUMN parent_id TEXT REFERENCES categories(id) ON DELETE SET NULL;
+ALTER TABLE categories ADD COLUMN description TEXT NOT NULL DEFAULT '';
+ALTER TABLE categories ADD COLUMN cover_image_id TEXT REFERENCES media(id) ON DELETE SET NULL;
+ALTER TABLE categories ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_categories_parent ON categories(parent_id);
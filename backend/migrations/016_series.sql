@@ -0,0 +1,20 @@
+-- Article series: an ordered list of articles with its own title, slug and
+-- description, for multi-part tutorials and the like. An article belongs to
+-- at most one series.
+
+CREATE TABLE series (
+    id          TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    title       TEXT NOT NULL,
+    slug        TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    created_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    updated_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE TABLE series_articles (
+    article_id TEXT PRIMARY KEY REFERENCES articles(id) ON DELETE CASCADE,
+    series_id  TEXT NOT NULL REFERENCES series(id) ON DELETE CASCADE,
+    position   INTEGER NOT NULL
+);
+
+CREATE INDEX idx_series_articles_series ON series_articles(series_id, position);
@@ -0,0 +1,31 @@
+-- Credited authors and public author profiles.
+--
+-- `articles.author_id` stays the user who created the article; the people
+-- credited on the byline live in `article_authors`, in display order. Only
+-- users credited on a published article are ever shown publicly, through
+-- their profile.
+
+CREATE TABLE author_profiles (
+    user_id    TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    slug       TEXT NOT NULL UNIQUE,
+    bio        TEXT NOT NULL DEFAULT '',
+    avatar_id  TEXT REFERENCES media(id) ON DELETE SET NULL,
+    -- JSON array of {"label": ..., "url": ...} objects.
+    links      TEXT NOT NULL DEFAULT '[]',
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE TABLE article_authors (
+    article_id TEXT NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+    user_id    TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    position   INTEGER NOT NULL,
+    PRIMARY KEY (article_id, user_id)
+);
+
+CREATE INDEX idx_article_authors_user ON article_authors(user_id);
+
+-- Existing articles credit their creator. Profiles are not created here:
+-- their slugs derive from display names, which SQL cannot slugify, so the
+-- server creates them at startup for credited users who have none.
+INSERT INTO article_authors (article_id, user_id, position)
+SELECT id, author_id, 0 FROM articles;
@@ -0,0 +1,21 @@
+-- Editors may only change pages and articles they wrote, unless the author or
+-- an admin adds them as a collaborator. Admins can change everything.
+
+CREATE TABLE page_collaborators (
+    page_id    TEXT NOT NULL REFERENCES pages(id) ON DELETE CASCADE,
+    user_id    TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    granted_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    PRIMARY KEY (page_id, user_id)
+);
+
+CREATE TABLE article_collaborators (
+    article_id TEXT NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+    user_id    TEXT NOT NULL REFERENCES users(id)    ON DELETE CASCADE,
+    granted_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    PRIMARY KEY (article_id, user_id)
+);
+
+CREATE INDEX idx_page_collaborators_user ON page_collaborators(user_id);
+CREATE INDEX idx_article_collaborators_user ON article_collaborators(user_id);
@@ -0,0 +1,61 @@
+-- Roles backed by a permission matrix. A role is a named set of permissions
+-- (see auth::permissions for the full list); the built-in roles below cannot
+-- be changed, and admins can define further roles through the API.
+--
+-- The `can_review` flag is folded into the roles: editors who could review
+-- become `editor` (publish any content), the others `contributor` (drafts
+-- only). Managing categories, menus, apps and the audit log now takes the
+-- `editor` role or a custom role that grants it.
+--
+-- `users` is rebuilt to drop the old CHECK constraint and the flag. As in
+-- 007, this relies on migrations running with foreign key enforcement off.
+
+CREATE TABLE roles (
+    name        TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    -- JSON array of permission names.
+    permissions TEXT NOT NULL DEFAULT '[]',
+    builtin     INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    updated_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+-- Admins always hold every permission, whatever is stored here.
+INSERT INTO roles (name, description, permissions, builtin) VALUES
+    ('admin', 'Full access, including users, roles and settings', '[]', 1),
+    ('editor', 'Publishes anyone''s content and manages the site structure',
+     '["content.view","content.create","content.edit_own","content.publish_own",'
+     || '"content.publish_any","taxonomy.manage","media.upload","media.delete",'
+     || '"menus.manage","apps.manage","audit.view"]', 1),
+    ('author', 'Writes and publishes their own content',
+     '["content.view","content.create","content.edit_own","content.publish_own",'
+     || '"taxonomy.manage","media.upload"]', 1),
+    ('contributor', 'Writes drafts and submits them for review',
+     '["content.view","content.create","content.edit_own","media.upload"]', 1),
+    ('viewer', 'Read-only access to the admin area', '["content.view"]', 1);
+
+CREATE TABLE users_new (
+    id           TEXT    PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    -- Opaque identifier from the OAuth2 provider (Authentik subject claim).
+    external_id  TEXT    NOT NULL UNIQUE,
+    email        TEXT    NOT NULL,
+    display_name TEXT    NOT NULL,
+    role         TEXT    NOT NULL REFERENCES roles(name),
+    created_at   TEXT    NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    last_login   TEXT    NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+INSERT INTO users_new (id, external_id, email, display_name, role, created_at, last_login)
+SELECT id, external_id, email, display_name,
+       CASE
+           WHEN role = 'admin' THEN 'admin'
+           WHEN can_review THEN 'editor'
+           ELSE 'contributor'
+       END,
+       created_at, last_login
+FROM users;
+
+DROP TABLE users;
+ALTER TABLE users_new RENAME TO users;
+
+CREATE INDEX idx_users_role ON users(role);
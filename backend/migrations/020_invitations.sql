@@ -0,0 +1,37 @@
+-- Registration policy and invitations.
+--
+-- `registration_policy` decides who gets an account on first login:
+--   open             — anyone the identity provider lets in
+--   invite_only      — only people with a pending invitation
+--   domain_allowlist — invitations, plus verified emails from the domains in
+--                      `registration_allowed_domains` (comma-separated)
+-- Existing users can always log in. The very first user becomes admin
+-- whatever the policy says. Users admitted without an invitation get
+-- `registration_default_role`.
+--
+-- The defaults keep what earlier versions did: anyone may sign up, as an
+-- editor.
+
+INSERT OR IGNORE INTO site_settings (key, value) VALUES
+    ('registration_policy',          'open'),
+    ('registration_allowed_domains', ''),
+    ('registration_default_role',    'editor');
+
+-- An invitation gives the person with this email address the role on their
+-- first login. Claimed invitations are kept as a record of who joined how,
+-- so `role` is not a foreign key; roles with pending invitations cannot be
+-- deleted.
+CREATE TABLE invitations (
+    id         TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    -- Stored lowercased.
+    email      TEXT NOT NULL,
+    role       TEXT NOT NULL,
+    invited_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    claimed_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    claimed_at TEXT
+);
+
+-- At most one pending invitation per address.
+CREATE UNIQUE INDEX idx_invitations_pending_email
+    ON invitations(email) WHERE claimed_at IS NULL;
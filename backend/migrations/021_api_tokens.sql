@@ -0,0 +1,24 @@
+-- Personal API tokens and service accounts.
+--
+-- A token acts as its user, within both the user's role and the token's
+-- scopes (see auth::api_tokens). Service accounts are users that only ever
+-- authenticate with tokens; their `external_id` is a random `service:` value
+-- that no identity provider issues.
+
+ALTER TABLE users ADD COLUMN is_service INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE api_tokens (
+    id           TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    user_id      TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name         TEXT NOT NULL,
+    -- SHA-256 hash of the token, as for sessions.
+    token        TEXT NOT NULL UNIQUE,
+    -- JSON array of scope names.
+    scopes       TEXT NOT NULL DEFAULT '[]',
+    created_by   TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at   TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    expires_at   TEXT NOT NULL,
+    last_used_at TEXT
+);
+
+CREATE INDEX idx_api_tokens_user ON api_tokens(user_id);
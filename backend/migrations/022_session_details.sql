@@ -0,0 +1,11 @@
+-- Per-session details for the session list: the browser and address the
+-- session was created from, and when it was last used. `ip` is the peer
+-- address, or the client address reported by a trusted reverse proxy.
+
+ALTER TABLE sessions ADD COLUMN user_agent   TEXT NOT NULL DEFAULT '';
+ALTER TABLE sessions ADD COLUMN ip           TEXT NOT NULL DEFAULT '';
+ALTER TABLE sessions ADD COLUMN last_seen_at TEXT;
+
+UPDATE sessions SET last_seen_at = created_at;
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
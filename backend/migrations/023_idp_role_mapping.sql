@@ -0,0 +1,12 @@
+-- Identity provider role mapping.
+--
+-- `idp_role_mappings` is a JSON list of rules tried in order on every login:
+--   [{"claim": "groups", "value": "pawtal-admins", "role": "admin"}]
+-- The first rule whose claim equals (or, for a list, contains) the value
+-- gives the user its role. An empty list turns mapping off.
+-- `idp_role_mapping_required` ('true'/'false') refuses logins that match no
+-- rule; otherwise such users keep the role they have.
+
+INSERT OR IGNORE INTO site_settings (key, value) VALUES
+    ('idp_role_mappings',         '[]'),
+    ('idp_role_mapping_required', 'false');
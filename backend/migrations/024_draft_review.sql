@@ -0,0 +1,13 @@
+-- Review of draft overlays.
+--
+-- Edits to published content live in a draft overlay rather than on the row,
+-- so submitting them for review cannot move the row to `in_review` without
+-- taking the live version offline. Instead the draft itself is flagged as in
+-- review; approving it publishes the draft, rejecting it clears the flag and
+-- leaves the reviewer's comment on the draft.
+
+ALTER TABLE page_drafts ADD COLUMN in_review      INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE page_drafts ADD COLUMN review_comment TEXT;
+
+ALTER TABLE article_drafts ADD COLUMN in_review      INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE article_drafts ADD COLUMN review_comment TEXT;
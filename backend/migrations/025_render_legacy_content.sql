@@ -0,0 +1,13 @@
+-- Legacy content was copied into `content_html` unsanitized (see 013).
+--
+-- Rows whose rendered HTML is still a verbatim copy of their HTML source are
+-- cleared here; at startup the server renders every row with an empty
+-- `content_html` under the current sanitization policy, before serving it.
+-- Rows saved since 013 whose source was already clean are rendered again to
+-- the same HTML.
+
+UPDATE pages SET content_html = ''
+WHERE content_format = 'html' AND content_html = content AND content != '';
+
+UPDATE articles SET content_html = ''
+WHERE content_format = 'html' AND content_html = content AND content != '';
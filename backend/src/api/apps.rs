@@ -15,12 +15,15 @@
 
 use axum::{
     extract::{Extension, Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
     Json,
 };
 
 use crate::db::models::{App, CreateApp, PaginatedResponse, PaginationParams, UpdateApp, User};
 use crate::error::AppResult;
 use crate::services::apps as svc;
+use crate::services::concurrency;
 use crate::AppState;
 
 // ─── Public endpoints ─────────────────────────────────────────────────────────
@@ -64,26 +67,29 @@ pub async fn admin_create(
 
 /// `GET /api/admin/apps/:id`
 ///
-/// Fetches a single app by its ID.
+/// Fetches a single app by its ID, with its version as the `ETag`.
 pub async fn admin_get(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> AppResult<Json<App>> {
+) -> AppResult<impl IntoResponse> {
     let app = svc::get_app(&state.db, &id).await?;
-    Ok(Json(app))
+    Ok((concurrency::etag_header(app.updated_at), Json(app)))
 }
 
 /// `PUT /api/admin/apps/:id`
 ///
-/// Applies a partial update to an existing app.
+/// Applies a partial update to an existing app. Honours `If-Match` /
+/// `expected_updated_at` like the page and article updates.
 pub async fn admin_update(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path(id): Path<String>,
-    Json(input): Json<UpdateApp>,
-) -> AppResult<Json<App>> {
+    headers: HeaderMap,
+    Json(mut input): Json<UpdateApp>,
+) -> AppResult<impl IntoResponse> {
+    input.expected_updated_at = concurrency::expected_version(&headers, input.expected_updated_at)?;
     let app = svc::update_app(&state.db, &id, input, &user.id).await?;
-    Ok(Json(app))
+    Ok((concurrency::etag_header(app.updated_at), Json(app)))
 }
 
 /// `DELETE /api/admin/apps/:id`
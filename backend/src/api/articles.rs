@@ -16,21 +16,38 @@
 //!     GET    /api/admin/articles/:id
 //!     PUT    /api/admin/articles/:id
 //!     DELETE /api/admin/articles/:id
-//!     POST   /api/admin/articles/:id/publish
+//!     POST   /api/admin/articles/:id/submit
 //!     POST   /api/admin/articles/:id/restore
+//!     DELETE /api/admin/articles/:id/draft
 //!     GET    /api/admin/articles/:id/revisions
+//!     GET    /api/admin/articles/:id/revisions/:a/diff/:b
 //!     POST   /api/admin/articles/:id/revisions/:rev_id/restore
+//!     PUT    /api/admin/articles/:id/revisions/:rev_id/pin
+//!     DELETE /api/admin/articles/:id/revisions/:rev_id/pin
+//!     GET    /api/admin/articles/:id/collaborators
+//!     POST   /api/admin/articles/:id/collaborators
+//!     DELETE /api/admin/articles/:id/collaborators/:user_id
+//!
+//!   Reviewer (content.publish_own or content.publish_any permission):
+//!     POST   /api/admin/articles/:id/publish
+//!     POST   /api/admin/articles/:id/approve
+//!     POST   /api/admin/articles/:id/reject
 
 use axum::{
     extract::{Extension, Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
     Json,
 };
 use serde::Deserialize;
 
-use crate::db::models::{Article, ArticleRevision, CreateArticle, PaginatedResponse,
-    PaginationParams, UpdateArticle, User};
+use crate::db::models::{AddCollaborator, Article, ArticleRevision, ArticleWithDraft, Collaborator,
+    CreateArticle, PaginatedResponse, PaginationParams, PinRevision, PublicArticle,
+    PublishedArticle, RejectContent, UpdateArticle, User};
 use crate::error::AppResult;
-use crate::services::articles as svc;
+use crate::services::{concurrency, ownership};
+use crate::services::diff::{DiffOptions, RevisionDiff};
+use crate::services::articles::{self as svc, ArticleFilter};
 use crate::AppState;
 
 // ─── Query parameter structs ──────────────────────────────────────────────────
@@ -41,40 +58,59 @@ pub struct StatusFilter {
     pub status: Option<String>,
 }
 
+/// Optional `?tag=`, `?category=` and `?author=` filters (slugs) used by the
+/// public list endpoint.
+#[derive(Debug, Deserialize)]
+pub struct ListFilter {
+    pub tag: Option<String>,
+    pub category: Option<String>,
+    pub author: Option<String>,
+}
+
 // ─── Public endpoints ─────────────────────────────────────────────────────────
 
 /// `GET /api/articles`
 ///
-/// Returns a paginated list of published articles, ordered newest first.
-/// Unauthenticated — only published content is visible.
+/// Returns a paginated list of published articles, ordered newest first,
+/// optionally only those tagged `?tag=<slug>`, filed under `?category=<slug>`
+/// and/or credited to `?author=<slug>`. Unauthenticated — only published
+/// content is visible.
 pub async fn public_list(
     State(state): State<AppState>,
     Query(pagination): Query<PaginationParams>,
-) -> AppResult<Json<PaginatedResponse<Article>>> {
-    let result = svc::list_published_articles(&state.db, &pagination).await?;
+    Query(filter): Query<ListFilter>,
+) -> AppResult<Json<PaginatedResponse<PublishedArticle>>> {
+    let filter = ArticleFilter {
+        tag: filter.tag.as_deref(),
+        category: filter.category.as_deref(),
+        author: filter.author.as_deref(),
+    };
+    let result = svc::list_published_articles(&state.db, &pagination, filter).await?;
     Ok(Json(result))
 }
 
 /// `GET /api/articles/:slug`
 ///
-/// Returns a published article by its URL slug. Unauthenticated — only
-/// published content is visible.
+/// Returns a published article, with its authors, categories, tags and
+/// series, by its URL slug. A slug the article used in the past answers with
+/// a 301 to the current slug. Unauthenticated — only published content is
+/// visible.
 pub async fn public_get_by_slug(
     State(state): State<AppState>,
     Path(slug): Path<String>,
-) -> AppResult<Json<Article>> {
-    let article = svc::get_article_by_slug(&state.db, &slug).await?;
+) -> AppResult<Json<PublicArticle>> {
+    let article = svc::get_public_article(&state.db, &slug).await?;
     Ok(Json(article))
 }
 
 /// `GET /api/articles/:slug/related`
 ///
-/// Returns up to 4 published articles that share at least one category with
-/// the requested article. Unauthenticated — only published content is visible.
+/// Returns up to 4 published articles that share at least one category or tag
+/// with the requested article. Unauthenticated — only published content is visible.
 pub async fn public_related(
     State(state): State<AppState>,
     Path(slug): Path<String>,
-) -> AppResult<Json<Vec<Article>>> {
+) -> AppResult<Json<Vec<PublishedArticle>>> {
     let article = svc::get_article_by_slug(&state.db, &slug).await?;
     let related = svc::get_related_articles(&state.db, &article.id, 4).await?;
     Ok(Json(related))
@@ -96,13 +132,15 @@ pub async fn admin_list(
 
 /// `GET /api/admin/articles/:id`
 ///
-/// Fetches a single article by its ID.
+/// Fetches a single article by its ID. The live version is returned at the top
+/// level, with any pending draft under `draft`. The `ETag` identifies the
+/// version for conditional updates.
 pub async fn admin_get(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> AppResult<Json<Article>> {
-    let article = svc::get_article(&state.db, &id).await?;
-    Ok(Json(article))
+) -> AppResult<impl IntoResponse> {
+    let article = svc::get_article_with_draft(&state.db, &id).await?;
+    Ok((concurrency::etag_header(article.article.updated_at), Json(article)))
 }
 
 /// `POST /api/admin/articles`
@@ -113,21 +151,31 @@ pub async fn admin_create(
     Extension(user): Extension<User>,
     Json(input): Json<CreateArticle>,
 ) -> AppResult<Json<Article>> {
-    let article = svc::create_article(&state.db, input, &user.id).await?;
+    let article = svc::create_article(&state.db, input, &user).await?;
+    state.scheduler.reschedule();
     Ok(Json(article))
 }
 
 /// `PUT /api/admin/articles/:id`
 ///
-/// Applies a partial update to an existing article.
+/// Applies a partial update to an existing article. Content edits to a published
+/// article are saved as a draft and leave the live version untouched.
+///
+/// Send the version from `admin_get` in `If-Match` (or `expected_updated_at`)
+/// to get a 409 with the current version instead of overwriting someone
+/// else's changes.
 pub async fn admin_update(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path(id): Path<String>,
-    Json(input): Json<UpdateArticle>,
-) -> AppResult<Json<Article>> {
-    let article = svc::update_article(&state.db, &id, input, &user.id).await?;
-    Ok(Json(article))
+    headers: HeaderMap,
+    Json(mut input): Json<UpdateArticle>,
+) -> AppResult<impl IntoResponse> {
+    input.expected_updated_at = concurrency::expected_version(&headers, input.expected_updated_at)?;
+    svc::update_article(&state.db, &id, input, &user).await?;
+    state.scheduler.reschedule();
+    let article = svc::get_article_with_draft(&state.db, &id).await?;
+    Ok((concurrency::etag_header(article.article.updated_at), Json(article)))
 }
 
 /// `DELETE /api/admin/articles/:id`
@@ -138,7 +186,7 @@ pub async fn admin_delete(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    svc::trash_article(&state.db, &id, &user.id).await?;
+    svc::trash_article(&state.db, &id, &user).await?;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -150,7 +198,46 @@ pub async fn admin_publish(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> AppResult<Json<Article>> {
-    let article = svc::publish_article(&state.db, &id, &user.id).await?;
+    let article = svc::publish_article(&state.db, &id, &user).await?;
+    state.scheduler.reschedule();
+    Ok(Json(article))
+}
+
+/// `POST /api/admin/articles/:id/submit`
+///
+/// Submits a draft article for editorial review.
+pub async fn admin_submit(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Article>> {
+    let article = svc::submit_article(&state.db, &id, &user).await?;
+    Ok(Json(article))
+}
+
+/// `POST /api/admin/articles/:id/approve`
+///
+/// Approves an article in review, publishing or scheduling it.
+pub async fn admin_approve(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Article>> {
+    let article = svc::approve_article(&state.db, &id, &user).await?;
+    state.scheduler.reschedule();
+    Ok(Json(article))
+}
+
+/// `POST /api/admin/articles/:id/reject`
+///
+/// Rejects an article in review back to `draft`, attaching the reviewer's comment.
+pub async fn admin_reject(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<RejectContent>,
+) -> AppResult<Json<Article>> {
+    let article = svc::reject_article(&state.db, &id, &input.comment, &user).await?;
     Ok(Json(article))
 }
 
@@ -162,7 +249,21 @@ pub async fn admin_restore(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> AppResult<Json<Article>> {
-    let article = svc::restore_article(&state.db, &id, &user.id).await?;
+    let article = svc::restore_article(&state.db, &id, &user).await?;
+    Ok(Json(article))
+}
+
+/// `DELETE /api/admin/articles/:id/draft`
+///
+/// Discards the pending draft of a published article. The live version is
+/// unchanged.
+pub async fn admin_discard_draft(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ArticleWithDraft>> {
+    svc::discard_draft(&state.db, &id, &user).await?;
+    let article = svc::get_article_with_draft(&state.db, &id).await?;
     Ok(Json(article))
 }
 
@@ -177,6 +278,20 @@ pub async fn admin_revisions(
     Ok(Json(revisions))
 }
 
+/// `GET /api/admin/articles/:id/revisions/:a/diff/:b`
+///
+/// Diffs revision `a` against revision `b` (either may be `current`), and `b`
+/// against the current version. `?format=html` diffs raw markup instead of
+/// visible text; `?granularity=block` compares whole blocks instead of words.
+pub async fn admin_revision_diff(
+    State(state): State<AppState>,
+    Path((id, from, to)): Path<(String, String, String)>,
+    Query(options): Query<DiffOptions>,
+) -> AppResult<Json<RevisionDiff>> {
+    let diff = svc::diff_revisions(&state.db, &id, &from, &to, options).await?;
+    Ok(Json(diff))
+}
+
 /// `POST /api/admin/articles/:id/revisions/:rev_id/restore`
 ///
 /// Restores an article to a previously captured revision state. Creates a new
@@ -185,7 +300,75 @@ pub async fn admin_restore_revision(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path((id, rev_id)): Path<(String, String)>,
-) -> AppResult<Json<Article>> {
-    let article = svc::restore_revision(&state.db, &id, &rev_id, &user.id).await?;
+) -> AppResult<Json<ArticleWithDraft>> {
+    svc::restore_revision(&state.db, &id, &rev_id, &user).await?;
+    state.scheduler.reschedule();
+    let article = svc::get_article_with_draft(&state.db, &id).await?;
     Ok(Json(article))
 }
+
+/// `PUT /api/admin/articles/:id/revisions/:rev_id/pin`
+///
+/// Pins a revision as a named version so retention never removes it.
+pub async fn admin_pin_revision(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, rev_id)): Path<(String, String)>,
+    Json(input): Json<PinRevision>,
+) -> AppResult<Json<ArticleRevision>> {
+    let revision = svc::pin_revision(&state.db, &id, &rev_id, &input.name, &user).await?;
+    Ok(Json(revision))
+}
+
+/// `DELETE /api/admin/articles/:id/revisions/:rev_id/pin`
+///
+/// Removes a revision's version name.
+pub async fn admin_unpin_revision(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, rev_id)): Path<(String, String)>,
+) -> AppResult<Json<ArticleRevision>> {
+    let revision = svc::unpin_revision(&state.db, &id, &rev_id, &user).await?;
+    Ok(Json(revision))
+}
+
+/// `GET /api/admin/articles/:id/collaborators`
+///
+/// Lists the users given edit access to the article besides its authors.
+pub async fn admin_collaborators(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<Collaborator>>> {
+    svc::get_article(&state.db, &id).await?;
+    let collaborators = ownership::list_collaborators(&state.db, "article", &id).await?;
+    Ok(Json(collaborators))
+}
+
+/// `POST /api/admin/articles/:id/collaborators`
+///
+/// Gives another user edit access to the article. Authors and admins only.
+pub async fn admin_add_collaborator(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<AddCollaborator>,
+) -> AppResult<Json<Vec<Collaborator>>> {
+    svc::get_article(&state.db, &id).await?;
+    let collaborators =
+        ownership::add_collaborator(&state.db, "article", &id, &input.user_id, &user).await?;
+    Ok(Json(collaborators))
+}
+
+/// `DELETE /api/admin/articles/:id/collaborators/:user_id`
+///
+/// Takes a collaborator's edit access away. Authors and admins only.
+pub async fn admin_remove_collaborator(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, user_id)): Path<(String, String)>,
+) -> AppResult<Json<Vec<Collaborator>>> {
+    svc::get_article(&state.db, &id).await?;
+    let collaborators =
+        ownership::remove_collaborator(&state.db, "article", &id, &user_id, &user).await?;
+    Ok(Json(collaborators))
+}
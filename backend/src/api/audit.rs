@@ -1,7 +1,8 @@
 //! HTTP handlers for the audit log resource.
 //!
-//! The audit log is append-only and admin-only. Entries are returned newest
-//! first so the most recent activity is immediately visible.
+//! The audit log is append-only and readable with the `audit.view`
+//! permission. Entries are returned newest first so the most recent activity
+//! is immediately visible.
 //!
 //! Route map (registered in main.rs):
 //!
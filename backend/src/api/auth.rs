@@ -2,26 +2,31 @@
 //!
 //! Flow:
 //!   GET  /api/auth/login    → redirect browser to IdP
-//!   GET  /api/auth/callback → exchange code, upsert user, set cookie, redirect to /admin
+//!   GET  /api/auth/callback → exchange code, map claims to a role, sign in or register,
+//!                             set cookie, redirect to /admin
 //!   POST /api/auth/logout   → clear cookie, delete session, redirect to /
 //!   GET  /api/admin/me      → return current user (protected by require_auth middleware)
 //!
-//! User management (admin-only):
+//! User management (users.manage permission):
 //!   GET  /api/admin/users
 //!   PUT  /api/admin/users/:id/role
+//!
+//! Invitations live in `api::invitations`.
+
+use std::net::{IpAddr, SocketAddr};
 
 use axum::{
-    extract::{Extension, Path, Query, State},
+    extract::{ConnectInfo, Extension, Path, Query, State},
     http::header,
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Deserialize;
-use uuid::Uuid;
-
-use crate::auth::oauth2::{build_auth_url, discover_oidc, exchange_code, fetch_userinfo};
-use crate::auth::session::{create_session, delete_session};
+use crate::auth::oauth2::{build_auth_url, exchange_code, fetch_userinfo, AuthRequest};
+use crate::auth::session::{create_session, delete_session, session_set_cookie, session_token};
 use crate::db::models::User;
+use crate::services::registration::{self, Identity};
+use crate::services::{role_mapping, roles};
 use crate::error::{AppError, AppResult};
 use crate::AppState;
 
@@ -35,19 +40,23 @@ pub struct CallbackParams {
 /// `GET /api/auth/login`
 ///
 /// Discovers the OIDC provider endpoints, builds the authorization URL, and
-/// redirects the browser to the IdP's login page. The CSRF `state` parameter
-/// is stored in a short-lived HttpOnly cookie and verified in the callback.
+/// redirects the browser to the IdP's login page. The CSRF `state`, the nonce
+/// and the PKCE verifier are stored in a short-lived, signed HttpOnly cookie
+/// and checked in the callback.
 pub async fn login(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
-    let discovery = discover_oidc(&state.http_client, &state.config.oauth2_issuer_url).await?;
+    let discovery = state
+        .oidc
+        .discovery(&state.http_client, &state.config.oauth2_issuer_url)
+        .await?;
 
-    let csrf_state = Uuid::new_v4().to_string();
-    let auth_url = build_auth_url(&discovery, &state.config, &csrf_state);
+    let request = AuthRequest::generate();
+    let auth_url = build_auth_url(&discovery, &state.config, &request);
 
-    // Store the CSRF state in a short-lived HttpOnly cookie so we can verify
-    // it when the IdP redirects back to our callback endpoint.
+    // Store the request in a short-lived HttpOnly cookie so we can verify it
+    // when the IdP redirects back to our callback endpoint.
     let state_cookie = format!(
         "pawtal_oauth_state={}; HttpOnly; SameSite=Lax; Path=/api/auth/callback; Max-Age=600",
-        csrf_state
+        request.to_cookie(&state.config)
     );
 
     let response = Response::builder()
@@ -63,15 +72,18 @@ pub async fn login(State(state): State<AppState>) -> AppResult<impl IntoResponse
 /// `GET /api/auth/callback`
 ///
 /// Receives the authorization code from the IdP, exchanges it for tokens,
-/// fetches user info, upserts the user record, creates a session, and sets
-/// an HttpOnly session cookie before redirecting to the admin area.
+/// validates the ID token, fetches user info, maps the IdP's claims to a role,
+/// signs the user in (registering them if the registration policy allows),
+/// creates a session, and sets an HttpOnly session cookie before redirecting
+/// to the admin area.
 pub async fn callback(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     Query(params): Query<CallbackParams>,
     headers: axum::http::HeaderMap,
 ) -> AppResult<impl IntoResponse> {
     // Verify the CSRF state parameter matches the cookie we set during login.
-    let stored_state = headers
+    let request = headers
         .get("cookie")
         .and_then(|v| v.to_str().ok())
         .and_then(|cookies| {
@@ -83,17 +95,50 @@ pub async fn callback(
             })
         })
         .ok_or_else(|| AppError::BadRequest("Missing OAuth state cookie".into()))?;
+    let request = AuthRequest::from_cookie(&request, &state.config)
+        .ok_or_else(|| AppError::BadRequest("Invalid OAuth state cookie".into()))?;
 
-    if params.state != stored_state {
+    if params.state != request.state {
         return Err(AppError::BadRequest("OAuth state mismatch".into()));
     }
-    let discovery = discover_oidc(&state.http_client, &state.config.oauth2_issuer_url).await?;
+    let discovery = state
+        .oidc
+        .discovery(&state.http_client, &state.config.oauth2_issuer_url)
+        .await?;
 
-    // Exchange the authorization code for tokens.
-    let tokens = exchange_code(&state.http_client, &discovery, &state.config, &params.code).await?;
+    // Exchange the authorization code for tokens, proving with the PKCE
+    // verifier that we started this login.
+    let tokens = exchange_code(
+        &state.http_client,
+        &discovery,
+        &state.config,
+        &params.code,
+        &request.code_verifier,
+    )
+    .await?;
 
-    // Fetch the user's identity claims.
+    // The ID token must be signed by the issuer, for us, and for this login.
+    let id_token = tokens
+        .id_token
+        .as_deref()
+        .ok_or_else(|| AppError::BadRequest("Token response has no id_token".into()))?;
+    let claims = state
+        .oidc
+        .verify_id_token(&state.http_client, &discovery, &state.config, id_token, &request.nonce)
+        .await?;
+
+    // Fetch the user's identity claims, which must describe the same subject.
     let userinfo = fetch_userinfo(&state.http_client, &discovery, &tokens.access_token).await?;
+    if userinfo.sub != claims.sub {
+        return Err(AppError::BadRequest("Userinfo subject does not match the ID token".into()));
+    }
+
+    // Map the provider's claims to a role; userinfo wins over the ID token.
+    // This refuses the login when a mapping is required and none matches,
+    // unless the user is the last admin.
+    let mut idp_claims = claims.raw;
+    idp_claims.extend(userinfo.raw);
+    let role = role_mapping::resolve(&state.db, &idp_claims, &userinfo.sub).await?;
 
     // Derive a display name: prefer `name`, fall back to `preferred_username`,
     // then to the `sub` identifier so the field is never empty.
@@ -102,50 +147,29 @@ pub async fn callback(
         .or(userinfo.preferred_username)
         .unwrap_or_else(|| userinfo.sub.clone());
 
-    let email = userinfo.email.unwrap_or_default();
+    let identity = Identity {
+        external_id: userinfo.sub,
+        email: userinfo.email.unwrap_or_default(),
+        display_name,
+        email_verified: userinfo.email_verified == Some(true),
+        role,
+    };
 
-    // Determine the role: the very first user gets "admin", everyone after gets "editor".
-    let user_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
-        .fetch_one(&state.db)
-        .await?;
-    let role = if user_count == 0 { "admin" } else { "editor" };
-
-    // Upsert the user. On conflict (same external_id) we update mutable fields
-    // so changes on the IdP side (new email, display name) are reflected here.
-    // We use RETURNING to get the internal UUID without a second query.
-    let user_id = sqlx::query_scalar::<_, String>(
-        r#"
-        INSERT INTO users (id, external_id, email, display_name, role, last_login)
-        VALUES (?, ?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
-        ON CONFLICT(external_id) DO UPDATE SET
-            email        = excluded.email,
-            display_name = excluded.display_name,
-            last_login   = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
-        RETURNING id
-        "#,
-    )
-    .bind(Uuid::new_v4().to_string())
-    .bind(&userinfo.sub)
-    .bind(&email)
-    .bind(&display_name)
-    .bind(role)
-    .fetch_one(&state.db)
-    .await?;
+    // Known users are refreshed, including their mapped role; new ones are
+    // admitted (or refused) by the registration policy.
+    let user_id = registration::sign_in(&state.db, &identity).await?;
 
     // Create a session and get the token that will become the cookie value.
-    let session_token = create_session(&state.db, &user_id).await?;
-
-    // Build an HttpOnly, SameSite=Strict, Secure cookie.
-    // Max-Age=604800 matches the 7-day TTL used in session creation.
-    let secure_flag = if state.config.base_url.starts_with("https") {
-        "; Secure"
-    } else {
-        ""
-    };
-    let cookie = format!(
-        "pawtal_session={}; HttpOnly; SameSite=Strict; Path=/; Max-Age=604800{}",
-        session_token, secure_flag
-    );
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let ip = client_ip(&headers, peer, &state.config.trusted_proxies);
+    let session_token = create_session(&state.db, &user_id, user_agent, &ip).await?;
+
+    // Build a signed HttpOnly, SameSite=Strict, Secure cookie. Max-Age matches
+    // the session's maximum lifetime; the server enforces the idle timeout.
+    let cookie = session_set_cookie(&session_token, &state.config);
 
     // Clear the OAuth state cookie now that it has been verified.
     let clear_state_cookie =
@@ -164,6 +188,33 @@ pub async fn callback(
     Ok(response)
 }
 
+/// The client's address. `X-Forwarded-For` is only read when the peer is one
+/// of the `trusted` proxies: each proxy appends the address it saw, so walking
+/// the header from the right, the first hop that is not a trusted proxy is the
+/// client. Anything left of it was sent by the client and is ignored.
+fn client_ip(headers: &axum::http::HeaderMap, peer: SocketAddr, trusted: &[IpAddr]) -> String {
+    let mut client = peer.ip();
+    if trusted.contains(&client) {
+        let hops: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .collect();
+        for hop in hops.into_iter().rev() {
+            let Ok(ip) = hop.parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !trusted.contains(&ip) {
+                break;
+            }
+        }
+    }
+    client.to_string()
+}
+
 /// `POST /api/auth/logout`
 ///
 /// Deletes the server-side session and clears the cookie by sending an expired
@@ -175,16 +226,10 @@ pub async fn logout(
     // Extract the session cookie if present so we can delete the server-side
     // record. If the cookie is absent (already logged out), we still redirect
     // cleanly — there's nothing to clean up.
-    if let Some(cookie_header) = headers.get("cookie").and_then(|v| v.to_str().ok()) {
-        for pair in cookie_header.split(';') {
-            let pair = pair.trim();
-            if let Some(("pawtal_session", token)) = pair.split_once('=') {
-                // Best-effort — if this fails (e.g. session already gone) we
-                // still want to clear the cookie and redirect.
-                let _ = delete_session(&state.db, token.trim()).await;
-                break;
-            }
-        }
+    if let Some(token) = session_token(&headers, &state.config) {
+        // Best-effort — if this fails (e.g. session already gone) we
+        // still want to clear the cookie and redirect.
+        let _ = delete_session(&state.db, &token).await;
     }
 
     // Overwrite the cookie with an expired one to force the browser to delete it.
@@ -218,10 +263,10 @@ pub struct RoleUpdate {
 
 /// `GET /api/admin/users`
 ///
-/// Returns all registered users. Admin-only.
+/// Returns all registered users.
 pub async fn list_users(State(state): State<AppState>) -> AppResult<Json<Vec<User>>> {
     let users = sqlx::query_as::<_, User>(
-        "SELECT id, external_id, email, display_name, role, created_at, last_login \
+        "SELECT id, external_id, email, display_name, role, created_at, last_login, is_service \
          FROM users \
          ORDER BY created_at ASC",
     )
@@ -233,44 +278,37 @@ pub async fn list_users(State(state): State<AppState>) -> AppResult<Json<Vec<Use
 
 /// `PUT /api/admin/users/:id/role`
 ///
-/// Changes the role of a user. Only "admin" and "editor" are valid roles.
-/// The authenticated admin cannot demote themselves to prevent lockout.
+/// Assigns a built-in or custom role to a user. Users cannot change their
+/// own role, so an admin cannot lock themselves out. A role that takes
+/// permissions away signs the user out of all sessions.
 pub async fn update_user_role(
     State(state): State<AppState>,
     Extension(current_user): Extension<User>,
     Path(id): Path<String>,
     Json(body): Json<RoleUpdate>,
 ) -> AppResult<Json<User>> {
-    // Only admins can change roles (defence-in-depth; also enforced by middleware).
-    if current_user.role != "admin" {
-        return Err(AppError::Forbidden);
-    }
+    let updated = roles::set_user_role(&state.db, &id, &body.role, &current_user).await?;
+    Ok(Json(updated))
+}
 
-    // Validate the role before touching the database.
-    if body.role != "admin" && body.role != "editor" {
-        return Err(AppError::BadRequest(format!(
-            "Invalid role '{}'. Must be 'admin' or 'editor'.",
-            body.role
-        )));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Prevent an admin from demoting themselves — they'd be locked out of the
-    // admin area immediately and would need direct database access to recover.
-    if id == current_user.id && body.role != current_user.role {
-        return Err(AppError::BadRequest(
-            "You cannot change your own role.".to_owned(),
-        ));
-    }
+    #[test]
+    fn forwarded_for_is_only_read_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let peer = SocketAddr::new(proxy, 40000);
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("x-forwarded-for", "6.6.6.6, 203.0.113.7".parse().unwrap());
 
-    let updated = sqlx::query_as::<_, User>(
-        "UPDATE users SET role = ? WHERE id = ? \
-         RETURNING id, external_id, email, display_name, role, created_at, last_login",
-    )
-    .bind(&body.role)
-    .bind(&id)
-    .fetch_optional(&state.db)
-    .await?
-    .ok_or(AppError::NotFound)?;
+        assert_eq!(client_ip(&headers, peer, &[]), "10.0.0.2");
+        assert_eq!(client_ip(&headers, peer, &[proxy]), "203.0.113.7");
 
-    Ok(Json(updated))
+        let outer: IpAddr = "203.0.113.7".parse().unwrap();
+        assert_eq!(client_ip(&headers, peer, &[proxy, outer]), "6.6.6.6");
+
+        headers.insert("x-forwarded-for", "garbage".parse().unwrap());
+        assert_eq!(client_ip(&headers, peer, &[proxy]), "10.0.0.2");
+    }
 }
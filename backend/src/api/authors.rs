@@ -0,0 +1,126 @@
+//! HTTP handlers for credited authors and author profiles.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Public:
+//!     GET  /api/authors
+//!     GET  /api/authors/:slug
+//!     GET  /api/authors/:slug/articles
+//!
+//!   Admin (require_auth middleware applied at router level):
+//!     GET  /api/admin/authors
+//!     GET  /api/admin/profile
+//!     PUT  /api/admin/profile
+//!
+//!   Users (users.manage permission):
+//!     GET  /api/admin/users/:id/profile
+//!     PUT  /api/admin/users/:id/profile
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    Json,
+};
+
+use crate::db::models::{AuthorOption, AuthorProfile, PaginatedResponse, PaginationParams,
+    PublicAuthor, PublishedArticle, UpdateAuthorProfile, User};
+use crate::error::AppResult;
+use crate::services::articles::{self, ArticleFilter};
+use crate::services::authors as svc;
+use crate::AppState;
+
+// ─── Public endpoints ─────────────────────────────────────────────────────────
+
+/// `GET /api/authors`
+///
+/// Returns everyone credited on a published article. Unauthenticated.
+pub async fn public_list(State(state): State<AppState>) -> AppResult<Json<Vec<PublicAuthor>>> {
+    let authors = svc::list_public_authors(&state.db).await?;
+    Ok(Json(authors))
+}
+
+/// `GET /api/authors/:slug`
+///
+/// Returns an author's public profile. 404 unless the author is credited on
+/// a published article. Unauthenticated.
+pub async fn public_get(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> AppResult<Json<PublicAuthor>> {
+    let author = svc::get_public_author(&state.db, &slug).await?;
+    Ok(Json(author))
+}
+
+/// `GET /api/authors/:slug/articles`
+///
+/// Returns a paginated list of the author's published articles, newest
+/// first. Unauthenticated.
+pub async fn public_articles(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(pagination): Query<PaginationParams>,
+) -> AppResult<Json<PaginatedResponse<PublishedArticle>>> {
+    let author = svc::get_public_author(&state.db, &slug).await?;
+    let filter = ArticleFilter {
+        author: Some(&author.slug),
+        ..Default::default()
+    };
+    let result = articles::list_published_articles(&state.db, &pagination, filter).await?;
+    Ok(Json(result))
+}
+
+// ─── Admin endpoints ──────────────────────────────────────────────────────────
+
+/// `GET /api/admin/authors`
+///
+/// Returns every user that can be credited as an author.
+pub async fn list_options(State(state): State<AppState>) -> AppResult<Json<Vec<AuthorOption>>> {
+    let authors = svc::list_author_options(&state.db).await?;
+    Ok(Json(authors))
+}
+
+/// `GET /api/admin/profile`
+///
+/// Returns the current user's author profile.
+pub async fn get_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> AppResult<Json<AuthorProfile>> {
+    let profile = svc::get_profile(&state.db, &user.id).await?;
+    Ok(Json(profile))
+}
+
+/// `PUT /api/admin/profile`
+///
+/// Updates the current user's author profile.
+pub async fn update_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<UpdateAuthorProfile>,
+) -> AppResult<Json<AuthorProfile>> {
+    let profile = svc::update_profile(&state.db, &user.id, input, &user.id).await?;
+    Ok(Json(profile))
+}
+
+/// `GET /api/admin/users/:id/profile`
+///
+/// Returns another user's author profile.
+pub async fn get_for_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<AuthorProfile>> {
+    let profile = svc::get_profile(&state.db, &id).await?;
+    Ok(Json(profile))
+}
+
+/// `PUT /api/admin/users/:id/profile`
+///
+/// Updates another user's author profile.
+pub async fn update_for_user(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<UpdateAuthorProfile>,
+) -> AppResult<Json<AuthorProfile>> {
+    let profile = svc::update_profile(&state.db, &id, input, &user.id).await?;
+    Ok(Json(profile))
+}
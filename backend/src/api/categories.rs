@@ -2,37 +2,100 @@
 //!
 //! Route map (registered in main.rs):
 //!
+//!   Public:
+//!     GET    /api/categories
+//!     GET    /api/categories/:slug
+//!     GET    /api/categories/:slug/articles
+//!     GET    /api/categories/:slug/pages
+//!
 //!   Admin (require_auth middleware applied at router level):
 //!     GET    /api/admin/categories
 //!     POST   /api/admin/categories
 //!     PUT    /api/admin/categories/:id
 //!     DELETE /api/admin/categories/:id
+//!     POST   /api/admin/categories/:id/merge
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use serde::Deserialize;
 
-use crate::db::models::{Category, CreateCategory};
+use crate::db::models::{Category, CategoryUsage, CreateCategory, MergeCategory, Page,
+    PaginatedResponse, PaginationParams, PublishedArticle, UpdateCategory};
 use crate::error::AppResult;
+use crate::services::articles::{self, ArticleFilter};
 use crate::services::categories as svc;
+use crate::services::pages;
 use crate::AppState;
 
-// ─── Input structs ────────────────────────────────────────────────────────────
+// ─── Query parameter structs ──────────────────────────────────────────────────
 
-/// Request body for `PUT /api/admin/categories/:id`.
+/// Optional `?reassign_to=<id>` used when deleting a category.
 #[derive(Debug, Deserialize)]
-pub struct UpdateCategoryInput {
-    pub name: String,
-    pub slug: Option<String>,
+pub struct DeleteParams {
+    pub reassign_to: Option<String>,
+}
+
+// ─── Public endpoints ─────────────────────────────────────────────────────────
+
+/// `GET /api/categories`
+///
+/// Returns all categories with their published page and article counts,
+/// which include content in descendant categories. Unauthenticated.
+pub async fn public_list(State(state): State<AppState>) -> AppResult<Json<Vec<CategoryUsage>>> {
+    let categories = svc::list_public_categories(&state.db).await?;
+    Ok(Json(categories))
+}
+
+/// `GET /api/categories/:slug`
+///
+/// Returns a single category by slug. Unauthenticated.
+pub async fn public_get(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> AppResult<Json<Category>> {
+    let category = svc::get_category_by_slug(&state.db, &slug).await?;
+    Ok(Json(category))
+}
+
+/// `GET /api/categories/:slug/articles`
+///
+/// Returns a paginated list of published articles in the category or its
+/// descendants, newest first. 404 for unknown categories. Unauthenticated.
+pub async fn public_articles(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(pagination): Query<PaginationParams>,
+) -> AppResult<Json<PaginatedResponse<PublishedArticle>>> {
+    let category = svc::get_category_by_slug(&state.db, &slug).await?;
+    let filter = ArticleFilter {
+        category: Some(&category.slug),
+        ..Default::default()
+    };
+    let result = articles::list_published_articles(&state.db, &pagination, filter).await?;
+    Ok(Json(result))
+}
+
+/// `GET /api/categories/:slug/pages`
+///
+/// Returns a paginated list of published pages in the category or its
+/// descendants, ordered by title. 404 for unknown categories. Unauthenticated.
+pub async fn public_pages(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(pagination): Query<PaginationParams>,
+) -> AppResult<Json<PaginatedResponse<Page>>> {
+    let category = svc::get_category_by_slug(&state.db, &slug).await?;
+    let result = pages::list_published_pages_in_category(&state.db, &category.id, &pagination).await?;
+    Ok(Json(result))
 }
 
 // ─── Admin endpoints ──────────────────────────────────────────────────────────
 
 /// `GET /api/admin/categories`
 ///
-/// Returns all categories ordered alphabetically by name.
+/// Returns all categories, ordered by position among siblings and then name.
 pub async fn list(State(state): State<AppState>) -> AppResult<Json<Vec<Category>>> {
     let categories = svc::list_categories(&state.db).await?;
     Ok(Json(categories))
@@ -51,23 +114,41 @@ pub async fn create(
 
 /// `PUT /api/admin/categories/:id`
 ///
-/// Updates the name and optionally the slug of an existing category.
+/// Updates an existing category. The name is required; other fields keep
+/// their values when omitted.
 pub async fn update(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(input): Json<UpdateCategoryInput>,
+    Json(input): Json<UpdateCategory>,
 ) -> AppResult<Json<Category>> {
-    let category = svc::update_category(&state.db, &id, input.name, input.slug).await?;
+    let category = svc::update_category(&state.db, &id, input).await?;
     Ok(Json(category))
 }
 
-/// `DELETE /api/admin/categories/:id`
+/// `DELETE /api/admin/categories/:id[?reassign_to=<id>]`
 ///
-/// Permanently deletes a category. Join-table entries are removed by the DB cascade.
+/// Permanently deletes a category; its children move up a level. With
+/// `reassign_to`, its content is filed under that category instead of just
+/// losing the assignment.
 pub async fn delete(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(params): Query<DeleteParams>,
 ) -> AppResult<Json<serde_json::Value>> {
-    svc::delete_category(&state.db, &id).await?;
+    let reassign_to = params.reassign_to.as_deref().filter(|r| !r.is_empty());
+    svc::delete_category(&state.db, &id, reassign_to).await?;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
+
+/// `POST /api/admin/categories/:id/merge`
+///
+/// Merges the category into `into_id`, which takes over its content and
+/// children. Returns the surviving category.
+pub async fn merge(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<MergeCategory>,
+) -> AppResult<Json<Category>> {
+    let category = svc::merge_categories(&state.db, &id, &input.into_id).await?;
+    Ok(Json(category))
+}
@@ -27,7 +27,7 @@ pub async fn atom_feed(State(state): State<AppState>) -> impl IntoResponse {
         per_page: Some(20),
     };
 
-    let articles = match article_svc::list_published_articles(&state.db, &params).await {
+    let articles = match article_svc::list_published_articles(&state.db, &params, Default::default()).await {
         Ok(r) => r.data,
         Err(_) => vec![],
     };
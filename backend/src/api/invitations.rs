@@ -0,0 +1,51 @@
+//! HTTP handlers for invitations.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Users (users.manage permission):
+//!     GET    /api/admin/invitations
+//!     POST   /api/admin/invitations
+//!     DELETE /api/admin/invitations/:id
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::db::models::{CreateInvitation, Invitation, User};
+use crate::error::AppResult;
+use crate::services::registration as svc;
+use crate::AppState;
+
+/// `GET /api/admin/invitations`
+///
+/// Returns pending invitations, then claimed ones.
+pub async fn list(State(state): State<AppState>) -> AppResult<Json<Vec<Invitation>>> {
+    let invitations = svc::list_invitations(&state.db).await?;
+    Ok(Json(invitations))
+}
+
+/// `POST /api/admin/invitations`
+///
+/// Invites an email address with a role, claimed when that person first logs
+/// in with a verified email.
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<CreateInvitation>,
+) -> AppResult<Json<Invitation>> {
+    let invitation = svc::create_invitation(&state.db, input, &user.id).await?;
+    Ok(Json(invitation))
+}
+
+/// `DELETE /api/admin/invitations/:id`
+///
+/// Withdraws a pending invitation.
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::revoke_invitation(&state.db, &id, &user).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
@@ -11,12 +11,15 @@
 
 use axum::{
     extract::{Extension, Path, State},
+    http::HeaderMap,
+    response::IntoResponse,
     Json,
 };
 use serde::Serialize;
 
 use crate::db::models::{Menu, MenuItem, UpdateMenu, User};
 use crate::error::AppResult;
+use crate::services::concurrency;
 use crate::services::menus as svc;
 use crate::AppState;
 
@@ -48,25 +51,32 @@ pub async fn public_get(
 /// `GET /api/admin/menus/:name`
 ///
 /// Same as the public endpoint but protected so editors can preview menus
-/// that aren't yet surfaced publicly.
+/// that aren't yet surfaced publicly. The `ETag` identifies the version for
+/// conditional updates.
 pub async fn admin_get(
     State(state): State<AppState>,
     Path(name): Path<String>,
-) -> AppResult<Json<MenuResponse>> {
+) -> AppResult<impl IntoResponse> {
     let (menu, items) = svc::get_menu(&state.db, &name).await?;
-    Ok(Json(MenuResponse { menu, items }))
+    Ok((concurrency::etag_header(menu.updated_at), Json(MenuResponse { menu, items })))
 }
 
 /// `PUT /api/admin/menus/:name`
 ///
 /// Replaces all items for the named menu. The menu is created automatically
-/// if it does not yet exist.
+/// if it does not yet exist. Honours `If-Match` / `expected_updated_at`;
+/// responds with the new version.
 pub async fn admin_update(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path(name): Path<String>,
+    headers: HeaderMap,
     Json(body): Json<UpdateMenu>,
-) -> AppResult<Json<serde_json::Value>> {
-    svc::update_menu(&state.db, &name, body.items, &user.id).await?;
-    Ok(Json(serde_json::json!({ "ok": true })))
+) -> AppResult<impl IntoResponse> {
+    let expected = concurrency::expected_version(&headers, body.expected_updated_at)?;
+    let version = svc::update_menu(&state.db, &name, body.items, expected, &user.id).await?;
+    Ok((
+        concurrency::etag_header(version),
+        Json(serde_json::json!({ "ok": true, "updated_at": version })),
+    ))
 }
@@ -2,11 +2,19 @@ pub mod apps;
 pub mod articles;
 pub mod audit;
 pub mod auth;
+pub mod authors;
 pub mod categories;
 pub mod feed;
+pub mod invitations;
 pub mod media;
 pub mod menus;
 pub mod pages;
+pub mod redirects;
+pub mod roles;
 pub mod search;
+pub mod series;
+pub mod sessions;
 pub mod settings;
+pub mod tags;
+pub mod tokens;
 pub mod trash;
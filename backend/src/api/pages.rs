@@ -7,7 +7,8 @@
 //! Route map (registered in main.rs):
 //!
 //!   Public:
-//!     GET  /api/pages/:slug
+//!     GET  /api/pages/*path
+//!     GET  /api/page-tree[?path=<path>]
 //!
 //!   Admin (require_auth middleware applied at router level):
 //!     GET    /api/admin/pages
@@ -15,20 +16,37 @@
 //!     GET    /api/admin/pages/:id
 //!     PUT    /api/admin/pages/:id
 //!     DELETE /api/admin/pages/:id
-//!     POST   /api/admin/pages/:id/publish
+//!     POST   /api/admin/pages/:id/submit
 //!     POST   /api/admin/pages/:id/restore
+//!     DELETE /api/admin/pages/:id/draft
 //!     GET    /api/admin/pages/:id/revisions
+//!     GET    /api/admin/pages/:id/revisions/:a/diff/:b
 //!     POST   /api/admin/pages/:id/revisions/:rev_id/restore
+//!     PUT    /api/admin/pages/:id/revisions/:rev_id/pin
+//!     DELETE /api/admin/pages/:id/revisions/:rev_id/pin
+//!     GET    /api/admin/pages/:id/collaborators
+//!     POST   /api/admin/pages/:id/collaborators
+//!     DELETE /api/admin/pages/:id/collaborators/:user_id
+//!
+//!   Reviewer (content.publish_own or content.publish_any permission):
+//!     POST   /api/admin/pages/:id/publish
+//!     POST   /api/admin/pages/:id/approve
+//!     POST   /api/admin/pages/:id/reject
 
 use axum::{
     extract::{Extension, Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
     Json,
 };
 use serde::Deserialize;
 
-use crate::db::models::{CreatePage, Page, PageRevision, PaginatedResponse, PaginationParams,
+use crate::db::models::{AddCollaborator, Collaborator, CreatePage, Page, PageRevision, PageTree,
+    PageWithDraft, PaginatedResponse, PaginationParams, PinRevision, PublicPage, RejectContent,
     UpdatePage, User};
 use crate::error::AppResult;
+use crate::services::{concurrency, ownership};
+use crate::services::diff::{DiffOptions, RevisionDiff};
 use crate::services::pages as svc;
 use crate::AppState;
 
@@ -40,20 +58,41 @@ pub struct StatusFilter {
     pub status: Option<String>,
 }
 
+/// Optional `?path=` used by the page tree endpoint to request breadcrumbs.
+#[derive(Debug, Deserialize)]
+pub struct TreeParams {
+    pub path: Option<String>,
+}
+
 // ─── Public endpoints ─────────────────────────────────────────────────────────
 
-/// `GET /api/pages/:slug`
+/// `GET /api/pages/*path`
 ///
-/// Returns a published page by its URL slug. Unauthenticated — only published
-/// content is visible.
-pub async fn public_get_by_slug(
+/// Returns a published page, with its categories and tags, by its full path,
+/// e.g. `guides/install/linux`.
+/// Top-level pages are addressed by their bare slug. Paths of renamed or moved
+/// pages answer with a 301 to the current path. Unauthenticated — only
+/// published content is visible.
+pub async fn public_get_by_path(
     State(state): State<AppState>,
-    Path(slug): Path<String>,
-) -> AppResult<Json<Page>> {
-    let page = svc::get_page_by_slug(&state.db, &slug).await?;
+    Path(path): Path<String>,
+) -> AppResult<Json<PublicPage>> {
+    let page = svc::get_public_page(&state.db, &path).await?;
     Ok(Json(page))
 }
 
+/// `GET /api/page-tree[?path=<path>]`
+///
+/// Returns the tree of published pages. When `path` is supplied, also returns
+/// the breadcrumb trail for that page (404 if it does not resolve).
+pub async fn public_tree(
+    State(state): State<AppState>,
+    Query(params): Query<TreeParams>,
+) -> AppResult<Json<PageTree>> {
+    let tree = svc::get_page_tree(&state.db, params.path.as_deref()).await?;
+    Ok(Json(tree))
+}
+
 // ─── Admin endpoints ──────────────────────────────────────────────────────────
 
 /// `GET /api/admin/pages`
@@ -70,13 +109,15 @@ pub async fn admin_list(
 
 /// `GET /api/admin/pages/:id`
 ///
-/// Fetches a single page by its ID.
+/// Fetches a single page by its ID. The live version is returned at the top
+/// level, with any pending draft under `draft`. The `ETag` identifies the
+/// version for conditional updates.
 pub async fn admin_get(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> AppResult<Json<Page>> {
-    let page = svc::get_page(&state.db, &id).await?;
-    Ok(Json(page))
+) -> AppResult<impl IntoResponse> {
+    let page = svc::get_page_with_draft(&state.db, &id).await?;
+    Ok((concurrency::etag_header(page.page.updated_at), Json(page)))
 }
 
 /// `POST /api/admin/pages`
@@ -87,21 +128,31 @@ pub async fn admin_create(
     Extension(user): Extension<User>,
     Json(input): Json<CreatePage>,
 ) -> AppResult<Json<Page>> {
-    let page = svc::create_page(&state.db, input, &user.id).await?;
+    let page = svc::create_page(&state.db, input, &user).await?;
+    state.scheduler.reschedule();
     Ok(Json(page))
 }
 
 /// `PUT /api/admin/pages/:id`
 ///
-/// Applies a partial update to an existing page.
+/// Applies a partial update to an existing page. Content edits to a published
+/// page are saved as a draft and leave the live version untouched.
+///
+/// Send the version from `admin_get` in `If-Match` (or `expected_updated_at`)
+/// to get a 409 with the current version instead of overwriting someone
+/// else's changes.
 pub async fn admin_update(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path(id): Path<String>,
-    Json(input): Json<UpdatePage>,
-) -> AppResult<Json<Page>> {
-    let page = svc::update_page(&state.db, &id, input, &user.id).await?;
-    Ok(Json(page))
+    headers: HeaderMap,
+    Json(mut input): Json<UpdatePage>,
+) -> AppResult<impl IntoResponse> {
+    input.expected_updated_at = concurrency::expected_version(&headers, input.expected_updated_at)?;
+    svc::update_page(&state.db, &id, input, &user).await?;
+    state.scheduler.reschedule();
+    let page = svc::get_page_with_draft(&state.db, &id).await?;
+    Ok((concurrency::etag_header(page.page.updated_at), Json(page)))
 }
 
 /// `DELETE /api/admin/pages/:id`
@@ -112,7 +163,7 @@ pub async fn admin_delete(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    svc::trash_page(&state.db, &id, &user.id).await?;
+    svc::trash_page(&state.db, &id, &user).await?;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -124,7 +175,46 @@ pub async fn admin_publish(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> AppResult<Json<Page>> {
-    let page = svc::publish_page(&state.db, &id, &user.id).await?;
+    let page = svc::publish_page(&state.db, &id, &user).await?;
+    state.scheduler.reschedule();
+    Ok(Json(page))
+}
+
+/// `POST /api/admin/pages/:id/submit`
+///
+/// Submits a draft page for editorial review.
+pub async fn admin_submit(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Page>> {
+    let page = svc::submit_page(&state.db, &id, &user).await?;
+    Ok(Json(page))
+}
+
+/// `POST /api/admin/pages/:id/approve`
+///
+/// Approves a page in review, publishing or scheduling it.
+pub async fn admin_approve(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Page>> {
+    let page = svc::approve_page(&state.db, &id, &user).await?;
+    state.scheduler.reschedule();
+    Ok(Json(page))
+}
+
+/// `POST /api/admin/pages/:id/reject`
+///
+/// Rejects a page in review back to `draft`, attaching the reviewer's comment.
+pub async fn admin_reject(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<RejectContent>,
+) -> AppResult<Json<Page>> {
+    let page = svc::reject_page(&state.db, &id, &input.comment, &user).await?;
     Ok(Json(page))
 }
 
@@ -136,7 +226,21 @@ pub async fn admin_restore(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> AppResult<Json<Page>> {
-    let page = svc::restore_page(&state.db, &id, &user.id).await?;
+    let page = svc::restore_page(&state.db, &id, &user).await?;
+    Ok(Json(page))
+}
+
+/// `DELETE /api/admin/pages/:id/draft`
+///
+/// Discards the pending draft of a published page. The live version is
+/// unchanged.
+pub async fn admin_discard_draft(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<PageWithDraft>> {
+    svc::discard_draft(&state.db, &id, &user).await?;
+    let page = svc::get_page_with_draft(&state.db, &id).await?;
     Ok(Json(page))
 }
 
@@ -151,6 +255,20 @@ pub async fn admin_revisions(
     Ok(Json(revisions))
 }
 
+/// `GET /api/admin/pages/:id/revisions/:a/diff/:b`
+///
+/// Diffs revision `a` against revision `b` (either may be `current`), and `b`
+/// against the current version. `?format=html` diffs raw markup instead of
+/// visible text; `?granularity=block` compares whole blocks instead of words.
+pub async fn admin_revision_diff(
+    State(state): State<AppState>,
+    Path((id, from, to)): Path<(String, String, String)>,
+    Query(options): Query<DiffOptions>,
+) -> AppResult<Json<RevisionDiff>> {
+    let diff = svc::diff_revisions(&state.db, &id, &from, &to, options).await?;
+    Ok(Json(diff))
+}
+
 /// `POST /api/admin/pages/:id/revisions/:rev_id/restore`
 ///
 /// Restores a page to a previously captured revision state. Creates a new
@@ -159,7 +277,75 @@ pub async fn admin_restore_revision(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path((id, rev_id)): Path<(String, String)>,
-) -> AppResult<Json<Page>> {
-    let page = svc::restore_revision(&state.db, &id, &rev_id, &user.id).await?;
+) -> AppResult<Json<PageWithDraft>> {
+    svc::restore_revision(&state.db, &id, &rev_id, &user).await?;
+    state.scheduler.reschedule();
+    let page = svc::get_page_with_draft(&state.db, &id).await?;
     Ok(Json(page))
 }
+
+/// `PUT /api/admin/pages/:id/revisions/:rev_id/pin`
+///
+/// Pins a revision as a named version so retention never removes it.
+pub async fn admin_pin_revision(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, rev_id)): Path<(String, String)>,
+    Json(input): Json<PinRevision>,
+) -> AppResult<Json<PageRevision>> {
+    let revision = svc::pin_revision(&state.db, &id, &rev_id, &input.name, &user).await?;
+    Ok(Json(revision))
+}
+
+/// `DELETE /api/admin/pages/:id/revisions/:rev_id/pin`
+///
+/// Removes a revision's version name.
+pub async fn admin_unpin_revision(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, rev_id)): Path<(String, String)>,
+) -> AppResult<Json<PageRevision>> {
+    let revision = svc::unpin_revision(&state.db, &id, &rev_id, &user).await?;
+    Ok(Json(revision))
+}
+
+/// `GET /api/admin/pages/:id/collaborators`
+///
+/// Lists the users given edit access to the page besides its authors.
+pub async fn admin_collaborators(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<Collaborator>>> {
+    svc::get_page(&state.db, &id).await?;
+    let collaborators = ownership::list_collaborators(&state.db, "page", &id).await?;
+    Ok(Json(collaborators))
+}
+
+/// `POST /api/admin/pages/:id/collaborators`
+///
+/// Gives another user edit access to the page. Authors and admins only.
+pub async fn admin_add_collaborator(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<AddCollaborator>,
+) -> AppResult<Json<Vec<Collaborator>>> {
+    svc::get_page(&state.db, &id).await?;
+    let collaborators =
+        ownership::add_collaborator(&state.db, "page", &id, &input.user_id, &user).await?;
+    Ok(Json(collaborators))
+}
+
+/// `DELETE /api/admin/pages/:id/collaborators/:user_id`
+///
+/// Takes a collaborator's edit access away. Authors and admins only.
+pub async fn admin_remove_collaborator(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, user_id)): Path<(String, String)>,
+) -> AppResult<Json<Vec<Collaborator>>> {
+    svc::get_page(&state.db, &id).await?;
+    let collaborators =
+        ownership::remove_collaborator(&state.db, "page", &id, &user_id, &user).await?;
+    Ok(Json(collaborators))
+}
@@ -0,0 +1,93 @@
+//! HTTP handlers for admin-managed redirect rules.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Settings (settings.manage permission, applied at router level):
+//!     GET    /api/admin/redirects            ?stale_days=N lists dead rules
+//!     POST   /api/admin/redirects
+//!     GET    /api/admin/redirects/:id
+//!     PUT    /api/admin/redirects/:id
+//!     DELETE /api/admin/redirects/:id
+//!
+//! Matching itself happens in the fallback router (see `main.rs`). Every
+//! write here invalidates the shared rule cache so changes apply immediately.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::db::models::{CreateRedirect, Redirect, UpdateRedirect, User};
+use crate::error::AppResult;
+use crate::services::redirects as svc;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    /// Only return rules not hit within this many days.
+    pub stale_days: Option<u32>,
+}
+
+/// `GET /api/admin/redirects`
+///
+/// Returns all redirect rules with their hit statistics, which lag behind by
+/// up to an hour (see `RedirectCache::flush_hits`).
+pub async fn list(
+    State(state): State<AppState>,
+    Query(params): Query<ListParams>,
+) -> AppResult<Json<Vec<Redirect>>> {
+    let rules = svc::list_redirects(&state.db, params.stale_days).await?;
+    Ok(Json(rules))
+}
+
+/// `POST /api/admin/redirects`
+///
+/// Creates a new redirect rule.
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<CreateRedirect>,
+) -> AppResult<Json<Redirect>> {
+    let rule = svc::create_redirect(&state.db, input, &user.id).await?;
+    state.redirect_cache.invalidate().await;
+    Ok(Json(rule))
+}
+
+/// `GET /api/admin/redirects/:id`
+///
+/// Fetches a single redirect rule by its ID.
+pub async fn get(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Redirect>> {
+    let rule = svc::get_redirect(&state.db, &id).await?;
+    Ok(Json(rule))
+}
+
+/// `PUT /api/admin/redirects/:id`
+///
+/// Applies a partial update to an existing redirect rule.
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<UpdateRedirect>,
+) -> AppResult<Json<Redirect>> {
+    let rule = svc::update_redirect(&state.db, &id, input, &user.id).await?;
+    state.redirect_cache.invalidate().await;
+    Ok(Json(rule))
+}
+
+/// `DELETE /api/admin/redirects/:id`
+///
+/// Permanently deletes a redirect rule.
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::delete_redirect(&state.db, &id, &user.id).await?;
+    state.redirect_cache.invalidate().await;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
@@ -0,0 +1,83 @@
+//! HTTP handlers for roles and the permission set.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Users (users.manage permission):
+//!     GET    /api/admin/permissions
+//!     GET    /api/admin/roles
+//!     POST   /api/admin/roles
+//!     GET    /api/admin/roles/:name
+//!     PUT    /api/admin/roles/:name
+//!     DELETE /api/admin/roles/:name
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::db::models::{CreateRole, PermissionInfo, Role, UpdateRole, User};
+use crate::error::AppResult;
+use crate::services::roles as svc;
+use crate::AppState;
+
+/// `GET /api/admin/permissions`
+///
+/// Returns every permission a role can grant, with a description.
+pub async fn permissions() -> Json<Vec<PermissionInfo>> {
+    Json(svc::list_permissions())
+}
+
+/// `GET /api/admin/roles`
+///
+/// Returns all roles with their permissions and how many users hold them.
+pub async fn list(State(state): State<AppState>) -> AppResult<Json<Vec<Role>>> {
+    let roles = svc::list_roles(&state.db).await?;
+    Ok(Json(roles))
+}
+
+/// `POST /api/admin/roles`
+///
+/// Defines a custom role from a name and a list of permissions.
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<CreateRole>,
+) -> AppResult<Json<Role>> {
+    let role = svc::create_role(&state.db, input, &user.id).await?;
+    Ok(Json(role))
+}
+
+/// `GET /api/admin/roles/:name`
+pub async fn get(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> AppResult<Json<Role>> {
+    let role = svc::get_role(&state.db, &name).await?;
+    Ok(Json(role))
+}
+
+/// `PUT /api/admin/roles/:name`
+///
+/// Changes a custom role. `permissions`, when present, replaces the list.
+/// Built-in roles cannot be changed.
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(name): Path<String>,
+    Json(input): Json<UpdateRole>,
+) -> AppResult<Json<Role>> {
+    let role = svc::update_role(&state.db, &name, input, &user.id).await?;
+    Ok(Json(role))
+}
+
+/// `DELETE /api/admin/roles/:name`
+///
+/// Deletes a custom role that no user holds any more.
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(name): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::delete_role(&state.db, &name, &user.id).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
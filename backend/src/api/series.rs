@@ -0,0 +1,95 @@
+//! HTTP handlers for the article series resource.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Public:
+//!     GET    /api/series/:slug
+//!
+//!   Admin (require_auth middleware applied at router level):
+//!     GET    /api/admin/series
+//!     POST   /api/admin/series
+//!     GET    /api/admin/series/:id
+//!     PUT    /api/admin/series/:id
+//!     DELETE /api/admin/series/:id
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::db::models::{CreateSeries, SeriesSummary, SeriesWithParts, UpdateSeries, User};
+use crate::error::AppResult;
+use crate::services::series as svc;
+use crate::AppState;
+
+// ─── Public endpoints ─────────────────────────────────────────────────────────
+
+/// `GET /api/series/:slug`
+///
+/// Returns the series and its published articles in reading order — the
+/// table of contents. Unauthenticated.
+pub async fn public_get(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> AppResult<Json<SeriesWithParts>> {
+    let series = svc::published_series(&state.db, &slug).await?;
+    Ok(Json(series))
+}
+
+// ─── Admin endpoints ──────────────────────────────────────────────────────────
+
+/// `GET /api/admin/series`
+///
+/// Returns all series with their article counts.
+pub async fn list(State(state): State<AppState>) -> AppResult<Json<Vec<SeriesSummary>>> {
+    let series = svc::list_series(&state.db).await?;
+    Ok(Json(series))
+}
+
+/// `POST /api/admin/series`
+///
+/// Creates a series from a title and an ordered list of article IDs.
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<CreateSeries>,
+) -> AppResult<Json<SeriesWithParts>> {
+    let series = svc::create_series(&state.db, input, &user.id).await?;
+    Ok(Json(series))
+}
+
+/// `GET /api/admin/series/:id`
+///
+/// Returns a series with all of its articles, whatever their status.
+pub async fn get(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<SeriesWithParts>> {
+    let series = svc::get_series_with_parts(&state.db, &id).await?;
+    Ok(Json(series))
+}
+
+/// `PUT /api/admin/series/:id`
+///
+/// Updates a series. `article_ids`, when present, replaces the article list.
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<UpdateSeries>,
+) -> AppResult<Json<SeriesWithParts>> {
+    let series = svc::update_series(&state.db, &id, input, &user.id).await?;
+    Ok(Json(series))
+}
+
+/// `DELETE /api/admin/series/:id`
+///
+/// Deletes a series. Its articles are not affected.
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::delete_series(&state.db, &id, &user.id).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
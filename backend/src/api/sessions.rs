@@ -0,0 +1,81 @@
+//! HTTP handlers for listing and revoking sessions.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Any signed-in user, for their own sessions:
+//!     GET    /api/admin/me/sessions
+//!     DELETE /api/admin/me/sessions       (all but the current one)
+//!     DELETE /api/admin/me/sessions/:id
+//!
+//!   Users (users.manage permission):
+//!     GET    /api/admin/users/:id/sessions
+//!     DELETE /api/admin/users/:id/sessions
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::HeaderMap,
+    Json,
+};
+
+use crate::auth::session::{self, session_token};
+use crate::db::models::{SessionInfo, User};
+use crate::error::AppResult;
+use crate::AppState;
+
+/// `GET /api/admin/me/sessions`
+///
+/// Returns the current user's sessions, flagging the one in use.
+pub async fn list_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<SessionInfo>>> {
+    let current = session_token(&headers, &state.config);
+    let sessions = session::list_sessions(&state.db, &user.id, current.as_deref()).await?;
+    Ok(Json(sessions))
+}
+
+/// `DELETE /api/admin/me/sessions`
+///
+/// Signs the current user out everywhere except this browser.
+pub async fn revoke_other_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    headers: HeaderMap,
+) -> AppResult<Json<serde_json::Value>> {
+    let current = session_token(&headers, &state.config);
+    let revoked =
+        session::revoke_user_sessions(&state.db, &user.id, current.as_deref(), &user.id).await?;
+    Ok(Json(serde_json::json!({ "ok": true, "revoked": revoked })))
+}
+
+/// `DELETE /api/admin/me/sessions/:id`
+pub async fn revoke_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    session::revoke_session(&state.db, &user.id, &id, &user.id).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+/// `GET /api/admin/users/:id/sessions`
+pub async fn list_for_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<SessionInfo>>> {
+    let sessions = session::list_sessions(&state.db, &id, None).await?;
+    Ok(Json(sessions))
+}
+
+/// `DELETE /api/admin/users/:id/sessions`
+///
+/// Signs a user out of every session. Their API tokens are not affected.
+pub async fn revoke_for_user(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let revoked = session::revoke_user_sessions(&state.db, &id, None, &user.id).await?;
+    Ok(Json(serde_json::json!({ "ok": true, "revoked": revoked })))
+}
@@ -8,16 +8,22 @@
 //!   Admin (require_auth middleware applied at router level):
 //!     GET  /api/admin/settings
 //!     PUT  /api/admin/settings
+//!     GET  /api/admin/settings/sanitization-report
 
 use std::collections::HashMap;
 
 use axum::{
     extract::{Extension, State},
+    http::HeaderMap,
+    response::IntoResponse,
     Json,
 };
+use chrono::{DateTime, Utc};
 
 use crate::db::models::User;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::services::concurrency;
+use crate::services::sanitize::{self, ReportEntry};
 use crate::services::settings as svc;
 use crate::AppState;
 
@@ -38,22 +44,48 @@ pub async fn public_get(
 
 /// `GET /api/admin/settings`
 ///
-/// Returns all key-value pairs in site_settings. Admin-only.
-pub async fn admin_get(
-    State(state): State<AppState>,
-) -> AppResult<Json<HashMap<String, String>>> {
+/// Returns all key-value pairs in site_settings. The `ETag`
+/// identifies the version of the settings as a whole.
+pub async fn admin_get(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
     let settings = svc::get_all_settings(&state.db).await?;
-    Ok(Json(settings))
+    let version = svc::settings_version(&state.db).await?;
+    Ok((concurrency::etag_header(version), Json(settings)))
 }
 
 /// `PUT /api/admin/settings`
 ///
-/// Upserts a batch of key-value pairs. Admin-only.
+/// Upserts a batch of key-value pairs. The version the edit was
+/// based on may be sent in `If-Match` or as an `expected_updated_at` entry;
+/// responds with the new version.
 pub async fn admin_update(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
-    Json(updates): Json<HashMap<String, String>>,
-) -> AppResult<Json<serde_json::Value>> {
-    svc::update_settings(&state.db, updates, &user.id).await?;
-    Ok(Json(serde_json::json!({ "ok": true })))
+    headers: HeaderMap,
+    Json(mut updates): Json<HashMap<String, String>>,
+) -> AppResult<impl IntoResponse> {
+    let expected_updated_at = match updates.remove("expected_updated_at") {
+        Some(value) => Some(
+            DateTime::parse_from_rfc3339(&value)
+                .map_err(|_| AppError::BadRequest("Invalid expected_updated_at".into()))?
+                .with_timezone(&Utc),
+        ),
+        None => None,
+    };
+    let expected = concurrency::expected_version(&headers, expected_updated_at)?;
+    let version = svc::update_settings(&state.db, updates, expected, &user.id).await?;
+    Ok((
+        concurrency::etag_header(version),
+        Json(serde_json::json!({ "ok": true, "updated_at": version })),
+    ))
+}
+
+/// `GET /api/admin/settings/sanitization-report`
+///
+/// Lists pages and articles whose stored content would change under the
+/// current sanitization policy.
+pub async fn sanitization_report(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<ReportEntry>>> {
+    let entries = sanitize::report(&state.db).await?;
+    Ok(Json(entries))
 }
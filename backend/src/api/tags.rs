@@ -0,0 +1,86 @@
+//! HTTP handlers for the tags resource.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Public:
+//!     GET    /api/tags/:slug
+//!
+//!   Admin (require_auth middleware applied at router level):
+//!     GET    /api/admin/tags
+//!     PUT    /api/admin/tags/:id
+//!     DELETE /api/admin/tags/:id
+//!     POST   /api/admin/tags/:id/merge
+//!
+//! There is no create endpoint: tags are created when first assigned to a
+//! page or article.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::db::models::{MergeTag, PaginationParams, RenameTag, Tag, TagListing, TagUsage};
+use crate::error::AppResult;
+use crate::services::tags as svc;
+use crate::AppState;
+
+// ─── Public endpoints ─────────────────────────────────────────────────────────
+
+/// `GET /api/tags/:slug`
+///
+/// Returns the tag with its published pages and a paginated list of its
+/// published articles, newest first. Unauthenticated.
+pub async fn public_get(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(pagination): Query<PaginationParams>,
+) -> AppResult<Json<TagListing>> {
+    let listing = svc::published_listing(&state.db, &slug, &pagination).await?;
+    Ok(Json(listing))
+}
+
+// ─── Admin endpoints ──────────────────────────────────────────────────────────
+
+/// `GET /api/admin/tags`
+///
+/// Returns all tags with their page and article counts, ordered by name.
+pub async fn list(State(state): State<AppState>) -> AppResult<Json<Vec<TagUsage>>> {
+    let tags = svc::list_tags(&state.db).await?;
+    Ok(Json(tags))
+}
+
+/// `PUT /api/admin/tags/:id`
+///
+/// Renames a tag. The slug follows the name unless one is supplied.
+pub async fn rename(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<RenameTag>,
+) -> AppResult<Json<Tag>> {
+    let tag = svc::rename_tag(&state.db, &id, &input.name, input.slug.as_deref()).await?;
+    Ok(Json(tag))
+}
+
+/// `POST /api/admin/tags/:id/merge`
+///
+/// Moves every assignment of the tag to `into_id` and deletes it. Returns the
+/// surviving tag.
+pub async fn merge(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<MergeTag>,
+) -> AppResult<Json<Tag>> {
+    let tag = svc::merge_tags(&state.db, &id, &input.into_id).await?;
+    Ok(Json(tag))
+}
+
+/// `DELETE /api/admin/tags/:id`
+///
+/// Deletes a tag and removes it from all content.
+pub async fn delete(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::delete_tag(&state.db, &id).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
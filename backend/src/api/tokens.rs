@@ -0,0 +1,115 @@
+//! HTTP handlers for API tokens and service accounts.
+//!
+//! Route map (registered in main.rs):
+//!
+//!   Any signed-in user (sessions only; tokens cannot manage tokens):
+//!     GET    /api/admin/scopes
+//!     GET    /api/admin/me/tokens
+//!     POST   /api/admin/me/tokens
+//!     DELETE /api/admin/me/tokens/:id
+//!
+//!   Users (users.manage permission):
+//!     POST   /api/admin/service-accounts
+//!     GET    /api/admin/users/:id/tokens
+//!     POST   /api/admin/users/:id/tokens
+//!     DELETE /api/admin/users/:id/tokens/:token_id
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::db::models::{
+    ApiToken, CreateApiToken, CreateServiceAccount, NewApiToken, ScopeInfo, User,
+};
+use crate::error::AppResult;
+use crate::services::api_tokens as svc;
+use crate::AppState;
+
+/// `GET /api/admin/scopes`
+///
+/// Returns every scope an API token can carry, with a description.
+pub async fn scopes() -> Json<Vec<ScopeInfo>> {
+    Json(svc::list_scopes())
+}
+
+/// `GET /api/admin/me/tokens`
+pub async fn list_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> AppResult<Json<Vec<ApiToken>>> {
+    let tokens = svc::list_tokens(&state.db, &user.id).await?;
+    Ok(Json(tokens))
+}
+
+/// `POST /api/admin/me/tokens`
+///
+/// Creates a personal token. The response carries the token itself, which
+/// cannot be retrieved again.
+pub async fn create_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<CreateApiToken>,
+) -> AppResult<Json<NewApiToken>> {
+    let token = svc::create_token(&state.db, &user.id, input, &user).await?;
+    Ok(Json(token))
+}
+
+/// `DELETE /api/admin/me/tokens/:id`
+pub async fn revoke_own(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::revoke_token(&state.db, &user.id, &id, &user).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+/// `POST /api/admin/service-accounts`
+///
+/// Creates a user that authenticates with API tokens only.
+pub async fn create_service_account(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(input): Json<CreateServiceAccount>,
+) -> AppResult<Json<User>> {
+    let account = svc::create_service_account(&state.db, input, &user).await?;
+    Ok(Json(account))
+}
+
+/// `GET /api/admin/users/:id/tokens`
+///
+/// Lists any user's tokens, so admins can see what can act on their behalf.
+pub async fn list_for_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<ApiToken>>> {
+    let tokens = svc::list_tokens(&state.db, &id).await?;
+    Ok(Json(tokens))
+}
+
+/// `POST /api/admin/users/:id/tokens`
+///
+/// Creates a token for a service account. Other users create their own.
+pub async fn create_for_user(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(input): Json<CreateApiToken>,
+) -> AppResult<Json<NewApiToken>> {
+    svc::ensure_service_account(&state.db, &id).await?;
+    let token = svc::create_token(&state.db, &id, input, &user).await?;
+    Ok(Json(token))
+}
+
+/// `DELETE /api/admin/users/:id/tokens/:token_id`
+///
+/// Revokes any user's token.
+pub async fn revoke_for_user(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((id, token_id)): Path<(String, String)>,
+) -> AppResult<Json<serde_json::Value>> {
+    svc::revoke_token(&state.db, &id, &token_id, &user).await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
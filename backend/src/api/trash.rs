@@ -16,6 +16,8 @@ use serde::Serialize;
 
 use crate::db::models::{Article, Page};
 use crate::error::AppResult;
+use crate::services::articles::ARTICLE_COLS;
+use crate::services::pages::{self, PAGE_COLS};
 use crate::AppState;
 
 // ─── Response structs ─────────────────────────────────────────────────────────
@@ -33,23 +35,19 @@ pub struct TrashResponse {
 ///
 /// Returns all pages and articles that are currently in the trash.
 pub async fn list(State(state): State<AppState>) -> AppResult<Json<TrashResponse>> {
-    let pages = sqlx::query_as::<_, Page>(
-        "SELECT id, title, slug, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, template \
-         FROM pages \
+    let pages = sqlx::query_as::<_, Page>(&format!(
+        "SELECT {PAGE_COLS} FROM pages \
          WHERE status = 'trashed' \
-         ORDER BY trashed_at DESC",
-    )
+         ORDER BY trashed_at DESC"
+    ))
     .fetch_all(&state.db)
     .await?;
 
-    let articles = sqlx::query_as::<_, Article>(
-        "SELECT id, title, slug, short_text, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes \
-         FROM articles \
+    let articles = sqlx::query_as::<_, Article>(&format!(
+        "SELECT {ARTICLE_COLS} FROM articles \
          WHERE status = 'trashed' \
-         ORDER BY trashed_at DESC",
-    )
+         ORDER BY trashed_at DESC"
+    ))
     .fetch_all(&state.db)
     .await?;
 
@@ -64,7 +62,7 @@ pub async fn empty(State(state): State<AppState>) -> AppResult<Json<serde_json::
     let pages_deleted = sqlx::query(
         "DELETE FROM pages \
          WHERE status = 'trashed' \
-           AND trashed_at < datetime('now', '-30 days')",
+           AND trashed_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-30 days')",
     )
     .execute(&state.db)
     .await?
@@ -73,12 +71,18 @@ pub async fn empty(State(state): State<AppState>) -> AppResult<Json<serde_json::
     let articles_deleted = sqlx::query(
         "DELETE FROM articles \
          WHERE status = 'trashed' \
-           AND trashed_at < datetime('now', '-30 days')",
+           AND trashed_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-30 days')",
     )
     .execute(&state.db)
     .await?
     .rows_affected();
 
+    // Children of purged pages were promoted to the top level by the FK's
+    // ON DELETE SET NULL; bring their materialised paths back in line.
+    if pages_deleted > 0 {
+        pages::refresh_all_paths(&state.db).await?;
+    }
+
     Ok(Json(serde_json::json!({
         "ok": true,
         "pages_deleted": pages_deleted,
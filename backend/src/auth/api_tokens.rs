@@ -0,0 +1,114 @@
+//! API tokens: bearer credentials for scripts and service accounts.
+//!
+//! A token is sent as `Authorization: Bearer <token>` and, like a session
+//! token, only its SHA-256 hash is stored. A request made with a token acts as
+//! the token's user, so the user's role still applies; on top of that the
+//! token's scopes decide which admin routes it may reach at all. Routes not
+//! covered by any scope — users, roles, settings, the audit log and token
+//! management itself — are closed to tokens.
+
+use axum::http::Method;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::auth::session::hash_token;
+use crate::db::models::User;
+use crate::error::{AppError, AppResult};
+use crate::services::roles;
+
+/// Prefix that makes tokens easy to recognise, e.g. by secret scanners.
+const TOKEN_PREFIX: &str = "pawtal_";
+
+pub const READ_DRAFTS: &str = "read:drafts";
+pub const ARTICLES_WRITE: &str = "articles:write";
+pub const PAGES_WRITE: &str = "pages:write";
+pub const MEDIA_UPLOAD: &str = "media:upload";
+pub const TAXONOMY_WRITE: &str = "taxonomy:write";
+
+/// Every scope with a short description, in display order.
+pub const SCOPES: &[(&str, &str)] = &[
+    (READ_DRAFTS, "Read content in the admin API, including drafts and media"),
+    (ARTICLES_WRITE, "Create, edit, submit and publish articles"),
+    (PAGES_WRITE, "Create, edit, submit and publish pages"),
+    (MEDIA_UPLOAD, "Upload media"),
+    (TAXONOMY_WRITE, "Manage categories, tags and series"),
+];
+
+/// Admin resources `read:drafts` opens to `GET` requests.
+const READABLE: &[&str] = &[
+    "apps", "articles", "authors", "categories", "media", "menus", "pages", "search",
+    "series", "tags", "trash",
+];
+
+/// Returns true when `name` is a known scope.
+pub fn is_known(name: &str) -> bool {
+    SCOPES.iter().any(|(known, _)| *known == name)
+}
+
+/// Returns true when a token with `scopes` may make a `method` request to
+/// the admin API `path`. `GET /api/admin/me` is always allowed.
+pub fn allows(scopes: &[String], method: &Method, path: &str) -> bool {
+    if path == "/api/admin/me" {
+        return true;
+    }
+    let Some(rest) = path.strip_prefix("/api/admin/") else {
+        return false;
+    };
+    let resource = rest.split('/').next().unwrap_or_default();
+    let required = if method == Method::GET || method == Method::HEAD {
+        if !READABLE.contains(&resource) {
+            return false;
+        }
+        READ_DRAFTS
+    } else {
+        match resource {
+            "articles" => ARTICLES_WRITE,
+            "pages" => PAGES_WRITE,
+            "media" if method == Method::POST => MEDIA_UPLOAD,
+            "categories" | "tags" | "series" => TAXONOMY_WRITE,
+            _ => return false,
+        }
+    };
+    scopes.iter().any(|s| s == required)
+}
+
+/// Generates a new token, returning it with the hash to store.
+pub fn generate() -> (String, String) {
+    let token = format!("{TOKEN_PREFIX}{}", Uuid::new_v4().simple());
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+/// Validates an API token and returns its `User`, with `scopes` set, and
+/// records when the token was used.
+///
+/// Returns `AppError::Unauthorized` if the token does not exist or has expired.
+pub async fn validate_token(pool: &SqlitePool, token: &str) -> AppResult<User> {
+    if !token.starts_with(TOKEN_PREFIX) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let (user_id, scopes) = sqlx::query_as::<_, (String, String)>(
+        "UPDATE api_tokens SET last_used_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         WHERE token = ? AND expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         RETURNING user_id, scopes",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::Unauthorized)?;
+
+    let mut user = sqlx::query_as::<_, User>(
+        "SELECT id, external_id, email, display_name, role, created_at, last_login, is_service \
+         FROM users WHERE id = ?",
+    )
+    .bind(&user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::Unauthorized)?;
+
+    user.permissions = roles::role_permissions(pool, &user.role).await?;
+    user.scopes = Some(serde_json::from_str(&scopes).unwrap_or_default());
+
+    Ok(user)
+}
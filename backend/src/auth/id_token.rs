@@ -0,0 +1,489 @@
+//! ID token validation.
+//!
+//! The callback only trusts a login once the provider's ID token checks out:
+//! its signature against the issuer's published keys (JWKS), then the `iss`,
+//! `aud`, `exp` and `nonce` claims against what this login expects.
+//!
+//! RS256/384/512 and ES256/384 are verified against the JWKS. HS256, which
+//! some providers (Authentik among them) use when no signing key is
+//! configured, is verified with the client secret.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::Deserialize;
+use sha2::Sha256;
+
+/// Clock skew tolerated between us and the provider, in seconds.
+const LEEWAY_SECONDS: i64 = 60;
+
+/// The issuer's JSON Web Key Set.
+#[derive(Debug, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+/// A public key from the JWKS. Only the members we verify with are decoded.
+#[derive(Debug, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: Option<String>,
+    #[serde(rename = "use")]
+    pub key_use: Option<String>,
+    /// RSA modulus and exponent.
+    pub n: Option<String>,
+    pub e: Option<String>,
+    /// EC curve and point.
+    pub crv: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: Option<String>,
+}
+
+/// The `aud` claim, which may be a single string or a list.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(aud) => aud == client_id,
+            Audience::Many(auds) => auds.iter().any(|aud| aud == client_id),
+        }
+    }
+
+    fn is_multiple(&self) -> bool {
+        matches!(self, Audience::Many(auds) if auds.len() > 1)
+    }
+}
+
+/// The ID token claims we check, and the rest.
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: Audience,
+    pub exp: i64,
+    pub nonce: Option<String>,
+    pub azp: Option<String>,
+    /// Every claim, including the ones above, for role mapping.
+    #[serde(skip)]
+    pub raw: serde_json::Map<String, serde_json::Value>,
+}
+
+/// What a token has to match to be accepted.
+pub struct Expected<'a> {
+    /// The `issuer` from the discovery document.
+    pub issuer: &'a str,
+    pub client_id: &'a str,
+    /// Key for HS256 tokens.
+    pub client_secret: &'a str,
+    /// The nonce sent with this login's authorization request.
+    pub nonce: &'a str,
+    /// Current Unix time.
+    pub now: i64,
+}
+
+/// Why an ID token was rejected.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Rejection {
+    #[error("malformed token")]
+    Malformed,
+    #[error("unsupported algorithm '{0}'")]
+    UnsupportedAlgorithm(String),
+    /// No key in the JWKS matches; the issuer may have rotated its keys.
+    #[error("no matching signing key")]
+    UnknownKey,
+    #[error("bad signature")]
+    BadSignature,
+    #[error("wrong issuer")]
+    Issuer,
+    #[error("wrong audience")]
+    Audience,
+    #[error("token expired")]
+    Expired,
+    #[error("nonce mismatch")]
+    Nonce,
+}
+
+/// Verifies `token`'s signature against `jwks` and its claims against
+/// `expected`, returning the claims.
+pub fn validate(token: &str, jwks: &Jwks, expected: &Expected) -> Result<IdTokenClaims, Rejection> {
+    let mut parts = token.split('.');
+    let (Some(header_b64), Some(payload_b64), Some(signature_b64), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Rejection::Malformed);
+    };
+    let header: Header = decode_json(header_b64)?;
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature_b64)
+        .map_err(|_| Rejection::Malformed)?;
+    let message = &token.as_bytes()[..header_b64.len() + 1 + payload_b64.len()];
+
+    verify_signature(&header, message, &signature, jwks, expected.client_secret)?;
+
+    let raw: serde_json::Map<String, serde_json::Value> = decode_json(payload_b64)?;
+    let mut claims = IdTokenClaims::deserialize(&serde_json::Value::Object(raw.clone()))
+        .map_err(|_| Rejection::Malformed)?;
+    claims.raw = raw;
+    if claims.iss != expected.issuer {
+        return Err(Rejection::Issuer);
+    }
+    // With several audiences, `azp` must name us; when present it always must.
+    let azp_ok = match &claims.azp {
+        Some(azp) => azp == expected.client_id,
+        None => !claims.aud.is_multiple(),
+    };
+    if !claims.aud.contains(expected.client_id) || !azp_ok {
+        return Err(Rejection::Audience);
+    }
+    if claims.exp + LEEWAY_SECONDS <= expected.now {
+        return Err(Rejection::Expired);
+    }
+    if claims.nonce.as_deref() != Some(expected.nonce) {
+        return Err(Rejection::Nonce);
+    }
+
+    Ok(claims)
+}
+
+fn decode_json<T: serde::de::DeserializeOwned>(part: &str) -> Result<T, Rejection> {
+    let bytes = URL_SAFE_NO_PAD.decode(part).map_err(|_| Rejection::Malformed)?;
+    serde_json::from_slice(&bytes).map_err(|_| Rejection::Malformed)
+}
+
+fn verify_signature(
+    header: &Header,
+    message: &[u8],
+    signature: &[u8],
+    jwks: &Jwks,
+    client_secret: &str,
+) -> Result<(), Rejection> {
+    if header.alg == "HS256" {
+        let mut mac = Hmac::<Sha256>::new_from_slice(client_secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(message);
+        return mac.verify_slice(signature).map_err(|_| Rejection::BadSignature);
+    }
+
+    let (kty, crv) = match header.alg.as_str() {
+        "RS256" | "RS384" | "RS512" => ("RSA", None),
+        "ES256" => ("EC", Some("P-256")),
+        "ES384" => ("EC", Some("P-384")),
+        other => return Err(Rejection::UnsupportedAlgorithm(other.to_string())),
+    };
+
+    // Without a `kid`, any signing key of the right type may have been used.
+    let candidates: Vec<&Jwk> = jwks
+        .keys
+        .iter()
+        .filter(|key| key.kty == kty && key.key_use.as_deref().is_none_or(|u| u == "sig"))
+        .filter(|key| crv.is_none() || key.crv.as_deref() == crv)
+        .filter(|key| header.kid.is_none() || key.kid == header.kid)
+        .collect();
+    if candidates.is_empty() {
+        return Err(Rejection::UnknownKey);
+    }
+
+    if candidates
+        .iter()
+        .any(|key| verify_with_key(&header.alg, key, message, signature))
+    {
+        Ok(())
+    } else {
+        Err(Rejection::BadSignature)
+    }
+}
+
+fn verify_with_key(alg: &str, key: &Jwk, message: &[u8], signature: &[u8]) -> bool {
+    let decode = |member: &Option<String>| {
+        member
+            .as_deref()
+            .and_then(|value| URL_SAFE_NO_PAD.decode(value).ok())
+    };
+
+    match alg {
+        "RS256" | "RS384" | "RS512" => {
+            let (Some(n), Some(e)) = (decode(&key.n), decode(&key.e)) else {
+                return false;
+            };
+            let params = match alg {
+                "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                _ => &signature::RSA_PKCS1_2048_8192_SHA512,
+            };
+            RsaPublicKeyComponents { n: &n, e: &e }
+                .verify(params, message, signature)
+                .is_ok()
+        }
+        "ES256" | "ES384" => {
+            let (Some(x), Some(y)) = (decode(&key.x), decode(&key.y)) else {
+                return false;
+            };
+            let params = if alg == "ES256" {
+                &signature::ECDSA_P256_SHA256_FIXED
+            } else {
+                &signature::ECDSA_P384_SHA384_FIXED
+            };
+            // Uncompressed SEC1 point: 0x04 || x || y.
+            let point = [&[0x04][..], &x, &y].concat();
+            UnparsedPublicKey::new(params, point)
+                .verify(message, signature)
+                .is_ok()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, RsaKeyPair};
+    use rsa::pkcs8::EncodePrivateKey;
+    use rsa::traits::PublicKeyParts;
+    use serde_json::json;
+
+    const SECRET: &str = "client-secret";
+    const NOW: i64 = 1_700_000_000;
+
+    fn expected() -> Expected<'static> {
+        Expected {
+            issuer: "https://idp.example/",
+            client_id: "pawtal",
+            client_secret: SECRET,
+            nonce: "n-1",
+            now: NOW,
+        }
+    }
+
+    fn claims() -> serde_json::Value {
+        json!({
+            "iss": "https://idp.example/",
+            "sub": "user-1",
+            "aud": "pawtal",
+            "exp": NOW + 300,
+            "nonce": "n-1",
+        })
+    }
+
+    /// Encodes a token, signing `header.payload` with `sign`.
+    fn encode(
+        header: serde_json::Value,
+        claims: &serde_json::Value,
+        sign: impl FnOnce(&[u8]) -> Vec<u8>,
+    ) -> String {
+        let header = URL_SAFE_NO_PAD.encode(header.to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let signature = URL_SAFE_NO_PAD.encode(sign(format!("{header}.{payload}").as_bytes()));
+        format!("{header}.{payload}.{signature}")
+    }
+
+    fn sign_hs256(claims: &serde_json::Value, secret: &str) -> String {
+        encode(json!({ "alg": "HS256" }), claims, |message| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        })
+    }
+
+    fn jwks(keys: &[serde_json::Value]) -> Jwks {
+        serde_json::from_value(json!({ "keys": keys })).unwrap()
+    }
+
+    /// A generated P-256 signing key, published under `kid`.
+    pub(crate) struct EcKey {
+        kid: String,
+        pair: EcdsaKeyPair,
+    }
+
+    impl EcKey {
+        pub(crate) fn generate(kid: &str) -> Self {
+            let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+            let pair = EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+            EcKey { kid: kid.into(), pair }
+        }
+
+        /// The public key as a JWKS entry.
+        pub(crate) fn jwk(&self) -> serde_json::Value {
+            // Uncompressed SEC1 point: 0x04 || x || y.
+            let (x, y) = self.pair.public_key().as_ref()[1..].split_at(32);
+            json!({
+                "kty": "EC",
+                "kid": self.kid,
+                "use": "sig",
+                "crv": "P-256",
+                "x": URL_SAFE_NO_PAD.encode(x),
+                "y": URL_SAFE_NO_PAD.encode(y),
+            })
+        }
+
+        pub(crate) fn sign(&self, claims: &serde_json::Value) -> String {
+            let header = json!({ "alg": "ES256", "kid": self.kid });
+            encode(header, claims, |message| {
+                let rng = SystemRandom::new();
+                self.pair.sign(&rng, message).unwrap().as_ref().to_vec()
+            })
+        }
+    }
+
+    /// A generated 2048-bit RSA signing key, published under `kid`.
+    struct RsaKey {
+        kid: String,
+        pair: RsaKeyPair,
+        public: rsa::RsaPublicKey,
+    }
+
+    impl RsaKey {
+        fn generate(kid: &str) -> Self {
+            let private = rsa::RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+            let pkcs8 = private.to_pkcs8_der().unwrap();
+            let pair = RsaKeyPair::from_pkcs8(pkcs8.as_bytes()).unwrap();
+            RsaKey { kid: kid.into(), pair, public: private.to_public_key() }
+        }
+
+        fn jwk(&self) -> serde_json::Value {
+            json!({
+                "kty": "RSA",
+                "kid": self.kid,
+                "n": URL_SAFE_NO_PAD.encode(self.public.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(self.public.e().to_bytes_be()),
+            })
+        }
+
+        fn sign(&self, claims: &serde_json::Value) -> String {
+            let header = json!({ "alg": "RS256", "kid": self.kid });
+            encode(header, claims, |message| {
+                let mut signature = vec![0; self.pair.public().modulus_len()];
+                let rng = SystemRandom::new();
+                self.pair
+                    .sign(&signature::RSA_PKCS1_SHA256, &rng, message, &mut signature)
+                    .unwrap();
+                signature
+            })
+        }
+    }
+
+    fn check(claims: &serde_json::Value) -> Result<IdTokenClaims, Rejection> {
+        let jwks = Jwks { keys: vec![] };
+        validate(&sign_hs256(claims, SECRET), &jwks, &expected())
+    }
+
+    #[test]
+    fn accepts_valid_token() {
+        let verified = check(&claims()).unwrap();
+        assert_eq!(verified.sub, "user-1");
+        assert_eq!(serde_json::Value::Object(verified.raw), claims());
+    }
+
+    #[test]
+    fn rejects_forged_signature() {
+        let jwks = Jwks { keys: vec![] };
+        let token = sign_hs256(&claims(), "other-secret");
+        assert_eq!(validate(&token, &jwks, &expected()).unwrap_err(), Rejection::BadSignature);
+    }
+
+    #[test]
+    fn rejects_wrong_claims() {
+        let mut c = claims();
+        c["iss"] = json!("https://evil.example/");
+        assert_eq!(check(&c).unwrap_err(), Rejection::Issuer);
+
+        let mut c = claims();
+        c["aud"] = json!(["other"]);
+        assert_eq!(check(&c).unwrap_err(), Rejection::Audience);
+
+        let mut c = claims();
+        c["aud"] = json!(["pawtal", "other"]);
+        assert_eq!(check(&c).unwrap_err(), Rejection::Audience);
+        c["azp"] = json!("pawtal");
+        assert!(check(&c).is_ok());
+
+        let mut c = claims();
+        c["exp"] = json!(NOW - LEEWAY_SECONDS);
+        assert_eq!(check(&c).unwrap_err(), Rejection::Expired);
+
+        let mut c = claims();
+        c["nonce"] = json!("replayed");
+        assert_eq!(check(&c).unwrap_err(), Rejection::Nonce);
+    }
+
+    #[test]
+    fn rejects_unknown_key_and_alg_none() {
+        let jwks = Jwks { keys: vec![] };
+        let payload = URL_SAFE_NO_PAD.encode(claims().to_string());
+
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256", "kid": "k1" }).to_string());
+        let token = format!("{header}.{payload}.AAAA");
+        assert_eq!(validate(&token, &jwks, &expected()).unwrap_err(), Rejection::UnknownKey);
+
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "none" }).to_string());
+        let token = format!("{header}.{payload}.");
+        assert_eq!(
+            validate(&token, &jwks, &expected()).unwrap_err(),
+            Rejection::UnsupportedAlgorithm("none".into())
+        );
+    }
+
+    #[test]
+    fn verifies_rs256_against_the_jwks() {
+        let key = RsaKey::generate("rsa-1");
+        let token = key.sign(&claims());
+
+        let verified = validate(&token, &jwks(&[key.jwk()]), &expected()).unwrap();
+        assert_eq!(verified.sub, "user-1");
+
+        // Another modulus published under the token's kid does not verify it.
+        let mut impostor = key.jwk();
+        let mut n = key.public.n().to_bytes_be();
+        n[0] ^= 0x01;
+        impostor["n"] = json!(URL_SAFE_NO_PAD.encode(n));
+        let result = validate(&token, &jwks(&[impostor]), &expected());
+        assert_eq!(result.unwrap_err(), Rejection::BadSignature);
+
+        let mut rotated = key.jwk();
+        rotated["kid"] = json!("rsa-2");
+        let result = validate(&token, &jwks(&[rotated]), &expected());
+        assert_eq!(result.unwrap_err(), Rejection::UnknownKey);
+    }
+
+    #[test]
+    fn verifies_es256_against_the_jwks() {
+        let key = EcKey::generate("ec-1");
+        let other = EcKey::generate("ec-2");
+        let token = key.sign(&claims());
+
+        let verified = validate(&token, &jwks(&[other.jwk(), key.jwk()]), &expected()).unwrap();
+        assert_eq!(verified.sub, "user-1");
+
+        let mut impostor = other.jwk();
+        impostor["kid"] = json!("ec-1");
+        let result = validate(&token, &jwks(&[impostor]), &expected());
+        assert_eq!(result.unwrap_err(), Rejection::BadSignature);
+
+        // A key for another curve is no candidate.
+        let mut p384 = key.jwk();
+        p384["crv"] = json!("P-384");
+        let result = validate(&token, &jwks(&[p384]), &expected());
+        assert_eq!(result.unwrap_err(), Rejection::UnknownKey);
+
+        // The claims are still checked once the signature is good.
+        let mut c = claims();
+        c["nonce"] = json!("replayed");
+        let result = validate(&key.sign(&c), &jwks(&[key.jwk()]), &expected());
+        assert_eq!(result.unwrap_err(), Rejection::Nonce);
+    }
+}
@@ -1,14 +1,18 @@
 //! Axum middleware extractors for authentication and authorization.
 //!
-//! Both middleware functions follow the same pattern:
-//!   1. Extract the `pawtal_session` cookie from the `Cookie` header.
-//!   2. Validate the token against the database.
+//! `require_auth` follows this pattern:
+//!   1. Extract the API token from `Authorization: Bearer`, or else the
+//!      `pawtal_session` cookie from the `Cookie` header.
+//!   2. Check the session cookie's signature, then validate the token against
+//!      the database; API tokens must also have a scope covering the route.
 //!   3. On success, inject the `User` into request extensions so downstream
 //!      handlers can retrieve it via `Extension<User>`.
 //!   4. On failure, short-circuit with the appropriate error response.
 //!
+//! `require_permission` then checks the injected user's role.
+//!
 //! Usage in router setup:
-//! ```rust
+//! ```ignore
 //! Router::new()
 //!     .route("/api/admin/me", get(me))
 //!     .layer(from_fn_with_state(state.clone(), require_auth))
@@ -16,60 +20,80 @@
 
 use axum::{
     extract::{Request, State},
+    http::{header, HeaderValue},
     middleware::Next,
     response::Response,
 };
 
-use crate::auth::session::validate_session;
+use crate::auth::api_tokens::{self, validate_token};
+use crate::auth::session::{session_cookie, session_set_cookie, validate_session, verify_cookie};
 use crate::error::AppError;
 use crate::AppState;
 
-/// Extracts the `pawtal_session` cookie value from the raw `Cookie` header.
-///
-/// The Cookie header is a semicolon-separated list of `name=value` pairs.
-/// We look for the specific cookie name and return its value if found.
-fn extract_session_cookie(request: &Request) -> Option<String> {
-    let cookie_header = request.headers().get("cookie")?.to_str().ok()?;
-
-    // Parse "name=value; name2=value2; ..." pairs
-    for pair in cookie_header.split(';') {
-        let pair = pair.trim();
-        if let Some((name, value)) = pair.split_once('=') {
-            if name.trim() == "pawtal_session" {
-                return Some(value.trim().to_string());
-            }
-        }
-    }
-
-    None
+/// Extracts the token from an `Authorization: Bearer <token>` header.
+fn extract_bearer_token(request: &Request) -> Option<String> {
+    let value = request.headers().get("authorization")?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then(|| token.trim().to_string())
 }
 
-/// Middleware that requires a valid session.
+/// Middleware that requires a valid session or API token.
 ///
 /// On success the authenticated `User` is inserted into request extensions,
 /// making it available to handlers via `Extension<User>`.
-/// On failure returns a 401 Unauthorized response.
+/// On failure returns a 401 Unauthorized response, or 403 Forbidden when an
+/// API token has no scope for the route.
 pub async fn require_auth(
     State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    let token = extract_session_cookie(&request).ok_or(AppError::Unauthorized)?;
+    // A session token whose cookie should be re-signed with the current secret.
+    let mut resign = None;
 
-    let user = validate_session(&state.db, &token).await?;
+    let user = if let Some(token) = extract_bearer_token(&request) {
+        let user = validate_token(&state.db, &token).await?;
+        let scopes = user.scopes.as_deref().unwrap_or_default();
+        if !api_tokens::allows(scopes, request.method(), request.uri().path()) {
+            return Err(AppError::Forbidden);
+        }
+        user
+    } else {
+        // Unsigned or forged cookies are turned away before the database lookup.
+        let cookie = session_cookie(request.headers()).ok_or(AppError::Unauthorized)?;
+        let verified = verify_cookie(&cookie, &state.config).ok_or(AppError::Unauthorized)?;
+        let user = validate_session(&state.db, &verified.token).await?;
+        if verified.needs_resign {
+            resign = Some(verified.token);
+        }
+        user
+    };
 
     // Insert the user into request extensions so handlers can retrieve it
     // with `Extension<User>` without hitting the database again.
     request.extensions_mut().insert(user);
 
-    Ok(next.run(request).await)
+    let mut response = next.run(request).await;
+    if let Some(token) = resign {
+        if let Ok(value) = HeaderValue::from_str(&session_set_cookie(&token, &state.config)) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+    Ok(response)
 }
 
-/// Middleware that requires the authenticated user to have the "admin" role.
+/// Middleware that requires the authenticated user's role to grant at least
+/// one of the given permissions (see `auth::permissions`).
 ///
-/// Must be applied **after** `require_auth` so that `User` is already in
-/// request extensions. Returns 403 Forbidden for non-admin users.
-pub async fn require_admin(
+/// Must be applied **after** `require_auth`. Returns 403 Forbidden otherwise.
+/// Attach it to a group of routes with the permissions as state:
+/// ```ignore
+/// .layer(from_fn_with_state(&[permissions::SETTINGS_MANAGE][..], require_permission))
+/// ```
+pub async fn require_permission(
+    State(permissions): State<&'static [&'static str]>,
     request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
@@ -78,10 +102,9 @@ pub async fn require_admin(
         .get::<crate::db::models::User>()
         .ok_or(AppError::Unauthorized)?;
 
-    if user.role != "admin" {
+    if !crate::auth::permissions::has_any(user, permissions) {
         return Err(AppError::Forbidden);
     }
 
     Ok(next.run(request).await)
 }
-
@@ -1,3 +1,6 @@
+pub mod api_tokens;
+pub mod id_token;
 pub mod middleware;
 pub mod oauth2;
+pub mod permissions;
 pub mod session;
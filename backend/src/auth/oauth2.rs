@@ -2,26 +2,52 @@
 //!
 //! This module handles the three legs of the authorization code flow:
 //!   1. Discover the provider's endpoints via `/.well-known/openid-configuration`
-//!   2. Build the redirect URL that sends the browser to the IdP
-//!   3. Exchange the authorization code for tokens and fetch user info
+//!   2. Build the redirect URL that sends the browser to the IdP, with a PKCE
+//!      challenge and a nonce
+//!   3. Exchange the authorization code for tokens, validate the ID token
+//!      (see `auth::id_token`) and fetch user info
+//!
+//! The discovery document and the issuer's keys are kept in an `OidcCache`
+//! rather than fetched on every login.
 //!
 //! All network calls go through a caller-supplied `reqwest::Client` so that
 //! connection pooling is controlled at the call site (typically AppState).
 
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use uuid::Uuid;
 
+use crate::auth::id_token::{self, Expected, IdTokenClaims, Jwks, Rejection};
+use crate::auth::session::{sign_token, verify_cookie};
 use crate::config::Config;
 use crate::error::{AppError, AppResult};
 
+/// How long the discovery document and JWKS are reused before being fetched
+/// again.
+const METADATA_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// An ID token signed with an unknown key makes us refetch the JWKS, since
+/// the issuer may have rotated its keys — but at most this often, so forged
+/// key ids cannot make every callback hit the issuer.
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Subset of the OpenID Connect discovery document we actually use.
 /// The full spec (RFC 8414) has many more fields; we only decode what we need
 /// so that provider-specific extras don't cause deserialization failures.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OidcDiscovery {
+    pub issuer: String,
     pub authorization_endpoint: String,
     pub token_endpoint: String,
     pub userinfo_endpoint: String,
+    pub jwks_uri: String,
 }
 
 /// Token response from the provider's token endpoint (RFC 6749 §5.1).
@@ -32,7 +58,6 @@ pub struct TokenResponse {
     pub token_type: String,
     #[allow(dead_code)]
     pub expires_in: Option<u64>,
-    #[allow(dead_code)]
     pub id_token: Option<String>,
 }
 
@@ -43,8 +68,12 @@ pub struct UserInfo {
     /// Provider-scoped unique identifier — stable and opaque.
     pub sub: String,
     pub email: Option<String>,
+    pub email_verified: Option<bool>,
     pub preferred_username: Option<String>,
     pub name: Option<String>,
+    /// Every claim, including the ones above, for role mapping.
+    #[serde(skip)]
+    pub raw: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Fetches and deserializes the OIDC discovery document from
@@ -69,16 +98,197 @@ pub async fn discover_oidc(client: &Client, issuer_url: &str) -> AppResult<OidcD
         .await
         .map_err(|e| AppError::Internal(format!("OIDC discovery parse failed: {e}")))?;
 
+    // The issuer must identify itself as the URL we asked (OIDC Discovery §4.3),
+    // since ID tokens are checked against it.
+    if discovery.issuer.trim_end_matches('/') != issuer_url.trim_end_matches('/') {
+        return Err(AppError::Internal(format!(
+            "OIDC discovery issuer '{}' does not match '{}'",
+            discovery.issuer, issuer_url
+        )));
+    }
+
     Ok(discovery)
 }
 
+/// Fetches the issuer's signing keys from `jwks_uri`.
+pub async fn fetch_jwks(client: &Client, discovery: &OidcDiscovery) -> AppResult<Jwks> {
+    client
+        .get(&discovery.jwks_uri)
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("JWKS request failed: {e}")))?
+        .json::<Jwks>()
+        .await
+        .map_err(|e| AppError::Internal(format!("JWKS parse failed: {e}")))
+}
+
+/// A fetched document and when it was fetched.
+struct Cached<T> {
+    value: Arc<T>,
+    fetched_at: Instant,
+}
+
+impl<T> Cached<T> {
+    fn new(value: T) -> Self {
+        Cached { value: Arc::new(value), fetched_at: Instant::now() }
+    }
+
+    fn younger_than(&self, age: Duration) -> bool {
+        self.fetched_at.elapsed() < age
+    }
+}
+
+/// Shared copy of the issuer's discovery document and JWKS, each refetched
+/// after `METADATA_TTL`.
+#[derive(Clone, Default)]
+pub struct OidcCache {
+    discovery: Arc<RwLock<Option<Cached<OidcDiscovery>>>>,
+    jwks: Arc<RwLock<Option<Cached<Jwks>>>>,
+}
+
+impl OidcCache {
+    /// Returns the discovery document, fetching it when missing or stale.
+    pub async fn discovery(
+        &self,
+        client: &Client,
+        issuer_url: &str,
+    ) -> AppResult<Arc<OidcDiscovery>> {
+        if let Some(cached) = self.discovery.read().await.as_ref() {
+            if cached.younger_than(METADATA_TTL) {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let mut guard = self.discovery.write().await;
+        if let Some(cached) = guard.as_ref() {
+            if cached.younger_than(METADATA_TTL) {
+                return Ok(cached.value.clone());
+            }
+        }
+        let cached = Cached::new(discover_oidc(client, issuer_url).await?);
+        let discovery = cached.value.clone();
+        *guard = Some(cached);
+        Ok(discovery)
+    }
+
+    /// Returns the issuer's keys, fetching them when missing or stale. With
+    /// `refresh`, keys older than `JWKS_REFRESH_INTERVAL` are refetched too.
+    async fn jwks(
+        &self,
+        client: &Client,
+        discovery: &OidcDiscovery,
+        refresh: bool,
+    ) -> AppResult<Arc<Jwks>> {
+        let max_age = if refresh { JWKS_REFRESH_INTERVAL } else { METADATA_TTL };
+        if let Some(cached) = self.jwks.read().await.as_ref() {
+            if cached.younger_than(max_age) {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let mut guard = self.jwks.write().await;
+        if let Some(cached) = guard.as_ref() {
+            if cached.younger_than(max_age) {
+                return Ok(cached.value.clone());
+            }
+        }
+        let cached = Cached::new(fetch_jwks(client, discovery).await?);
+        let jwks = cached.value.clone();
+        *guard = Some(cached);
+        Ok(jwks)
+    }
+
+    /// Validates an ID token for this login (see `id_token::validate`). A token
+    /// signed with a key we don't know refetches the JWKS once, to pick up
+    /// rotated keys.
+    pub async fn verify_id_token(
+        &self,
+        client: &Client,
+        discovery: &OidcDiscovery,
+        config: &Config,
+        token: &str,
+        nonce: &str,
+    ) -> AppResult<IdTokenClaims> {
+        let expected = Expected {
+            issuer: &discovery.issuer,
+            client_id: &config.oauth2_client_id,
+            client_secret: &config.oauth2_client_secret,
+            nonce,
+            now: chrono::Utc::now().timestamp(),
+        };
+
+        let jwks = self.jwks(client, discovery, false).await?;
+        let result = match id_token::validate(token, &jwks, &expected) {
+            Err(Rejection::UnknownKey) => {
+                let jwks = self.jwks(client, discovery, true).await?;
+                id_token::validate(token, &jwks, &expected)
+            }
+            result => result,
+        };
+
+        result.map_err(|e| {
+            tracing::warn!("Rejected ID token: {e}");
+            AppError::BadRequest(format!("Invalid ID token: {e}"))
+        })
+    }
+}
+
+/// Per-login values that must survive the round trip through the IdP: the
+/// CSRF `state`, the ID token `nonce` and the PKCE code verifier. They travel
+/// in a signed, short-lived cookie.
+pub struct AuthRequest {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+impl AuthRequest {
+    /// Generates fresh random values for a new login.
+    pub fn generate() -> Self {
+        AuthRequest {
+            state: Uuid::new_v4().to_string(),
+            nonce: Uuid::new_v4().simple().to_string(),
+            // 64 characters, within RFC 7636's 43-128.
+            code_verifier: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+        }
+    }
+
+    /// The S256 PKCE challenge: base64url(SHA-256(verifier)).
+    pub fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+
+    /// The signed cookie value carrying this request.
+    pub fn to_cookie(&self, config: &Config) -> String {
+        let value = format!("{}:{}:{}", self.state, self.nonce, self.code_verifier);
+        sign_token(&value, &config.session_secret)
+    }
+
+    /// Reads a cookie written by `to_cookie`. Returns `None` if it is
+    /// malformed or its signature does not check out.
+    pub fn from_cookie(value: &str, config: &Config) -> Option<Self> {
+        let verified = verify_cookie(value, config)?;
+        let mut parts = verified.token.split(':');
+        let (Some(state), Some(nonce), Some(code_verifier), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        Some(AuthRequest {
+            state: state.to_string(),
+            nonce: nonce.to_string(),
+            code_verifier: code_verifier.to_string(),
+        })
+    }
+}
+
 /// Builds the authorization URL that the browser should be redirected to.
 ///
 /// Uses `response_type=code` (authorization code flow) and requests the
-/// `openid email profile` scopes. The `state` parameter is an opaque value
-/// generated by the caller; it should be stored in the user's session and
-/// verified when the callback arrives to guard against CSRF.
-pub fn build_auth_url(discovery: &OidcDiscovery, config: &Config, state: &str) -> String {
+/// `openid email profile` scopes. The request's `state` is verified when the
+/// callback arrives to guard against CSRF; its `nonce` must come back in the
+/// ID token, and its code challenge binds the code to this browser (PKCE).
+pub fn build_auth_url(discovery: &OidcDiscovery, config: &Config, request: &AuthRequest) -> String {
     let redirect_uri = format!(
         "{}/api/auth/callback",
         config.base_url.trim_end_matches('/')
@@ -86,26 +296,30 @@ pub fn build_auth_url(discovery: &OidcDiscovery, config: &Config, state: &str) -
 
     // Build the query string manually so we don't need an extra URL-building
     // dependency. All values here are either static strings or already-encoded
-    // config values; the only runtime value that needs encoding is `state`.
+    // config values; the per-login values are encoded too for good measure.
     format!(
-        "{}?response_type=code&client_id={}&redirect_uri={}&scope=openid+email+profile&state={}",
+        "{}?response_type=code&client_id={}&redirect_uri={}&scope=openid+email+profile&state={}\
+         &nonce={}&code_challenge={}&code_challenge_method=S256",
         discovery.authorization_endpoint,
         urlencoding::encode(&config.oauth2_client_id),
         urlencoding::encode(&redirect_uri),
-        urlencoding::encode(state),
+        urlencoding::encode(&request.state),
+        urlencoding::encode(&request.nonce),
+        urlencoding::encode(&request.code_challenge()),
     )
 }
 
-/// Exchanges an authorization code for an access token (and optionally an
-/// ID token) by posting to the provider's token endpoint.
+/// Exchanges an authorization code for an access token and an ID token by
+/// posting to the provider's token endpoint.
 ///
 /// Uses HTTP Basic authentication with the client credentials, which is the
-/// most interoperable method across providers.
+/// most interoperable method across providers, and sends the PKCE verifier.
 pub async fn exchange_code(
     client: &Client,
     discovery: &OidcDiscovery,
     config: &Config,
     code: &str,
+    code_verifier: &str,
 ) -> AppResult<TokenResponse> {
     let redirect_uri = format!(
         "{}/api/auth/callback",
@@ -116,6 +330,7 @@ pub async fn exchange_code(
         ("grant_type", "authorization_code"),
         ("code", code),
         ("redirect_uri", redirect_uri.as_str()),
+        ("code_verifier", code_verifier),
     ];
 
     let response = client
@@ -163,8 +378,147 @@ pub async fn fetch_userinfo(
         )));
     }
 
-    response
-        .json::<UserInfo>()
+    let raw = response
+        .json::<serde_json::Map<String, serde_json::Value>>()
         .await
-        .map_err(|e| AppError::Internal(format!("Userinfo parse failed: {e}")))
+        .map_err(|e| AppError::Internal(format!("Userinfo parse failed: {e}")))?;
+    let mut userinfo = UserInfo::deserialize(&serde_json::Value::Object(raw.clone()))
+        .map_err(|e| AppError::Internal(format!("Userinfo parse failed: {e}")))?;
+    userinfo.raw = raw;
+    Ok(userinfo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use axum::{extract::State, routing::get, Json, Router};
+    use serde_json::{json, Value};
+
+    use crate::auth::id_token::tests::EcKey;
+
+    /// A local issuer serving discovery and JWKS, counting requests.
+    #[derive(Clone, Default)]
+    struct Issuer {
+        url: Arc<String>,
+        keys: Arc<Mutex<Vec<Value>>>,
+        discovery_hits: Arc<AtomicUsize>,
+        jwks_hits: Arc<AtomicUsize>,
+    }
+
+    async fn discovery_document(State(issuer): State<Issuer>) -> Json<Value> {
+        issuer.discovery_hits.fetch_add(1, Ordering::SeqCst);
+        let url = issuer.url.as_str();
+        Json(json!({
+            "issuer": url,
+            "authorization_endpoint": format!("{url}/authorize"),
+            "token_endpoint": format!("{url}/token"),
+            "userinfo_endpoint": format!("{url}/userinfo"),
+            "jwks_uri": format!("{url}/jwks"),
+        }))
+    }
+
+    async fn jwks_document(State(issuer): State<Issuer>) -> Json<Value> {
+        issuer.jwks_hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({ "keys": *issuer.keys.lock().unwrap() }))
+    }
+
+    async fn userinfo_document() -> Json<Value> {
+        Json(json!({
+            "sub": "user-1",
+            "email": "ada@example.com",
+            "email_verified": true,
+            "name": "Ada Lovelace",
+            "groups": ["pawtal-admins"],
+        }))
+    }
+
+    async fn serve_issuer() -> Issuer {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = Issuer {
+            url: Arc::new(format!("http://{}", listener.local_addr().unwrap())),
+            ..Issuer::default()
+        };
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery_document))
+            .route("/jwks", get(jwks_document))
+            .route("/userinfo", get(userinfo_document))
+            .with_state(issuer.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        issuer
+    }
+
+    fn config(issuer_url: &str) -> Config {
+        Config {
+            port: 8080,
+            database_url: String::new(),
+            uploads_dir: String::new(),
+            oauth2_client_id: "pawtal".into(),
+            oauth2_client_secret: "client-secret".into(),
+            oauth2_issuer_url: issuer_url.into(),
+            session_secret: "secret".into(),
+            session_secret_previous: None,
+            base_url: String::new(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_metadata_and_refetches_rotated_keys() {
+        let issuer = serve_issuer().await;
+        let config = config(&issuer.url);
+        let client = Client::new();
+        let cache = OidcCache::default();
+        let claims = json!({
+            "iss": issuer.url.as_str(),
+            "sub": "user-1",
+            "aud": "pawtal",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": "n-1",
+        });
+        let old_key = EcKey::generate("k1");
+        let new_key = EcKey::generate("k2");
+        *issuer.keys.lock().unwrap() = vec![old_key.jwk()];
+
+        let discovery = cache.discovery(&client, &issuer.url).await.unwrap();
+        cache.discovery(&client, &issuer.url).await.unwrap();
+        assert_eq!(issuer.discovery_hits.load(Ordering::SeqCst), 1);
+
+        let verify = |token: String| {
+            let (cache, client, discovery, config) = (&cache, &client, &discovery, &config);
+            async move { cache.verify_id_token(client, discovery, config, &token, "n-1").await }
+        };
+        assert!(verify(old_key.sign(&claims)).await.is_ok());
+        assert!(verify(old_key.sign(&claims)).await.is_ok());
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 1);
+
+        // The issuer rotates its keys. Keys fetched within the refresh interval
+        // are not refetched, whatever kid a token names.
+        *issuer.keys.lock().unwrap() = vec![new_key.jwk()];
+        assert!(verify(new_key.sign(&claims)).await.is_err());
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 1);
+
+        // Once they are older, an unknown kid refetches them, once.
+        if let Some(cached) = cache.jwks.write().await.as_mut() {
+            cached.fetched_at -= JWKS_REFRESH_INTERVAL;
+        }
+        assert!(verify(new_key.sign(&claims)).await.is_ok());
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 2);
+        assert!(verify(old_key.sign(&claims)).await.is_err());
+        assert!(verify(new_key.sign(&claims)).await.is_ok());
+        assert_eq!(issuer.jwks_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn userinfo_keeps_every_claim() {
+        let issuer = serve_issuer().await;
+        let client = Client::new();
+        let discovery = discover_oidc(&client, &issuer.url).await.unwrap();
+
+        let userinfo = fetch_userinfo(&client, &discovery, "token").await.unwrap();
+        assert_eq!(userinfo.email.as_deref(), Some("ada@example.com"));
+        assert_eq!(Value::Object(userinfo.raw), userinfo_document().await.0);
+    }
 }
@@ -0,0 +1,58 @@
+//! The permission set that roles are built from.
+//!
+//! A role grants a list of these permissions; the `require_permission`
+//! middleware checks them per route group, and the services check the
+//! own-versus-any distinctions that depend on the content being touched.
+//! The `admin` role always holds every permission.
+
+use crate::db::models::User;
+
+pub const ADMIN_ROLE: &str = "admin";
+
+pub const CONTENT_VIEW: &str = "content.view";
+pub const CONTENT_CREATE: &str = "content.create";
+pub const CONTENT_EDIT_OWN: &str = "content.edit_own";
+pub const CONTENT_EDIT_ANY: &str = "content.edit_any";
+pub const CONTENT_PUBLISH_OWN: &str = "content.publish_own";
+pub const CONTENT_PUBLISH_ANY: &str = "content.publish_any";
+pub const TAXONOMY_MANAGE: &str = "taxonomy.manage";
+pub const MEDIA_UPLOAD: &str = "media.upload";
+pub const MEDIA_DELETE: &str = "media.delete";
+pub const MENUS_MANAGE: &str = "menus.manage";
+pub const APPS_MANAGE: &str = "apps.manage";
+pub const AUDIT_VIEW: &str = "audit.view";
+pub const SETTINGS_MANAGE: &str = "settings.manage";
+pub const USERS_MANAGE: &str = "users.manage";
+
+/// Every permission with a short description, in display order.
+pub const ALL: &[(&str, &str)] = &[
+    (CONTENT_VIEW, "See the admin area and all pages and articles"),
+    (CONTENT_CREATE, "Create pages and articles"),
+    (CONTENT_EDIT_OWN, "Edit, submit and trash own content and content shared with them"),
+    (CONTENT_EDIT_ANY, "Edit, submit and trash anyone's content"),
+    (CONTENT_PUBLISH_OWN, "Publish, approve and reject own content"),
+    (CONTENT_PUBLISH_ANY, "Publish, approve and reject anyone's content"),
+    (TAXONOMY_MANAGE, "Manage categories, tags and series"),
+    (MEDIA_UPLOAD, "Upload media"),
+    (MEDIA_DELETE, "Delete media"),
+    (MENUS_MANAGE, "Edit navigation menus"),
+    (APPS_MANAGE, "Manage apps"),
+    (AUDIT_VIEW, "Read the audit log"),
+    (SETTINGS_MANAGE, "Change settings and redirects, and empty the trash"),
+    (USERS_MANAGE, "Manage users, roles and other users' author profiles"),
+];
+
+/// Returns true when `name` is a known permission.
+pub fn is_known(name: &str) -> bool {
+    ALL.iter().any(|(known, _)| *known == name)
+}
+
+/// Returns true when the user's role grants `permission`.
+pub fn has(user: &User, permission: &str) -> bool {
+    user.role == ADMIN_ROLE || user.permissions.iter().any(|p| p == permission)
+}
+
+/// Returns true when the user's role grants any of `permissions`.
+pub fn has_any(user: &User, permissions: &[&str]) -> bool {
+    permissions.iter().any(|p| has(user, p))
+}
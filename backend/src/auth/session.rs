@@ -4,43 +4,165 @@
 //! HttpOnly cookie. The token is hashed with SHA-256 before storage so that a
 //! database leak does not directly expose usable session credentials.
 //!
-//! Sessions expire after 7 days. Expired session cleanup can be triggered on a
-//! schedule or opportunistically.
+//! The cookie carries the token followed by an HMAC-SHA256 signature keyed
+//! with `Config::session_secret`, so malformed or forged cookies are rejected
+//! without touching the database. After a secret rotation, cookies signed with
+//! `session_secret_previous` are still accepted (and re-signed) until that
+//! setting is removed.
+//!
+//! Sessions expire after 7 days without use, and 30 days after sign-in at the
+//! latest; each use pushes the idle expiry forward. Expired session cleanup can
+//! be triggered on a schedule or opportunistically. Each session records the
+//! user agent and IP it was created from and when it was last used, so users
+//! can review and revoke their sessions and admins can sign a user out
+//! everywhere.
 
+use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::db::models::User;
+use axum::http::HeaderMap;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::db::models::{SessionInfo, User};
 use crate::error::{AppError, AppResult};
+use crate::services::{audit, roles};
 
 /// Returns the hex-encoded SHA-256 hash of a session token.
-fn hash_token(token: &str) -> String {
+pub(crate) fn hash_token(token: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
-/// How long a session lives before it expires (7 days in seconds).
-const SESSION_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+/// How long a session lives without being used (7 days in seconds).
+const SESSION_IDLE_TIMEOUT_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// How long a session lives at most, however often it is used (30 days in
+/// seconds). Also the cookie's `Max-Age`.
+pub const SESSION_MAX_LIFETIME_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// `last_seen_at` and the idle expiry are refreshed at most this often, to
+/// spare a write per request.
+const LAST_SEEN_RESOLUTION_SECONDS: i64 = 60;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Returns the cookie value for a session token: the token, a `.`, and the
+/// hex-encoded HMAC-SHA256 of the token under `secret`.
+pub fn sign_token(token: &str, secret: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(token.as_bytes());
+    format!("{}.{:x}", token, mac.finalize().into_bytes())
+}
+
+/// A session token whose cookie signature checked out.
+pub struct VerifiedToken {
+    pub token: String,
+    /// Signed with `session_secret_previous`; the cookie should be re-signed.
+    pub needs_resign: bool,
+}
+
+/// Checks a cookie value's signature against the current secret, then the
+/// previous one. Returns `None` for unsigned, malformed or forged values.
+pub fn verify_cookie(value: &str, config: &Config) -> Option<VerifiedToken> {
+    let (token, signature) = value.rsplit_once('.')?;
+    let signature = decode_hex(signature)?;
+    let matches = |secret: &str| {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).ok()?;
+        mac.update(token.as_bytes());
+        mac.verify_slice(&signature).ok()
+    };
+    if matches(&config.session_secret).is_some() {
+        return Some(VerifiedToken { token: token.to_string(), needs_resign: false });
+    }
+    let previous = config.session_secret_previous.as_deref().filter(|s| !s.is_empty())?;
+    matches(previous)?;
+    Some(VerifiedToken { token: token.to_string(), needs_resign: true })
+}
+
+/// Returns the verified session token from the request's cookie, if any.
+pub(crate) fn session_token(headers: &HeaderMap, config: &Config) -> Option<String> {
+    let value = session_cookie(headers)?;
+    verify_cookie(&value, config).map(|v| v.token)
+}
+
+/// Builds the `Set-Cookie` value that stores a session token, signed with the
+/// current secret.
+pub fn session_set_cookie(token: &str, config: &Config) -> String {
+    let secure_flag = if config.base_url.starts_with("https") {
+        "; Secure"
+    } else {
+        ""
+    };
+    format!(
+        "pawtal_session={}; HttpOnly; SameSite=Strict; Path=/; Max-Age={}{}",
+        sign_token(token, &config.session_secret),
+        SESSION_MAX_LIFETIME_SECONDS,
+        secure_flag
+    )
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Extracts the `pawtal_session` cookie value from the raw `Cookie` header.
+///
+/// The Cookie header is a semicolon-separated list of `name=value` pairs.
+/// We look for the specific cookie name and return its value if found.
+pub(crate) fn session_cookie(headers: &HeaderMap) -> Option<String> {
+    let cookie_header = headers.get("cookie")?.to_str().ok()?;
+
+    // Parse "name=value; name2=value2; ..." pairs
+    for pair in cookie_header.split(';') {
+        let pair = pair.trim();
+        if let Some((name, value)) = pair.split_once('=') {
+            if name.trim() == "pawtal_session" {
+                return Some(value.trim().to_string());
+            }
+        }
+    }
+
+    None
+}
 
 /// Creates a new session for the given user and returns the session token.
 ///
-/// The token is a UUID v4 string. It is stored directly in the `sessions` table
-/// and returned to the caller to be set as a cookie value.
-pub async fn create_session(pool: &SqlitePool, user_id: &str) -> AppResult<String> {
+/// The token is a UUID v4 string. Its hash is stored in the `sessions` table,
+/// with the client's user agent and IP, and the token is returned to the
+/// caller to be set as a cookie value.
+pub async fn create_session(
+    pool: &SqlitePool,
+    user_id: &str,
+    user_agent: &str,
+    ip: &str,
+) -> AppResult<String> {
     let session_id = Uuid::new_v4().to_string();
     let token = Uuid::new_v4().to_string();
     let token_hash = hash_token(&token);
 
     sqlx::query(
         r#"
-        INSERT INTO sessions (id, user_id, token, expires_at, created_at)
+        INSERT INTO sessions
+            (id, user_id, token, expires_at, created_at, user_agent, ip, last_seen_at)
         VALUES (
             ?,
             ?,
             ?,
             strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ? || ' seconds'),
+            strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+            ?,
+            ?,
             strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
         )
         "#,
@@ -48,7 +170,9 @@ pub async fn create_session(pool: &SqlitePool, user_id: &str) -> AppResult<Strin
     .bind(&session_id)
     .bind(user_id)
     .bind(&token_hash)
-    .bind(SESSION_TTL_SECONDS.to_string())
+    .bind(SESSION_IDLE_TIMEOUT_SECONDS.to_string())
+    .bind(user_agent)
+    .bind(ip)
     .execute(pool)
     .await?;
 
@@ -59,15 +183,16 @@ pub async fn create_session(pool: &SqlitePool, user_id: &str) -> AppResult<Strin
 /// Validates a session token and returns the associated `User`.
 ///
 /// Returns `AppError::Unauthorized` if the token does not exist or has expired.
+/// Using a session extends its idle expiry, up to its maximum lifetime.
 /// Expired sessions are left in the database; use `cleanup_expired_sessions` to
 /// remove them in bulk.
 pub async fn validate_session(pool: &SqlitePool, token: &str) -> AppResult<User> {
     let token_hash = hash_token(token);
 
-    let user = sqlx::query_as::<_, User>(
+    let mut user = sqlx::query_as::<_, User>(
         r#"
         SELECT u.id, u.external_id, u.email, u.display_name, u.role,
-               u.created_at, u.last_login
+               u.created_at, u.last_login, u.is_service
         FROM sessions s
         JOIN users u ON u.id = s.user_id
         WHERE s.token = ?
@@ -79,6 +204,24 @@ pub async fn validate_session(pool: &SqlitePool, token: &str) -> AppResult<User>
     .await?
     .ok_or(AppError::Unauthorized)?;
 
+    user.permissions = roles::role_permissions(pool, &user.role).await?;
+
+    sqlx::query(
+        "UPDATE sessions \
+         SET last_seen_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), \
+             expires_at = MIN( \
+                 strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?1 || ' seconds'), \
+                 strftime('%Y-%m-%dT%H:%M:%SZ', created_at, ?2 || ' seconds')) \
+         WHERE token = ?3 \
+           AND last_seen_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?4 || ' seconds')",
+    )
+    .bind(SESSION_IDLE_TIMEOUT_SECONDS.to_string())
+    .bind(SESSION_MAX_LIFETIME_SECONDS.to_string())
+    .bind(&token_hash)
+    .bind((-LAST_SEEN_RESOLUTION_SECONDS).to_string())
+    .execute(pool)
+    .await?;
+
     Ok(user)
 }
 
@@ -97,3 +240,145 @@ pub async fn delete_session(pool: &SqlitePool, token: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Returns a user's unexpired sessions, most recently used first. The session
+/// whose token is `current_token` is flagged as current.
+pub async fn list_sessions(
+    pool: &SqlitePool,
+    user_id: &str,
+    current_token: Option<&str>,
+) -> AppResult<Vec<SessionInfo>> {
+    let sessions = sqlx::query_as::<_, SessionInfo>(
+        "SELECT id, user_agent, ip, created_at, last_seen_at, expires_at, \
+                token = ? AS current \
+         FROM sessions \
+         WHERE user_id = ? AND expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         ORDER BY last_seen_at DESC",
+    )
+    .bind(current_token.map(hash_token).unwrap_or_default())
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Ends one of `user_id`'s sessions. Returns `NotFound` if it has no such
+/// session.
+pub async fn revoke_session(
+    pool: &SqlitePool,
+    user_id: &str,
+    session_id: &str,
+    acting_user_id: &str,
+) -> AppResult<()> {
+    let result = sqlx::query("DELETE FROM sessions WHERE id = ? AND user_id = ?")
+        .bind(session_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    audit::log_action(
+        pool,
+        acting_user_id,
+        "revoke_session",
+        "user",
+        user_id,
+        &json!({ "session_id": session_id }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Ends all of `user_id`'s sessions, except the one with `keep_token` if
+/// given. Returns how many were ended.
+pub async fn revoke_user_sessions(
+    pool: &SqlitePool,
+    user_id: &str,
+    keep_token: Option<&str>,
+    acting_user_id: &str,
+) -> AppResult<u64> {
+    let result = sqlx::query("DELETE FROM sessions WHERE user_id = ? AND token != ?")
+        .bind(user_id)
+        .bind(keep_token.map(hash_token).unwrap_or_default())
+        .execute(pool)
+        .await?;
+
+    audit::log_action(
+        pool,
+        acting_user_id,
+        "revoke_sessions",
+        "user",
+        user_id,
+        &json!({ "count": result.rows_affected() }),
+    )
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(previous: Option<&str>) -> Config {
+        Config {
+            port: 8080,
+            database_url: String::new(),
+            uploads_dir: String::new(),
+            oauth2_client_id: String::new(),
+            oauth2_client_secret: String::new(),
+            oauth2_issuer_url: String::new(),
+            session_secret: "current".into(),
+            session_secret_previous: previous.map(str::to_owned),
+            base_url: String::new(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn signed_cookies_verify() {
+        let cookie = sign_token("tok", "current");
+        let verified = verify_cookie(&cookie, &config(None)).unwrap();
+        assert_eq!(verified.token, "tok");
+        assert!(!verified.needs_resign);
+    }
+
+    #[test]
+    fn forged_and_unsigned_cookies_are_rejected() {
+        let config = config(None);
+        assert!(verify_cookie(&sign_token("tok", "other"), &config).is_none());
+        // A valid signature does not carry over to another token.
+        let cookie = sign_token("tok", "current");
+        let signature = cookie.rsplit_once('.').unwrap().1;
+        assert!(verify_cookie(&format!("tok2.{signature}"), &config).is_none());
+        // Cookies from before signing are a bare UUID.
+        assert!(verify_cookie("0f8d7781-aa5b-4a41-81d1-58e17acc2cd1", &config).is_none());
+    }
+
+    #[test]
+    fn previous_secret_is_accepted_until_unset() {
+        let cookie = sign_token("tok", "old");
+        let verified = verify_cookie(&cookie, &config(Some("old"))).unwrap();
+        assert_eq!(verified.token, "tok");
+        assert!(verified.needs_resign);
+
+        assert!(verify_cookie(&cookie, &config(None)).is_none());
+        // An empty `SESSION_SECRET_PREVIOUS` is ignored rather than used as a key.
+        let empty = sign_token("tok", "");
+        assert!(verify_cookie(&empty, &config(Some(""))).is_none());
+    }
+
+    #[test]
+    fn hex_signatures_are_decoded_strictly() {
+        assert_eq!(decode_hex("00ff7a"), Some(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("+f"), None);
+        assert_eq!(decode_hex("éé"), None);
+        assert!(verify_cookie("tok.abc", &config(None)).is_none());
+        assert!(verify_cookie("tok.zz", &config(None)).is_none());
+    }
+}
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use std::net::IpAddr;
+
+use serde::{Deserialize, Deserializer};
 
 /// Application configuration loaded from environment variables via `envy`.
 ///
@@ -27,19 +29,47 @@ pub struct Config {
     /// discovery and token exchange.
     pub oauth2_issuer_url: String,
 
-    /// Secret used to sign/verify session tokens. Must be long and random in
+    /// Secret used to sign/verify session cookies. Must be long and random in
     /// production — treat it like a password.
     pub session_secret: String,
 
+    /// The session secret in use before the last rotation. Cookies signed with
+    /// it keep working, and are re-signed with `session_secret` on their next
+    /// request, until this is unset.
+    #[serde(default)]
+    pub session_secret_previous: Option<String>,
+
     /// Public base URL of this application, e.g. `https://your-domain.com`.
     /// Used when constructing OAuth2 redirect URIs.
     pub base_url: String,
+
+    /// Comma-separated addresses of reverse proxies in front of this server.
+    /// `X-Forwarded-For` is only believed on requests coming from one of them;
+    /// otherwise the client address is the connection's peer address.
+    #[serde(default, deserialize_with = "ip_list")]
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 fn default_port() -> u16 {
     8080
 }
 
+/// Parses a comma-separated list of IP addresses. Blank entries are skipped,
+/// so an empty variable means no addresses.
+fn ip_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<IpAddr>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse()
+                .map_err(|_| serde::de::Error::custom(format!("invalid IP address '{entry}'")))
+        })
+        .collect()
+}
+
 impl Config {
     /// Load configuration from the current process environment.
     ///
@@ -1,4 +1,5 @@
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use sqlx::{ConnectOptions, Connection};
 use std::str::FromStr;
 
 pub mod models;
@@ -15,15 +16,29 @@ pub async fn create_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error>
         .foreign_keys(true)
         .journal_mode(SqliteJournalMode::Wal);
 
+    // Apply all pending migrations from the ./migrations directory.
+    // The macro embeds migration files at compile time so the binary is
+    // self-contained — no migrations directory needed at runtime.
+    //
+    // Migrations run on a dedicated connection with foreign key enforcement
+    // off: SQLite can only change constraints by rebuilding a table, and
+    // dropping the old table would otherwise cascade into its child rows. The
+    // pragma cannot be toggled inside the transaction each migration runs in,
+    // so it has to be set on the connection up front.
+    let mut conn = connect_options.clone().foreign_keys(false).connect().await?;
+    sqlx::migrate!("./migrations").run(&mut conn).await?;
+    let violations: Vec<(String,)> = sqlx::query_as("PRAGMA foreign_key_check")
+        .fetch_all(&mut conn)
+        .await?;
+    for (table,) in &violations {
+        tracing::warn!("foreign key violation in table {table} after migrations");
+    }
+    conn.close().await?;
+
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
         .connect_with(connect_options)
         .await?;
 
-    // Apply all pending migrations from the ./migrations directory.
-    // The macro embeds migration files at compile time so the binary is
-    // self-contained — no migrations directory needed at runtime.
-    sqlx::migrate!("./migrations").run(&pool).await?;
-
     Ok(pool)
 }
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 // ─── Read models (database rows) ─────────────────────────────────────────────
@@ -17,6 +17,75 @@ pub struct User {
     pub role: String,
     pub created_at: DateTime<Utc>,
     pub last_login: DateTime<Utc>,
+    /// Service accounts authenticate with API tokens only, never through OIDC.
+    pub is_service: bool,
+    /// Permissions granted by `role`; loaded with the session, empty otherwise.
+    #[sqlx(skip)]
+    pub permissions: Vec<String>,
+    /// Scopes of the API token the request was made with; `None` for sessions.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+}
+
+/// A named set of permissions. Built-in roles cannot be changed or deleted.
+#[derive(Debug, Clone, Serialize)]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub builtin: bool,
+    pub user_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A signed-in browser session, as listed to its user and to admins.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SessionInfo {
+    pub id: String,
+    pub user_agent: String,
+    pub ip: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// True for the session the request was made with.
+    pub current: bool,
+}
+
+/// An API token as listed; the token itself is only shown once, on creation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A newly created API token, with the secret to send as `Bearer`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NewApiToken {
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+    pub token: String,
+}
+
+/// One entry of the set of scopes an API token can carry.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopeInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// One entry of the permission set roles are built from.
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionInfo {
+    pub name: &'static str,
+    pub description: &'static str,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -25,13 +94,28 @@ pub struct Page {
     pub title: String,
     pub slug: String,
     pub content: String,
+    /// `html` or `markdown`; says how `content` is written.
+    pub content_format: String,
+    /// `content` rendered and sanitized; what the public site displays.
+    pub content_html: String,
     pub status: String,
     pub publish_at: Option<DateTime<Utc>>,
+    /// When set, the page stops being served at this time and is archived.
+    pub unpublish_at: Option<DateTime<Utc>>,
     pub author_id: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub trashed_at: Option<DateTime<Utc>>,
     pub template: String,
+    /// Parent page for nested hierarchies; `None` for top-level pages.
+    pub parent_id: Option<String>,
+    /// Position among siblings (ascending).
+    pub sort_order: i32,
+    /// Slugs of all ancestors plus this page's own slug, joined with `/`
+    /// (e.g. `guides/install/linux`). Maintained by the pages service.
+    pub path: String,
+    /// Reviewer's note from the most recent rejection; cleared on approval.
+    pub review_comment: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -40,8 +124,13 @@ pub struct PageRevision {
     pub page_id: String,
     pub title: String,
     pub content: String,
+    pub content_format: String,
     pub author_id: String,
     pub created_at: DateTime<Utc>,
+    /// Note supplied with the update that created this revision.
+    pub message: Option<String>,
+    /// Set when the revision is pinned as a named version.
+    pub version_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -51,14 +140,65 @@ pub struct Article {
     pub slug: String,
     pub short_text: String,
     pub content: String,
+    /// `html` or `markdown`; says how `content` is written.
+    pub content_format: String,
+    /// `content` rendered and sanitized; what the public site displays.
+    pub content_html: String,
     pub status: String,
     pub publish_at: Option<DateTime<Utc>>,
+    /// When set, the article stops being served at this time and is archived.
+    pub unpublish_at: Option<DateTime<Utc>>,
     pub author_id: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub trashed_at: Option<DateTime<Utc>>,
     pub cover_image_id: Option<String>,
     pub reading_time_minutes: i32,
+    /// Reviewer's note from the most recent rejection; cleared on approval.
+    pub review_comment: Option<String>,
+}
+
+/// An article as the public endpoints return it. `author_id` is left out: it
+/// is the account that created the article, not necessarily anyone on its
+/// byline, and users only appear publicly through their author profile. The
+/// trash and review fields are internal too.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PublishedArticle {
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    pub short_text: String,
+    pub content: String,
+    pub content_format: String,
+    pub content_html: String,
+    pub status: String,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub unpublish_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub cover_image_id: Option<String>,
+    pub reading_time_minutes: i32,
+}
+
+impl From<Article> for PublishedArticle {
+    fn from(article: Article) -> Self {
+        PublishedArticle {
+            id: article.id,
+            title: article.title,
+            slug: article.slug,
+            short_text: article.short_text,
+            content: article.content,
+            content_format: article.content_format,
+            content_html: article.content_html,
+            status: article.status,
+            publish_at: article.publish_at,
+            unpublish_at: article.unpublish_at,
+            created_at: article.created_at,
+            updated_at: article.updated_at,
+            cover_image_id: article.cover_image_id,
+            reading_time_minutes: article.reading_time_minutes,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -68,8 +208,89 @@ pub struct ArticleRevision {
     pub title: String,
     pub short_text: String,
     pub content: String,
+    pub content_format: String,
     pub author_id: String,
     pub created_at: DateTime<Utc>,
+    /// Note supplied with the update that created this revision.
+    pub message: Option<String>,
+    /// Set when the revision is pinned as a named version.
+    pub version_name: Option<String>,
+}
+
+/// Pending edits to a published page, not yet visible to the public.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PageDraft {
+    pub page_id: String,
+    pub title: String,
+    pub content: String,
+    pub content_format: String,
+    pub author_id: String,
+    pub updated_at: DateTime<Utc>,
+    /// Submitted for review; approving it publishes the draft.
+    pub in_review: bool,
+    pub review_comment: Option<String>,
+}
+
+/// Pending edits to a published article, not yet visible to the public.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArticleDraft {
+    pub article_id: String,
+    pub title: String,
+    pub short_text: String,
+    pub content: String,
+    pub content_format: String,
+    pub cover_image_id: Option<String>,
+    pub author_id: String,
+    pub updated_at: DateTime<Utc>,
+    /// Submitted for review; approving it publishes the draft.
+    pub in_review: bool,
+    pub review_comment: Option<String>,
+}
+
+/// A page as the admin API returns it: the live row plus any pending draft,
+/// its categories and its tags.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageWithDraft {
+    #[serde(flatten)]
+    pub page: Page,
+    pub draft: Option<PageDraft>,
+    pub categories: Vec<Category>,
+    pub tags: Vec<Tag>,
+}
+
+/// An article as the admin API returns it: the live row plus any pending
+/// draft, its categories and its tags.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArticleWithDraft {
+    #[serde(flatten)]
+    pub article: Article,
+    pub draft: Option<ArticleDraft>,
+    pub categories: Vec<Category>,
+    pub tags: Vec<Tag>,
+    /// Credited authors in byline order.
+    pub authors: Vec<AuthorOption>,
+}
+
+/// A published page as the public detail endpoint returns it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicPage {
+    #[serde(flatten)]
+    pub page: Page,
+    pub categories: Vec<Category>,
+    pub tags: Vec<Tag>,
+}
+
+/// A published article as the public detail endpoint returns it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicArticle {
+    #[serde(flatten)]
+    pub article: PublishedArticle,
+    /// Credited authors in byline order.
+    pub authors: Vec<PublicAuthor>,
+    pub categories: Vec<Category>,
+    pub tags: Vec<Tag>,
+    /// The series the article is part of, with links to its neighbours.
+    pub series: Option<SeriesMembership>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -77,6 +298,61 @@ pub struct Category {
     pub id: String,
     pub name: String,
     pub slug: String,
+    /// Parent category; `None` for top-level categories.
+    pub parent_id: Option<String>,
+    pub description: String,
+    /// Optional cover image from the media library.
+    pub cover_image_id: Option<String>,
+    /// Position among siblings (ascending).
+    pub sort_order: i32,
+}
+
+/// A category with the number of published pages and articles in it or in
+/// any of its descendants.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CategoryUsage {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub category: Category,
+    pub page_count: i64,
+    pub article_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+}
+
+/// A tag with the number of pages and articles it is assigned to.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TagUsage {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub page_count: i64,
+    pub article_count: i64,
+}
+
+/// Published content carrying a tag, as `GET /api/tags/:slug` returns it.
+#[derive(Debug, Serialize)]
+pub struct TagListing {
+    pub tag: Tag,
+    pub pages: Vec<Page>,
+    pub articles: PaginatedResponse<PublishedArticle>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenameTag {
+    pub name: String,
+    pub slug: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeTag {
+    /// The tag that takes over the merged tag's assignments.
+    pub into_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -110,10 +386,118 @@ pub struct App {
     pub icon_filename: Option<String>,
 }
 
+/// A link shown on an author profile, e.g. a website or social account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// A user's author profile as its owner and admins see it.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorProfile {
+    pub user_id: String,
+    pub display_name: String,
+    pub slug: String,
+    pub bio: String,
+    pub avatar_id: Option<String>,
+    pub links: Vec<AuthorLink>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What the public API shows of an author. Deliberately leaves out the user
+/// id, email and role.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicAuthor {
+    pub slug: String,
+    pub display_name: String,
+    pub bio: String,
+    pub avatar_id: Option<String>,
+    pub links: Vec<AuthorLink>,
+}
+
+/// A user that can be credited as an author, for the editor's author picker.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AuthorOption {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateAuthorProfile {
+    pub slug: Option<String>,
+    pub bio: Option<String>,
+    /// `Some("")` removes the avatar; `None` keeps it.
+    pub avatar_id: Option<String>,
+    pub links: Option<Vec<AuthorLink>>,
+}
+
+/// A user given edit access to a page or article they did not write.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Collaborator {
+    pub user_id: String,
+    pub display_name: String,
+    pub granted_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Series {
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A series with the number of articles in it.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SeriesSummary {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub series: Series,
+    pub article_count: i64,
+}
+
+/// One article of a series.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SeriesPart {
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    pub short_text: String,
+    pub status: String,
+    /// 1-based position within the series.
+    pub position: i64,
+}
+
+/// A series with its articles in order — the table of contents.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesWithParts {
+    #[serde(flatten)]
+    pub series: Series,
+    pub parts: Vec<SeriesPart>,
+}
+
+/// Where an article sits in its series.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesMembership {
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    /// 1-based position of the article.
+    pub position: i64,
+    pub total: i64,
+    pub previous: Option<SeriesPart>,
+    pub next: Option<SeriesPart>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Menu {
     pub id: String,
     pub name: String,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -127,10 +511,24 @@ pub struct MenuItem {
     pub sort_order: i32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Redirect {
+    pub id: String,
+    pub match_type: String,
+    pub source: String,
+    pub target: String,
+    pub status_code: i32,
+    pub hit_count: i64,
+    pub last_hit_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AuditLogEntry {
     pub id: String,
-    pub user_id: String,
+    /// `None` for actions taken by the system, e.g. scheduled unpublishing.
+    pub user_id: Option<String>,
     pub action: String,
     pub entity_type: String,
     pub entity_id: String,
@@ -144,15 +542,70 @@ pub struct AuditLogEntry {
 // they only need `Deserialize`. Optional fields use `Option` so callers can
 // omit them for partial updates (PATCH semantics).
 
+/// Deserializes a present field (including `null`) as `Some`, so that
+/// `Option<Option<T>>` can tell "absent" (keep) apart from `null` (clear).
+fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// A publish or unpublish time as sent by a client.
+///
+/// Timestamps with an offset (`2025-03-01T09:00:00Z`, `...+01:00`) are exact
+/// instants. Wall-clock times without one (`2025-03-01T09:00`, as produced by
+/// a `datetime-local` input) are interpreted in the site timezone by
+/// `services::schedule::resolve`.
+#[derive(Debug, Clone, Copy)]
+pub enum ScheduleTime {
+    Instant(DateTime<Utc>),
+    Local(NaiveDateTime),
+}
+
+impl<'de> Deserialize<'de> for ScheduleTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        let raw = raw.trim();
+        if let Ok(at) = DateTime::parse_from_rfc3339(raw) {
+            return Ok(ScheduleTime::Instant(at.with_timezone(&Utc)));
+        }
+        ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+            .iter()
+            .find_map(|fmt| NaiveDateTime::parse_from_str(raw, fmt).ok())
+            .map(ScheduleTime::Local)
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "invalid date-time '{raw}': expected RFC 3339 or YYYY-MM-DDTHH:MM"
+                ))
+            })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreatePage {
     pub title: String,
     pub slug: Option<String>,
     pub content: Option<String>,
+    /// `html` (the default) or `markdown`.
+    pub content_format: Option<String>,
     pub status: Option<String>,
-    pub publish_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<ScheduleTime>,
+    pub unpublish_at: Option<ScheduleTime>,
     pub category_ids: Option<Vec<String>>,
+    /// Tag names; unknown tags are created. Replaces the current tags.
+    pub tags: Option<Vec<String>>,
     pub template: Option<String>,
+    pub parent_id: Option<String>,
+    pub sort_order: Option<i32>,
+    /// Take over a slug that other content used in the past, dropping the
+    /// redirect that still points at that content.
+    #[serde(default)]
+    pub reclaim_slug: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -160,10 +613,29 @@ pub struct UpdatePage {
     pub title: Option<String>,
     pub slug: Option<String>,
     pub content: Option<String>,
+    /// `html` (the default) or `markdown`.
+    pub content_format: Option<String>,
     pub status: Option<String>,
-    pub publish_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<ScheduleTime>,
+    /// `Some(None)` (an explicit `null`) clears the expiry; absent keeps it.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub unpublish_at: Option<Option<ScheduleTime>>,
     pub category_ids: Option<Vec<String>>,
+    /// Tag names; unknown tags are created. Replaces the current tags.
+    pub tags: Option<Vec<String>>,
     pub template: Option<String>,
+    /// `Some("")` moves the page to the top level; `None` keeps the current parent.
+    pub parent_id: Option<String>,
+    pub sort_order: Option<i32>,
+    /// Take over a slug that other content used in the past, dropping the
+    /// redirect that still points at that content.
+    #[serde(default)]
+    pub reclaim_slug: bool,
+    /// Optional note stored with the revision this update creates.
+    pub message: Option<String>,
+    /// Version the edit was based on; stale updates are rejected (see
+    /// `services::concurrency`). `If-Match` takes precedence.
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -172,10 +644,21 @@ pub struct CreateArticle {
     pub slug: Option<String>,
     pub short_text: Option<String>,
     pub content: Option<String>,
+    /// `html` (the default) or `markdown`.
+    pub content_format: Option<String>,
     pub status: Option<String>,
-    pub publish_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<ScheduleTime>,
+    pub unpublish_at: Option<ScheduleTime>,
     pub category_ids: Option<Vec<String>>,
+    /// Tag names; unknown tags are created. Replaces the current tags.
+    pub tags: Option<Vec<String>>,
+    /// Users credited as authors, in byline order. Defaults to the creator.
+    pub author_ids: Option<Vec<String>>,
     pub cover_image_id: Option<String>,
+    /// Take over a slug that other content used in the past, dropping the
+    /// redirect that still points at that content.
+    #[serde(default)]
+    pub reclaim_slug: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -184,16 +667,76 @@ pub struct UpdateArticle {
     pub slug: Option<String>,
     pub short_text: Option<String>,
     pub content: Option<String>,
+    /// `html` (the default) or `markdown`.
+    pub content_format: Option<String>,
     pub status: Option<String>,
-    pub publish_at: Option<DateTime<Utc>>,
+    pub publish_at: Option<ScheduleTime>,
+    /// `Some(None)` (an explicit `null`) clears the expiry; absent keeps it.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub unpublish_at: Option<Option<ScheduleTime>>,
     pub category_ids: Option<Vec<String>>,
+    /// Tag names; unknown tags are created. Replaces the current tags.
+    pub tags: Option<Vec<String>>,
+    /// Users credited as authors, in byline order. Replaces the current credits.
+    pub author_ids: Option<Vec<String>>,
     pub cover_image_id: Option<String>,
+    /// Take over a slug that other content used in the past, dropping the
+    /// redirect that still points at that content.
+    #[serde(default)]
+    pub reclaim_slug: bool,
+    /// Optional note stored with the revision this update creates.
+    pub message: Option<String>,
+    /// Version the edit was based on; stale updates are rejected (see
+    /// `services::concurrency`). `If-Match` takes precedence.
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateCategory {
     pub name: String,
     pub slug: Option<String>,
+    pub parent_id: Option<String>,
+    pub description: Option<String>,
+    pub cover_image_id: Option<String>,
+    /// Defaults to the end of the sibling list.
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCategory {
+    pub name: Option<String>,
+    pub slug: Option<String>,
+    /// `Some("")` moves the category to the top level; `None` keeps the current parent.
+    pub parent_id: Option<String>,
+    pub description: Option<String>,
+    /// `Some("")` removes the cover image; `None` keeps it.
+    pub cover_image_id: Option<String>,
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeCategory {
+    /// The category that takes over the merged category's content and children.
+    pub into_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSeries {
+    pub title: String,
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    /// Articles in reading order.
+    #[serde(default)]
+    pub article_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSeries {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    /// Articles in reading order; replaces the current list when present.
+    pub article_ids: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -212,11 +755,96 @@ pub struct UpdateApp {
     pub icon_id: Option<String>,
     pub url: Option<String>,
     pub page_id: Option<String>,
+    /// Version the edit was based on; stale updates are rejected (see
+    /// `services::concurrency`). `If-Match` takes precedence.
+    pub expected_updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRedirect {
+    pub match_type: String,
+    pub source: String,
+    pub target: Option<String>,
+    pub status_code: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRedirect {
+    pub match_type: Option<String>,
+    pub source: Option<String>,
+    pub target: Option<String>,
+    pub status_code: Option<i32>,
+}
+
+/// Body of the pin-revision endpoints.
+#[derive(Debug, Deserialize)]
+pub struct PinRevision {
+    pub name: String,
+}
+
+/// Body of the reject endpoints in the review workflow.
+#[derive(Debug, Deserialize)]
+pub struct RejectContent {
+    pub comment: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRole {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRole {
+    pub description: Option<String>,
+    pub permissions: Option<Vec<String>>,
+}
+
+/// A pending or claimed invitation to join with a given role.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Invitation {
+    pub id: String,
+    pub email: String,
+    pub role: String,
+    pub invited_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInvitation {
+    pub email: String,
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiToken {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Defaults to 90 days from now; at most a year ahead.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateServiceAccount {
+    pub display_name: String,
+    pub role: String,
+}
+
+/// Body of the add-collaborator endpoints.
+#[derive(Debug, Deserialize)]
+pub struct AddCollaborator {
+    pub user_id: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateMenu {
     pub items: Vec<MenuItemInput>,
+    /// Version the edit was based on; stale updates are rejected (see
+    /// `services::concurrency`). `If-Match` takes precedence.
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -232,6 +860,34 @@ pub struct MenuItemInput {
 
 // ─── Utility types ────────────────────────────────────────────────────────────
 
+/// One node of the public page tree. Children are ordered by `sort_order`,
+/// then title.
+#[derive(Debug, Serialize)]
+pub struct PageTreeNode {
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    pub path: String,
+    pub sort_order: i32,
+    pub children: Vec<PageTreeNode>,
+}
+
+/// A single breadcrumb entry, from the root down to the requested page.
+#[derive(Debug, Serialize)]
+pub struct Breadcrumb {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+}
+
+/// Response body for the public page tree endpoint. `breadcrumbs` is empty
+/// when no path was requested.
+#[derive(Debug, Serialize)]
+pub struct PageTree {
+    pub tree: Vec<PageTreeNode>,
+    pub breadcrumbs: Vec<Breadcrumb>,
+}
+
 /// A single hit returned by the cross-entity search endpoint. Only serialized,
 /// never read from a request body or a DB row directly.
 #[derive(Debug, Serialize)]
@@ -277,3 +933,44 @@ pub struct PaginatedResponse<T: Serialize> {
     pub page: u32,
     pub per_page: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_article_leaves_out_author_id() {
+        let now = Utc::now();
+        let article = Article {
+            id: "a-1".into(),
+            title: "Hello".into(),
+            slug: "hello".into(),
+            short_text: String::new(),
+            content: "<p>Hi</p>".into(),
+            content_format: "html".into(),
+            content_html: "<p>Hi</p>".into(),
+            status: "published".into(),
+            publish_at: None,
+            unpublish_at: None,
+            author_id: "user-1".into(),
+            created_at: now,
+            updated_at: now,
+            trashed_at: None,
+            cover_image_id: None,
+            reading_time_minutes: 1,
+            review_comment: None,
+        };
+        let public = PublicArticle {
+            article: article.into(),
+            authors: vec![],
+            categories: vec![],
+            tags: vec![],
+            series: None,
+        };
+
+        let json = serde_json::to_value(&public).unwrap();
+        assert_eq!(json["slug"], "hello");
+        assert!(json.get("author_id").is_none());
+        assert!(json.get("review_comment").is_none());
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -9,6 +9,10 @@ use serde_json::json;
 pub enum AppError {
     #[error("Not found")]
     NotFound,
+    /// The resource lives at a new location (e.g. after a slug change). The
+    /// payload is the URL to send in the `Location` header.
+    #[error("Moved permanently to {0}")]
+    MovedPermanently(String),
     #[error("Unauthorized")]
     Unauthorized,
     #[error("Forbidden")]
@@ -17,6 +21,13 @@ pub enum AppError {
     BadRequest(String),
     #[error("Conflict: {0}")]
     Conflict(String),
+    /// An update was based on an outdated version. Carries the current
+    /// representation and its `ETag`.
+    #[error("This item was changed by someone else since you loaded it")]
+    VersionConflict {
+        current: serde_json::Value,
+        etag: String,
+    },
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     #[error("Internal error: {0}")]
@@ -27,10 +38,24 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
             AppError::NotFound => (StatusCode::NOT_FOUND, self.to_string()),
+            AppError::MovedPermanently(location) => {
+                let body = Json(json!({ "redirect": location }));
+                return (
+                    StatusCode::MOVED_PERMANENTLY,
+                    [(header::LOCATION, location.clone())],
+                    body,
+                )
+                    .into_response();
+            }
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, self.to_string()),
             AppError::Forbidden => (StatusCode::FORBIDDEN, self.to_string()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::VersionConflict { current, etag } => {
+                let body = Json(json!({ "error": self.to_string(), "current": current }));
+                return (StatusCode::CONFLICT, [(header::ETAG, etag.clone())], body)
+                    .into_response();
+            }
             AppError::Database(e) => {
                 tracing::error!("Database error: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".into())
@@ -12,7 +12,7 @@ use axum::{
     body::Body,
     extract::{DefaultBodyLimit, State},
     http::{Request, Response, StatusCode},
-    middleware::{from_fn, from_fn_with_state},
+    middleware::from_fn_with_state,
     response::IntoResponse,
     routing::{delete, get, post, put},
     Json, Router,
@@ -24,6 +24,9 @@ use std::net::SocketAddr;
 use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+use auth::middleware::require_permission;
+use auth::permissions as perm;
+
 /// Shared application state passed to every handler via Axum's `State`
 /// extractor. Must be `Clone` — Axum clones it once per request.
 #[derive(Clone)]
@@ -33,6 +36,13 @@ pub struct AppState {
     /// Shared HTTP client for outbound requests (OAuth2, etc.). Reusing a
     /// single client avoids per-request connection pool and TLS overhead.
     pub http_client: reqwest::Client,
+    /// Compiled redirect rules, checked by the fallback router before a
+    /// request is proxied to the frontend.
+    pub redirect_cache: services::redirects::RedirectCache,
+    /// The OIDC issuer's discovery document and signing keys.
+    pub oidc: auth::oauth2::OidcCache,
+    /// Wakes the publish scheduler after writes that change schedule times.
+    pub scheduler: tasks::Scheduler,
 }
 
 #[tokio::main]
@@ -80,6 +90,17 @@ async fn main() {
         .await
         .expect("failed to connect to database and run migrations");
 
+    // Content that predates sanitization is rendered before it is served.
+    let rendered = services::sanitize::render_missing(&pool)
+        .await
+        .expect("failed to render migrated content");
+    if rendered > 0 {
+        info!("Rendered {rendered} pages and articles stored before sanitization");
+    }
+    services::authors::ensure_credited_profiles(&pool)
+        .await
+        .expect("failed to create author profiles");
+
     // Capture the port before `config` is moved into AppState, so we can use
     // it when binding the listener below.
     let port = config.port;
@@ -94,6 +115,9 @@ async fn main() {
         db: pool,
         config,
         http_client: reqwest::Client::new(),
+        redirect_cache: services::redirects::RedirectCache::default(),
+        oidc: auth::oauth2::OidcCache::default(),
+        scheduler: tasks::Scheduler::default(),
     };
 
     // ── Route groups ──────────────────────────────────────────────────────────
@@ -104,13 +128,23 @@ async fn main() {
     // 1. Public routes — no authentication required.
     let public_routes = Router::new()
         .route("/api/health", get(health_check))
-        .route("/api/pages/{slug}", get(api::pages::public_get_by_slug))
+        .route("/api/pages/{*path}", get(api::pages::public_get_by_path))
+        .route("/api/page-tree", get(api::pages::public_tree))
         .route("/api/articles", get(api::articles::public_list))
         .route("/api/articles/{slug}", get(api::articles::public_get_by_slug))
         .route("/api/articles/{slug}/related", get(api::articles::public_related))
         .route("/feed.xml", get(api::feed::atom_feed))
         .route("/api/menus/{name}", get(api::menus::public_get))
         .route("/api/settings/public", get(api::settings::public_get))
+        .route("/api/categories", get(api::categories::public_list))
+        .route("/api/categories/{slug}", get(api::categories::public_get))
+        .route("/api/categories/{slug}/articles", get(api::categories::public_articles))
+        .route("/api/categories/{slug}/pages", get(api::categories::public_pages))
+        .route("/api/tags/{slug}", get(api::tags::public_get))
+        .route("/api/series/{slug}", get(api::series::public_get))
+        .route("/api/authors", get(api::authors::public_list))
+        .route("/api/authors/{slug}", get(api::authors::public_get))
+        .route("/api/authors/{slug}/articles", get(api::authors::public_articles))
         .route("/api/apps", get(api::apps::public_list))
         .route("/api/search", get(api::search::public_search));
 
@@ -122,138 +156,327 @@ async fn main() {
         .route("/api/auth/logout", post(api::auth::logout));
 
     // 3. Admin routes — protected by the require_auth middleware layer.
-    //    Every route added here will require a valid session cookie.
+    //    Every route added here will require a valid session cookie. Each
+    //    group below additionally requires the user's role to grant one of
+    //    its permissions (see auth::permissions); whether the content is the
+    //    user's own is checked in the services.
 
-    // Admin-only routes — require both authentication AND the "admin" role.
-    let admin_only_routes = Router::new()
+    // Users, roles and other users' author profiles.
+    let users_routes = Router::new()
         .route("/api/admin/users", get(api::auth::list_users))
         .route(
             "/api/admin/users/{id}/role",
             put(api::auth::update_user_role),
         )
+        .route(
+            "/api/admin/users/{id}/profile",
+            get(api::authors::get_for_user).put(api::authors::update_for_user),
+        )
+        .route("/api/admin/permissions", get(api::roles::permissions))
+        .route("/api/admin/roles", get(api::roles::list).post(api::roles::create))
+        .route(
+            "/api/admin/roles/{name}",
+            get(api::roles::get).put(api::roles::update).delete(api::roles::delete),
+        )
+        .route(
+            "/api/admin/invitations",
+            get(api::invitations::list).post(api::invitations::create),
+        )
+        .route("/api/admin/invitations/{id}", delete(api::invitations::delete))
+        .route(
+            "/api/admin/service-accounts",
+            post(api::tokens::create_service_account),
+        )
+        .route(
+            "/api/admin/users/{id}/tokens",
+            get(api::tokens::list_for_user).post(api::tokens::create_for_user),
+        )
+        .route(
+            "/api/admin/users/{id}/sessions",
+            get(api::sessions::list_for_user).delete(api::sessions::revoke_for_user),
+        )
+        .route(
+            "/api/admin/users/{id}/tokens/{token_id}",
+            delete(api::tokens::revoke_for_user),
+        )
+        .layer(from_fn_with_state(&[perm::USERS_MANAGE][..], require_permission));
+
+    // Site settings, redirects and permanent deletion.
+    let settings_routes = Router::new()
         .route(
             "/api/admin/settings",
             get(api::settings::admin_get).put(api::settings::admin_update),
         )
+        .route(
+            "/api/admin/settings/sanitization-report",
+            get(api::settings::sanitization_report),
+        )
         .route("/api/admin/trash/empty", post(api::trash::empty))
-        .layer(from_fn(auth::middleware::require_admin));
-
-    let admin_routes = Router::new()
-        .route("/api/admin/me", get(api::auth::me))
-        // Articles CRUD
         .route(
-            "/api/admin/articles",
-            get(api::articles::admin_list).post(api::articles::admin_create),
+            "/api/admin/redirects",
+            get(api::redirects::list).post(api::redirects::create),
         )
         .route(
-            "/api/admin/articles/{id}",
-            get(api::articles::admin_get)
-                .put(api::articles::admin_update)
-                .delete(api::articles::admin_delete),
+            "/api/admin/redirects/{id}",
+            get(api::redirects::get)
+                .put(api::redirects::update)
+                .delete(api::redirects::delete),
         )
+        .layer(from_fn_with_state(&[perm::SETTINGS_MANAGE][..], require_permission));
+
+    // Reviewer routes — putting content live. Roles that may only publish
+    // their own content are held to it by the services.
+    let reviewer_routes = Router::new()
         .route(
             "/api/admin/articles/{id}/publish",
             post(api::articles::admin_publish),
         )
         .route(
-            "/api/admin/articles/{id}/restore",
-            post(api::articles::admin_restore),
+            "/api/admin/articles/{id}/approve",
+            post(api::articles::admin_approve),
         )
+        .route(
+            "/api/admin/articles/{id}/reject",
+            post(api::articles::admin_reject),
+        )
+        .route(
+            "/api/admin/pages/{id}/publish",
+            post(api::pages::admin_publish),
+        )
+        .route(
+            "/api/admin/pages/{id}/approve",
+            post(api::pages::admin_approve),
+        )
+        .route(
+            "/api/admin/pages/{id}/reject",
+            post(api::pages::admin_reject),
+        )
+        .layer(from_fn_with_state(
+            &[perm::CONTENT_PUBLISH_OWN, perm::CONTENT_PUBLISH_ANY][..],
+            require_permission,
+        ));
+
+    // Read-only access to the admin area.
+    let view_routes = Router::new()
+        .route("/api/admin/authors", get(api::authors::list_options))
+        .route("/api/admin/articles", get(api::articles::admin_list))
+        .route("/api/admin/articles/{id}", get(api::articles::admin_get))
         .route(
             "/api/admin/articles/{id}/revisions",
             get(api::articles::admin_revisions),
         )
+        .route(
+            "/api/admin/articles/{id}/revisions/{a}/diff/{b}",
+            get(api::articles::admin_revision_diff),
+        )
+        .route(
+            "/api/admin/articles/{id}/collaborators",
+            get(api::articles::admin_collaborators),
+        )
+        .route("/api/admin/pages", get(api::pages::admin_list))
+        .route("/api/admin/pages/{id}", get(api::pages::admin_get))
+        .route(
+            "/api/admin/pages/{id}/revisions",
+            get(api::pages::admin_revisions),
+        )
+        .route(
+            "/api/admin/pages/{id}/revisions/{a}/diff/{b}",
+            get(api::pages::admin_revision_diff),
+        )
+        .route(
+            "/api/admin/pages/{id}/collaborators",
+            get(api::pages::admin_collaborators),
+        )
+        .route("/api/admin/categories", get(api::categories::list))
+        .route("/api/admin/tags", get(api::tags::list))
+        .route("/api/admin/series", get(api::series::list))
+        .route("/api/admin/series/{id}", get(api::series::get))
+        .route("/api/admin/menus/{name}", get(api::menus::admin_get))
+        .route("/api/admin/apps", get(api::apps::admin_list))
+        .route("/api/admin/apps/{id}", get(api::apps::admin_get))
+        .route("/api/admin/trash", get(api::trash::list))
+        // Search (admin — includes unpublished content)
+        .route("/api/admin/search", get(api::search::admin_search))
+        .route("/api/admin/media", get(api::media::admin_list))
+        .layer(from_fn_with_state(&[perm::CONTENT_VIEW][..], require_permission));
+
+    let create_routes = Router::new()
+        .route("/api/admin/articles", post(api::articles::admin_create))
+        .route("/api/admin/pages", post(api::pages::admin_create))
+        .layer(from_fn_with_state(&[perm::CONTENT_CREATE][..], require_permission));
+
+    // Changing existing content. Roles that may only edit their own content
+    // are held to it by the services.
+    let edit_routes = Router::new()
+        .route(
+            "/api/admin/articles/{id}",
+            put(api::articles::admin_update).delete(api::articles::admin_delete),
+        )
+        .route(
+            "/api/admin/articles/{id}/submit",
+            post(api::articles::admin_submit),
+        )
+        .route(
+            "/api/admin/articles/{id}/restore",
+            post(api::articles::admin_restore),
+        )
+        .route(
+            "/api/admin/articles/{id}/draft",
+            delete(api::articles::admin_discard_draft),
+        )
+        .route(
+            "/api/admin/articles/{id}/revisions/{rev_id}/pin",
+            put(api::articles::admin_pin_revision).delete(api::articles::admin_unpin_revision),
+        )
         .route(
             "/api/admin/articles/{id}/revisions/{rev_id}/restore",
             post(api::articles::admin_restore_revision),
         )
-        // Pages CRUD
         .route(
-            "/api/admin/pages",
-            get(api::pages::admin_list).post(api::pages::admin_create),
+            "/api/admin/articles/{id}/collaborators",
+            post(api::articles::admin_add_collaborator),
+        )
+        .route(
+            "/api/admin/articles/{id}/collaborators/{user_id}",
+            delete(api::articles::admin_remove_collaborator),
         )
         .route(
             "/api/admin/pages/{id}",
-            get(api::pages::admin_get)
-                .put(api::pages::admin_update)
-                .delete(api::pages::admin_delete),
+            put(api::pages::admin_update).delete(api::pages::admin_delete),
         )
         .route(
-            "/api/admin/pages/{id}/publish",
-            post(api::pages::admin_publish),
+            "/api/admin/pages/{id}/submit",
+            post(api::pages::admin_submit),
         )
         .route(
             "/api/admin/pages/{id}/restore",
             post(api::pages::admin_restore),
         )
         .route(
-            "/api/admin/pages/{id}/revisions",
-            get(api::pages::admin_revisions),
+            "/api/admin/pages/{id}/draft",
+            delete(api::pages::admin_discard_draft),
+        )
+        .route(
+            "/api/admin/pages/{id}/revisions/{rev_id}/pin",
+            put(api::pages::admin_pin_revision).delete(api::pages::admin_unpin_revision),
         )
         .route(
             "/api/admin/pages/{id}/revisions/{rev_id}/restore",
             post(api::pages::admin_restore_revision),
         )
-        // Categories
         .route(
-            "/api/admin/categories",
-            get(api::categories::list).post(api::categories::create),
+            "/api/admin/pages/{id}/collaborators",
+            post(api::pages::admin_add_collaborator),
         )
+        .route(
+            "/api/admin/pages/{id}/collaborators/{user_id}",
+            delete(api::pages::admin_remove_collaborator),
+        )
+        .layer(from_fn_with_state(
+            &[perm::CONTENT_EDIT_OWN, perm::CONTENT_EDIT_ANY][..],
+            require_permission,
+        ));
+
+    // Categories, tags and series.
+    let taxonomy_routes = Router::new()
+        .route("/api/admin/categories", post(api::categories::create))
         .route(
             "/api/admin/categories/{id}",
             put(api::categories::update).delete(api::categories::delete),
         )
-        // Menus
+        .route("/api/admin/categories/{id}/merge", post(api::categories::merge))
         .route(
-            "/api/admin/menus/{name}",
-            get(api::menus::admin_get).put(api::menus::admin_update),
+            "/api/admin/tags/{id}",
+            put(api::tags::rename).delete(api::tags::delete),
         )
-        // Apps — reorder MUST be registered before /{id} to avoid routing ambiguity
+        .route("/api/admin/tags/{id}/merge", post(api::tags::merge))
+        .route("/api/admin/series", post(api::series::create))
         .route(
-            "/api/admin/apps/reorder",
-            put(api::apps::admin_reorder),
+            "/api/admin/series/{id}",
+            put(api::series::update).delete(api::series::delete),
         )
+        .layer(from_fn_with_state(&[perm::TAXONOMY_MANAGE][..], require_permission));
+
+    let menus_routes = Router::new()
+        .route("/api/admin/menus/{name}", put(api::menus::admin_update))
+        .layer(from_fn_with_state(&[perm::MENUS_MANAGE][..], require_permission));
+
+    // Apps — reorder MUST be registered before /{id} to avoid routing ambiguity
+    let apps_routes = Router::new()
         .route(
-            "/api/admin/apps",
-            get(api::apps::admin_list).post(api::apps::admin_create),
+            "/api/admin/apps/reorder",
+            put(api::apps::admin_reorder),
         )
+        .route("/api/admin/apps", post(api::apps::admin_create))
         .route(
             "/api/admin/apps/{id}",
-            get(api::apps::admin_get)
-                .put(api::apps::admin_update)
-                .delete(api::apps::admin_delete),
+            put(api::apps::admin_update).delete(api::apps::admin_delete),
         )
-        // Trash
-        .route("/api/admin/trash", get(api::trash::list))
-        // Audit log
+        .layer(from_fn_with_state(&[perm::APPS_MANAGE][..], require_permission));
+
+    let media_routes = Router::new()
+        .route("/api/admin/media", post(api::media::admin_upload))
+        .layer(from_fn_with_state(&[perm::MEDIA_UPLOAD][..], require_permission))
+        .merge(
+            Router::new()
+                .route("/api/admin/media/{id}", delete(api::media::admin_delete))
+                .layer(from_fn_with_state(&[perm::MEDIA_DELETE][..], require_permission)),
+        );
+
+    let audit_routes = Router::new()
         .route("/api/admin/audit-log", get(api::audit::list))
-        // Search (admin — includes unpublished content)
-        .route("/api/admin/search", get(api::search::admin_search))
-        // Media — upload has a 50 MB body size limit
+        .layer(from_fn_with_state(&[perm::AUDIT_VIEW][..], require_permission));
+
+    let admin_routes = Router::new()
+        // Any signed-in user may see who they are and edit their own profile.
+        .route("/api/admin/me", get(api::auth::me))
+        .route("/api/admin/scopes", get(api::tokens::scopes))
+        .route(
+            "/api/admin/me/tokens",
+            get(api::tokens::list_own).post(api::tokens::create_own),
+        )
+        .route("/api/admin/me/tokens/{id}", delete(api::tokens::revoke_own))
         .route(
-            "/api/admin/media",
-            get(api::media::admin_list).post(api::media::admin_upload),
+            "/api/admin/me/sessions",
+            get(api::sessions::list_own).delete(api::sessions::revoke_other_own),
         )
+        .route("/api/admin/me/sessions/{id}", delete(api::sessions::revoke_own))
         .route(
-            "/api/admin/media/{id}",
-            delete(api::media::admin_delete),
+            "/api/admin/profile",
+            get(api::authors::get_own).put(api::authors::update_own),
         )
+        .merge(view_routes)
+        .merge(create_routes)
+        .merge(edit_routes)
+        .merge(taxonomy_routes)
+        .merge(menus_routes)
+        .merge(apps_routes)
+        .merge(media_routes)
+        .merge(audit_routes)
+        // Media — upload has a 50 MB body size limit
         .layer(DefaultBodyLimit::max(50 * 1024 * 1024)) // 50 MB
-        .merge(admin_only_routes)
+        .merge(users_routes)
+        .merge(settings_routes)
+        .merge(reviewer_routes)
         .layer(from_fn_with_state(
             state.clone(),
             auth::middleware::require_auth,
         ));
 
     // Clone the pool before `state` is moved into the router.
-    tasks::spawn_background_tasks(state.db.clone());
+    tasks::spawn_background_tasks(
+        state.db.clone(),
+        state.scheduler.clone(),
+        state.redirect_cache.clone(),
+    );
 
     // ServeDir must be nested before `.with_state()` so it is part of the same
     // router tree. We clone `uploads_dir` here because `state` is moved below.
     let uploads_dir = state.config.uploads_dir.clone();
 
-    // Reuse the shared reqwest client from AppState for the SvelteKit proxy.
-    let http_client = state.http_client.clone();
+    // The fallback runs outside the typed router state, so it captures its
+    // own copy of the state it needs for redirect matching and proxying.
+    let fallback_state = state.clone();
 
     let app = Router::new()
         .merge(public_routes)
@@ -263,12 +486,23 @@ async fn main() {
         // after `/uploads/` maps to `{uploads_dir}/{rest}`, so a URL like
         // `/uploads/{id}/thumbnail.webp` maps to `uploads/{id}/thumbnail.webp`.
         .nest_service("/uploads", ServeDir::new(&uploads_dir))
-        // All remaining requests (i.e. the SvelteKit frontend) are reverse-
-        // proxied to the Node server. This is only active in Docker where
-        // FRONTEND_ORIGIN points at the running SvelteKit process on port 3000.
-        // In local development Vite's dev server handles the frontend directly.
+        // All remaining requests (i.e. the SvelteKit frontend) are first
+        // checked against the admin-managed redirect rules and otherwise
+        // reverse-proxied to the Node server. Proxying is only active in Docker
+        // where FRONTEND_ORIGIN points at the running SvelteKit process on port
+        // 3000. In local development Vite's dev server handles the frontend
+        // directly.
         .fallback(move |req: Request<Body>| {
-            proxy_to_frontend(req, http_client.clone(), frontend_origin.clone())
+            let state = fallback_state.clone();
+            let frontend_origin = frontend_origin.clone();
+            async move {
+                if let Some(response) = apply_redirect_rules(&state, req.method(), req.uri()).await {
+                    return response;
+                }
+                proxy_to_frontend(req, state.http_client.clone(), frontend_origin)
+                    .await
+                    .into_response()
+            }
         })
         .with_state(state);
 
@@ -279,7 +513,8 @@ async fn main() {
         .await
         .expect("failed to bind TCP listener");
 
-    axum::serve(listener, app)
+    // Connect info gives handlers the peer address, recorded with sessions.
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .expect("server error");
 }
@@ -299,6 +534,44 @@ async fn health_check(State(state): State<AppState>) -> Json<serde_json::Value>
     }))
 }
 
+/// Matches GET/HEAD requests against the redirect rules. Returns the
+/// redirect (or 410 Gone) response when a rule applies, recording the hit.
+///
+/// Lookup failures are logged and treated as "no match" so a broken rule set
+/// never takes the frontend down with it.
+async fn apply_redirect_rules(
+    state: &AppState,
+    method: &axum::http::Method,
+    uri: &axum::http::Uri,
+) -> Option<Response<Body>> {
+    if method != axum::http::Method::GET && method != axum::http::Method::HEAD {
+        return None;
+    }
+
+    let rules = match state.redirect_cache.rules(&state.db).await {
+        Ok(rules) => rules,
+        Err(e) => {
+            warn!("redirects: failed to load rules: {e}");
+            return None;
+        }
+    };
+    let hit = services::redirects::match_path(&rules, uri.path(), uri.query())?;
+
+    state.redirect_cache.record_hit(&hit.rule_id).await;
+
+    let status = StatusCode::from_u16(hit.status_code).unwrap_or(StatusCode::MOVED_PERMANENTLY);
+    if status == StatusCode::GONE {
+        return Some((status, "gone").into_response());
+    }
+    Some(
+        Response::builder()
+            .status(status)
+            .header(axum::http::header::LOCATION, hit.location)
+            .body(Body::empty())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+    )
+}
+
 /// Reverse-proxy fallback: forwards every request that didn't match an API or
 /// `/uploads` route to the SvelteKit Node server running on `frontend_origin`.
 ///
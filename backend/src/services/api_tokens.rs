@@ -0,0 +1,245 @@
+//! API token and service account service.
+//!
+//! Users create personal tokens for themselves; users with `users.manage`
+//! create service accounts and issue their tokens. Tokens are validated in
+//! `auth::api_tokens`.
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::auth::api_tokens::{self, SCOPES};
+use crate::db::models::{
+    ApiToken, CreateApiToken, CreateServiceAccount, NewApiToken, ScopeInfo, User,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::{audit, roles, schedule};
+
+const DEFAULT_LIFETIME_DAYS: i64 = 90;
+const MAX_LIFETIME_DAYS: i64 = 365;
+const MAX_NAME_LEN: usize = 100;
+
+/// `api_tokens` as stored, with the scopes still encoded as JSON.
+#[derive(sqlx::FromRow)]
+struct ApiTokenRow {
+    id: String,
+    user_id: String,
+    name: String,
+    scopes: String,
+    created_by: Option<String>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+const TOKEN_SELECT: &str =
+    "SELECT id, user_id, name, scopes, created_by, created_at, expires_at, last_used_at \
+     FROM api_tokens";
+
+impl ApiTokenRow {
+    fn into_token(self) -> ApiToken {
+        ApiToken {
+            id: self.id,
+            user_id: self.user_id,
+            name: self.name,
+            scopes: serde_json::from_str(&self.scopes).unwrap_or_default(),
+            created_by: self.created_by,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            last_used_at: self.last_used_at,
+        }
+    }
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns the scopes a token can carry.
+pub fn list_scopes() -> Vec<ScopeInfo> {
+    SCOPES
+        .iter()
+        .map(|&(name, description)| ScopeInfo { name, description })
+        .collect()
+}
+
+/// Returns a user's tokens, newest first, including expired ones.
+pub async fn list_tokens(pool: &SqlitePool, user_id: &str) -> AppResult<Vec<ApiToken>> {
+    let rows = sqlx::query_as::<_, ApiTokenRow>(&format!(
+        "{TOKEN_SELECT} WHERE user_id = ? ORDER BY created_at DESC"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(ApiTokenRow::into_token).collect())
+}
+
+/// Creates a token for `user_id`. The returned secret is not stored and cannot
+/// be shown again.
+pub async fn create_token(
+    pool: &SqlitePool,
+    user_id: &str,
+    input: CreateApiToken,
+    acting_user: &User,
+) -> AppResult<NewApiToken> {
+    let name = input.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Token names must be 1-{MAX_NAME_LEN} characters"
+        )));
+    }
+    let scopes = validate_scopes(input.scopes)?;
+    let now = Utc::now();
+    let expires_at = input
+        .expires_at
+        .unwrap_or(now + Duration::days(DEFAULT_LIFETIME_DAYS));
+    if expires_at <= now || expires_at > now + Duration::days(MAX_LIFETIME_DAYS) {
+        return Err(AppError::BadRequest(format!(
+            "expires_at must be in the future and at most {MAX_LIFETIME_DAYS} days ahead"
+        )));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let (token, hash) = api_tokens::generate();
+    sqlx::query(
+        "INSERT INTO api_tokens (id, user_id, name, token, scopes, created_by, expires_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(name)
+    .bind(&hash)
+    .bind(json!(scopes).to_string())
+    .bind(&acting_user.id)
+    .bind(schedule::db_timestamp(expires_at))
+    .execute(pool)
+    .await?;
+
+    audit::log_action(
+        pool,
+        &acting_user.id,
+        "create",
+        "api_token",
+        &id,
+        &json!({ "user_id": user_id, "name": name, "scopes": scopes }),
+    )
+    .await?;
+
+    Ok(NewApiToken {
+        api_token: get_token(pool, user_id, &id).await?,
+        token,
+    })
+}
+
+/// Deletes one of `user_id`'s tokens. Returns `NotFound` if it has no such
+/// token.
+pub async fn revoke_token(
+    pool: &SqlitePool,
+    user_id: &str,
+    token_id: &str,
+    acting_user: &User,
+) -> AppResult<()> {
+    let result = sqlx::query("DELETE FROM api_tokens WHERE id = ? AND user_id = ?")
+        .bind(token_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    audit::log_action(
+        pool,
+        &acting_user.id,
+        "delete",
+        "api_token",
+        token_id,
+        &json!({ "user_id": user_id }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Creates a service account: a user with a role that authenticates with API
+/// tokens only.
+pub async fn create_service_account(
+    pool: &SqlitePool,
+    input: CreateServiceAccount,
+    acting_user: &User,
+) -> AppResult<User> {
+    let display_name = input.display_name.trim();
+    if display_name.is_empty() {
+        return Err(AppError::BadRequest("display_name must not be empty".into()));
+    }
+    roles::get_role(pool, &input.role)
+        .await
+        .map_err(|_| AppError::BadRequest(format!("Invalid role '{}'", input.role)))?;
+
+    let id = Uuid::new_v4().to_string();
+    let user = sqlx::query_as::<_, User>(
+        "INSERT INTO users (id, external_id, email, display_name, role, is_service) \
+         VALUES (?, ?, '', ?, ?, 1) \
+         RETURNING id, external_id, email, display_name, role, created_at, last_login, \
+                   is_service",
+    )
+    .bind(&id)
+    .bind(format!("service:{}", Uuid::new_v4().simple()))
+    .bind(display_name)
+    .bind(&input.role)
+    .fetch_one(pool)
+    .await?;
+
+    audit::log_action(
+        pool,
+        &acting_user.id,
+        "create_service_account",
+        "user",
+        &id,
+        &json!({ "display_name": display_name, "role": input.role }),
+    )
+    .await?;
+
+    Ok(user)
+}
+
+/// Returns `NotFound` unless `user_id` is a service account. Tokens for other
+/// users are only ever created by those users themselves.
+pub async fn ensure_service_account(pool: &SqlitePool, user_id: &str) -> AppResult<()> {
+    sqlx::query_scalar::<_, String>("SELECT id FROM users WHERE id = ? AND is_service = 1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(())
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+async fn get_token(pool: &SqlitePool, user_id: &str, id: &str) -> AppResult<ApiToken> {
+    sqlx::query_as::<_, ApiTokenRow>(&format!("{TOKEN_SELECT} WHERE id = ? AND user_id = ?"))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(ApiTokenRow::into_token)
+        .ok_or(AppError::NotFound)
+}
+
+/// Rejects unknown scopes and empty lists; returns the rest sorted and
+/// deduplicated.
+fn validate_scopes(scopes: Vec<String>) -> AppResult<Vec<String>> {
+    let mut set = BTreeSet::new();
+    for scope in scopes {
+        if !api_tokens::is_known(&scope) {
+            return Err(AppError::BadRequest(format!("Unknown scope '{}'", scope)));
+        }
+        set.insert(scope);
+    }
+    if set.is_empty() {
+        return Err(AppError::BadRequest("A token needs at least one scope".into()));
+    }
+    Ok(set.into_iter().collect())
+}
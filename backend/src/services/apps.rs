@@ -10,7 +10,7 @@ use uuid::Uuid;
 
 use crate::db::models::{App, CreateApp, PaginatedResponse, PaginationParams, UpdateApp};
 use crate::error::{AppError, AppResult};
-use crate::services::audit;
+use crate::services::{audit, concurrency};
 
 // ─── Column list shared by all SELECT queries ─────────────────────────────────
 
@@ -108,6 +108,7 @@ pub async fn create_app(
 
 /// Applies a partial update to an existing app. Fields absent from `input`
 /// keep their current values.
+/// Rejected with a conflict when `expected_updated_at` is stale.
 pub async fn update_app(
     pool: &SqlitePool,
     id: &str,
@@ -115,6 +116,10 @@ pub async fn update_app(
     user_id: &str,
 ) -> AppResult<App> {
     let existing = get_app(pool, id).await?;
+    if concurrency::is_stale(input.expected_updated_at, existing.updated_at) {
+        let version = existing.updated_at;
+        return Err(concurrency::conflict(&existing, version));
+    }
 
     let name = input.name.unwrap_or_else(|| existing.name.clone());
     let description = input.description.unwrap_or_else(|| existing.description.clone());
@@ -122,11 +127,11 @@ pub async fn update_app(
     let url = if input.url.is_some() { input.url } else { existing.url };
     let page_id = if input.page_id.is_some() { input.page_id } else { existing.page_id };
 
-    sqlx::query(
+    let written = sqlx::query(
         "UPDATE apps \
          SET name = ?, description = ?, icon_id = ?, url = ?, page_id = ?, \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
-         WHERE id = ?",
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = ? AND strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) = ?",
     )
     .bind(&name)
     .bind(&description)
@@ -134,8 +139,15 @@ pub async fn update_app(
     .bind(&url)
     .bind(&page_id)
     .bind(id)
+    .bind(concurrency::db_version(existing.updated_at))
     .execute(pool)
     .await?;
+    if written.rows_affected() == 0 {
+        // Somebody else saved since the version check above.
+        let current = get_app(pool, id).await?;
+        let version = current.updated_at;
+        return Err(concurrency::conflict(&current, version));
+    }
 
     audit::log_action(
         pool,
@@ -183,7 +195,7 @@ pub async fn reorder_apps(
 ) -> AppResult<()> {
     for (index, app_id) in ids.iter().enumerate() {
         sqlx::query(
-            "UPDATE apps SET sort_order = ?, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+            "UPDATE apps SET sort_order = ?, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
              WHERE id = ?",
         )
         .bind(index as i32)
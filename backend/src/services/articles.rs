@@ -8,14 +8,27 @@
 //! field that is included in every revision snapshot.
 
 use serde_json::json;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
-use crate::db::models::{Article, ArticleRevision, CreateArticle, PaginatedResponse,
-    PaginationParams, UpdateArticle};
+use crate::db::models::{Article, ArticleDraft, ArticleRevision, ArticleWithDraft, CreateArticle,
+    PaginatedResponse, PaginationParams, PublicArticle, PublishedArticle, UpdateArticle, User};
 use crate::error::{AppError, AppResult};
 use crate::helpers::slugify;
-use crate::services::audit;
+use crate::services::sanitize::SanitizePolicy;
+use crate::services::visibility::{self, PUBLIC_FILTER};
+use crate::services::diff::{self, DiffOptions, RevisionDiff, Snapshot};
+use crate::services::{
+    audit, authors, categories, concurrency, ownership, render, review, revisions, sanitize,
+    schedule, series, slugs, tags,
+};
+
+// ─── Column list shared by all SELECT queries ─────────────────────────────────
+
+pub(crate) const ARTICLE_COLS: &str =
+    "id, title, slug, short_text, content, content_format, content_html, status, publish_at, \
+     unpublish_at, author_id, created_at, updated_at, trashed_at, cover_image_id, \
+     reading_time_minutes, review_comment";
 
 // ─── Public service functions ─────────────────────────────────────────────────
 
@@ -35,15 +48,17 @@ pub async fn list_articles(
     // Two almost-identical queries depending on whether a status is requested.
     // Keeping them as separate query! calls (rather than building SQL strings)
     // means sqlx can type-check them at compile time.
+    if let Some(status) = status_filter {
+        validate_status_filter(status)?;
+    }
+
     let (rows, total) = if let Some(status) = status_filter {
-        let rows = sqlx::query_as::<_, Article>(
-            "SELECT id, title, slug, short_text, content, status, publish_at, author_id, \
-                    created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes \
-             FROM articles \
+        let rows = sqlx::query_as::<_, Article>(&format!(
+            "SELECT {ARTICLE_COLS} FROM articles \
              WHERE status = ? \
              ORDER BY updated_at DESC \
-             LIMIT ? OFFSET ?",
-        )
+             LIMIT ? OFFSET ?"
+        ))
         .bind(status)
         .bind(per_page)
         .bind(offset)
@@ -58,14 +73,12 @@ pub async fn list_articles(
 
         (rows, total)
     } else {
-        let rows = sqlx::query_as::<_, Article>(
-            "SELECT id, title, slug, short_text, content, status, publish_at, author_id, \
-                    created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes \
-             FROM articles \
+        let rows = sqlx::query_as::<_, Article>(&format!(
+            "SELECT {ARTICLE_COLS} FROM articles \
              WHERE status != 'trashed' \
              ORDER BY updated_at DESC \
-             LIMIT ? OFFSET ?",
-        )
+             LIMIT ? OFFSET ?"
+        ))
         .bind(per_page)
         .bind(offset)
         .fetch_all(pool)
@@ -88,6 +101,15 @@ pub async fn list_articles(
     })
 }
 
+/// Narrows `list_published_articles` to one tag, one category (with its
+/// descendants) and/or one credited author, all given by slug.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ArticleFilter<'a> {
+    pub tag: Option<&'a str>,
+    pub category: Option<&'a str>,
+    pub author: Option<&'a str>,
+}
+
 /// Returns a paginated list of published articles, ordered newest first.
 ///
 /// Intended for the public-facing API — no authentication required. Only
@@ -95,28 +117,54 @@ pub async fn list_articles(
 pub async fn list_published_articles(
     pool: &SqlitePool,
     params: &PaginationParams,
-) -> AppResult<PaginatedResponse<Article>> {
+    filter: ArticleFilter<'_>,
+) -> AppResult<PaginatedResponse<PublishedArticle>> {
     let per_page = params.per_page() as i64;
     let offset = params.offset() as i64;
 
-    let rows = sqlx::query_as::<_, Article>(
-        "SELECT id, title, slug, short_text, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes \
-         FROM articles \
-         WHERE status = 'published' \
+    let mut conditions = String::new();
+    let mut binds = Vec::new();
+    if let Some(tag) = filter.tag {
+        conditions.push_str(
+            " AND id IN (SELECT at.article_id FROM article_tags at \
+                         JOIN tags t ON t.id = at.tag_id WHERE t.slug = ?)",
+        );
+        binds.push(tag);
+    }
+    if let Some(category) = filter.category {
+        // A category includes everything filed under its descendants.
+        let subtree = categories::subtree_ids("SELECT id FROM categories WHERE slug = ?");
+        conditions.push_str(&format!(
+            " AND id IN (SELECT article_id FROM article_categories WHERE category_id IN ({subtree}))"
+        ));
+        binds.push(category);
+    }
+    if let Some(author) = filter.author {
+        conditions.push_str(
+            " AND id IN (SELECT aa.article_id FROM article_authors aa \
+                         JOIN author_profiles p ON p.user_id = aa.user_id WHERE p.slug = ?)",
+        );
+        binds.push(author);
+    }
+
+    let rows_sql = format!(
+        "SELECT {ARTICLE_COLS} FROM articles \
+         WHERE {PUBLIC_FILTER}{conditions} \
          ORDER BY created_at DESC \
-         LIMIT ? OFFSET ?",
-    )
-    .bind(per_page)
-    .bind(offset)
-    .fetch_all(pool)
-    .await?;
+         LIMIT ? OFFSET ?"
+    );
+    let mut rows_query = sqlx::query_as::<_, PublishedArticle>(&rows_sql);
+    for value in &binds {
+        rows_query = rows_query.bind(*value);
+    }
+    let rows = rows_query.bind(per_page).bind(offset).fetch_all(pool).await?;
 
-    let total = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM articles WHERE status = 'published'",
-    )
-    .fetch_one(pool)
-    .await?;
+    let count_sql = format!("SELECT COUNT(*) FROM articles WHERE {PUBLIC_FILTER}{conditions}");
+    let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql);
+    for value in &binds {
+        count_query = count_query.bind(*value);
+    }
+    let total = count_query.fetch_one(pool).await?;
 
     Ok(PaginatedResponse {
         data: rows,
@@ -128,11 +176,9 @@ pub async fn list_published_articles(
 
 /// Fetches a single article by primary key. Returns `NotFound` if absent.
 pub async fn get_article(pool: &SqlitePool, id: &str) -> AppResult<Article> {
-    sqlx::query_as::<_, Article>(
-        "SELECT id, title, slug, short_text, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes \
-         FROM articles WHERE id = ?",
-    )
+    sqlx::query_as::<_, Article>(&format!(
+        "SELECT {ARTICLE_COLS} FROM articles WHERE id = ?"
+    ))
     .bind(id)
     .fetch_optional(pool)
     .await?
@@ -142,17 +188,56 @@ pub async fn get_article(pool: &SqlitePool, id: &str) -> AppResult<Article> {
 /// Fetches a published article by its slug. Used by the public API — only
 /// `published` articles are visible without authentication.
 pub async fn get_article_by_slug(pool: &SqlitePool, slug: &str) -> AppResult<Article> {
-    sqlx::query_as::<_, Article>(
-        "SELECT id, title, slug, short_text, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, cover_image_id, reading_time_minutes \
-         FROM articles WHERE slug = ? AND status = 'published'",
-    )
+    sqlx::query_as::<_, Article>(&format!(
+        "SELECT {ARTICLE_COLS} FROM articles WHERE slug = ? AND {PUBLIC_FILTER}"
+    ))
     .bind(slug)
     .fetch_optional(pool)
     .await?
     .ok_or(AppError::NotFound)
 }
 
+/// Public slug resolver: like `get_article_by_slug`, but when `slug` is one
+/// the article used in the past, returns `MovedPermanently` pointing at the
+/// article's current API URL instead of `NotFound`.
+pub async fn resolve_article_slug(pool: &SqlitePool, slug: &str) -> AppResult<Article> {
+    match get_article_by_slug(pool, slug).await {
+        Err(AppError::NotFound) => {}
+        other => return other,
+    }
+
+    let Some(owner) = slugs::historic_owner(pool, "article", slug).await? else {
+        return Err(AppError::NotFound);
+    };
+    let current = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT slug FROM articles WHERE id = ? AND {PUBLIC_FILTER}"
+    ))
+    .bind(&owner)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    Err(AppError::MovedPermanently(format!("/api/articles/{current}")))
+}
+
+/// Resolves a public slug like `resolve_article_slug` and returns the article
+/// together with its credited authors, categories, tags and series
+/// membership.
+pub async fn get_public_article(pool: &SqlitePool, slug: &str) -> AppResult<PublicArticle> {
+    let article = resolve_article_slug(pool, slug).await?;
+    let authors = authors::public_credits(pool, &article.id).await?;
+    let categories = categories::categories_for(pool, "article", &article.id).await?;
+    let tags = tags::tags_for(pool, "article", &article.id).await?;
+    let series = series::membership(pool, &article.id).await?;
+    Ok(PublicArticle {
+        article: article.into(),
+        authors,
+        categories,
+        tags,
+        series,
+    })
+}
+
 /// Creates a new article, including an initial revision and optional category
 /// assignments.
 ///
@@ -161,8 +246,10 @@ pub async fn get_article_by_slug(pool: &SqlitePool, slug: &str) -> AppResult<Art
 pub async fn create_article(
     pool: &SqlitePool,
     input: CreateArticle,
-    author_id: &str,
+    author: &User,
 ) -> AppResult<Article> {
+    let author_id = author.id.as_str();
+
     // Derive or validate the slug.
     let slug = input
         .slug
@@ -170,41 +257,78 @@ pub async fn create_article(
         .map(|s| s.to_owned())
         .unwrap_or_else(|| slugify(&input.title));
 
-    ensure_slug_unique(pool, &slug, None).await?;
+    ensure_slug_unique(pool, &slug, None, input.reclaim_slug).await?;
 
     let id = Uuid::new_v4().to_string();
     let short_text = input.short_text.unwrap_or_default();
     let content = input.content.unwrap_or_default();
+    let content_format = input
+        .content_format
+        .unwrap_or_else(|| render::DEFAULT_FORMAT.to_owned());
+    render::validate_format(&content_format)?;
+    if let Some(ref names) = input.tags {
+        tags::validate_names(names)?;
+    }
+    let author_ids = input.author_ids.unwrap_or_else(|| vec![author.id.clone()]);
+    authors::validate_author_ids(pool, &author_ids).await?;
+    let policy = sanitize::policy(pool).await?;
+    let content = render::sanitize_source(&content, &content_format, &policy);
     let status = input.status.unwrap_or_else(|| "draft".to_owned());
     validate_status(&status)?;
-    let reading_time = estimate_reading_time(&content);
+    review::ensure_can_set_status(author, true, None, &status)?;
+    let tz = schedule::site_timezone(pool).await?;
+    let publish_at = input.publish_at.map(|at| schedule::resolve(at, tz)).transpose()?;
+    let unpublish_at = input.unpublish_at.map(|at| schedule::resolve(at, tz)).transpose()?;
+    schedule::ensure_publish_time(&status, publish_at)?;
+    visibility::validate_window(publish_at, unpublish_at)?;
+    visibility::ensure_not_expired(&status, unpublish_at)?;
+    let content_html = render::render(&content, &content_format, &policy);
+    let reading_time = estimate_reading_time(&content_html);
 
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
     sqlx::query(
         "INSERT INTO articles \
-             (id, title, slug, short_text, content, status, publish_at, author_id, \
-              cover_image_id, reading_time_minutes) \
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             (id, title, slug, short_text, content, content_format, content_html, status, \
+              publish_at, unpublish_at, author_id, cover_image_id, reading_time_minutes) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(&id)
     .bind(&input.title)
     .bind(&slug)
     .bind(&short_text)
     .bind(&content)
+    .bind(&content_format)
+    .bind(&content_html)
     .bind(&status)
-    .bind(&input.publish_at)
+    .bind(publish_at.map(schedule::db_timestamp))
+    .bind(unpublish_at.map(schedule::db_timestamp))
     .bind(author_id)
     .bind(&input.cover_image_id)
     .bind(reading_time)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
 
+    // A reclaimed slug stops redirecting to its previous owner.
+    slugs::release(&mut *tx, "article", &slug).await?;
+
     // Attach categories when provided.
     if let Some(ref cat_ids) = input.category_ids {
-        set_article_categories(pool, &id, cat_ids).await?;
+        set_article_categories(&mut tx, &id, cat_ids).await?;
     }
+    if let Some(ref names) = input.tags {
+        tags::set_tags(&mut tx, "article", &id, names).await?;
+    }
+    authors::set_authors(&mut tx, &id, &author_ids).await?;
 
     // Record the initial revision so history starts from creation.
-    create_revision(pool, &id, &input.title, &short_text, &content, author_id).await?;
+    let body = Body {
+        title: &input.title,
+        short_text: &short_text,
+        content: &content,
+        content_format: &content_format,
+    };
+    create_revision(&mut *tx, &id, &body, author_id, None).await?;
+    tx.commit().await?;
 
     audit::log_action(
         pool,
@@ -223,64 +347,200 @@ pub async fn create_article(
 ///
 /// Fields absent from `input` keep their current values. A new revision is
 /// created for every successful update so the full edit history is preserved.
+///
+/// While the article stays published, the content fields (title, short text,
+/// content, cover image) are saved to its draft overlay and the live version
+/// is left untouched; everything else applies immediately. The slug,
+/// categories, tags and authors cannot change along with such edits, or while
+/// a draft is pending. Once the article is no longer published, a pending
+/// draft is folded back into the row.
+///
+/// When `expected_updated_at` no longer matches, nothing is written and the
+/// current version (with its draft) is returned in the conflict error.
 pub async fn update_article(
     pool: &SqlitePool,
     id: &str,
     input: UpdateArticle,
-    user_id: &str,
+    user: &User,
 ) -> AppResult<Article> {
+    let user_id = user.id.as_str();
     let existing = get_article(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "article", id, "update").await?;
+    let draft = get_draft(pool, id).await?;
 
-    // Merge supplied values with existing ones.
-    let title = input.title.unwrap_or_else(|| existing.title.clone());
-    let short_text = input.short_text.unwrap_or_else(|| existing.short_text.clone());
-    let content = input.content.unwrap_or_else(|| existing.content.clone());
+    if concurrency::is_stale(input.expected_updated_at, existing.updated_at) {
+        let version = existing.updated_at;
+        let current = get_article_with_draft(pool, id).await?;
+        return Err(concurrency::conflict(&current, version));
+    }
+
+    // Merge supplied values with the working copy: the draft when there is
+    // one, otherwise the row itself.
+    let base = match &draft {
+        Some(d) => (&d.title, &d.short_text, &d.content, &d.content_format, &d.cover_image_id),
+        None => (
+            &existing.title,
+            &existing.short_text,
+            &existing.content,
+            &existing.content_format,
+            &existing.cover_image_id,
+        ),
+    };
+    let (base_title, base_short_text, base_content, base_content_format, base_cover_image_id) =
+        base;
+    let title = input.title.unwrap_or_else(|| base_title.clone());
+    let short_text = input.short_text.unwrap_or_else(|| base_short_text.clone());
+    let content = input.content.unwrap_or_else(|| base_content.clone());
+    let content_format = input
+        .content_format
+        .unwrap_or_else(|| base_content_format.clone());
+    render::validate_format(&content_format)?;
+    if let Some(ref names) = input.tags {
+        tags::validate_names(names)?;
+    }
+    if let Some(ref author_ids) = input.author_ids {
+        authors::validate_author_ids(pool, author_ids).await?;
+    }
+    let policy = sanitize::policy(pool).await?;
+    let content = render::sanitize_source(&content, &content_format, &policy);
     let status = input.status.unwrap_or_else(|| existing.status.clone());
     validate_status(&status)?;
-    let publish_at = if input.publish_at.is_some() {
-        input.publish_at
-    } else {
-        existing.publish_at
+    let own = ownership::is_author(pool, user, "article", id).await?;
+    review::ensure_can_set_status(user, own, Some(&existing.status), &status)?;
+    let message = revisions::normalize_message(input.message)?;
+    let tz = schedule::site_timezone(pool).await?;
+    let publish_at = match input.publish_at {
+        Some(at) => Some(schedule::resolve(at, tz)?),
+        None => existing.publish_at,
     };
+    let unpublish_at = match input.unpublish_at {
+        Some(at) => at.map(|at| schedule::resolve(at, tz)).transpose()?,
+        None => existing.unpublish_at,
+    };
+    let window_changed =
+        publish_at != existing.publish_at || unpublish_at != existing.unpublish_at;
+    review::ensure_can_reschedule(user, own, &existing.status, window_changed)?;
+    schedule::ensure_publish_time(&status, publish_at)?;
+    visibility::validate_window(publish_at, unpublish_at)?;
+    visibility::ensure_not_expired(&status, unpublish_at)?;
     // Preserve the existing cover image if a new one was not supplied.
     let cover_image_id = if input.cover_image_id.is_some() {
         input.cover_image_id
     } else {
-        existing.cover_image_id.clone()
+        base_cover_image_id.clone()
+    };
+
+    // Live content keeps serving its published version; edits go to the draft.
+    let overlay = existing.status == "published" && status == "published";
+    let (row_title, row_short_text, row_content, row_content_format, row_cover_image_id) =
+        if overlay {
+            (
+                &existing.title,
+                &existing.short_text,
+                &existing.content,
+                &existing.content_format,
+                &existing.cover_image_id,
+            )
+        } else {
+            (&title, &short_text, &content, &content_format, &cover_image_id)
+        };
+    let row_content_html = if overlay {
+        existing.content_html.clone()
+    } else {
+        render::render(&content, &content_format, &policy)
     };
-    let reading_time = estimate_reading_time(&content);
+    let reading_time = estimate_reading_time(&row_content_html);
 
     // Compute the new slug and check uniqueness only when it changed.
     let slug = input.slug.unwrap_or_else(|| existing.slug.clone());
     if slug != existing.slug {
-        ensure_slug_unique(pool, &slug, Some(id)).await?;
+        ensure_slug_unique(pool, &slug, Some(id), input.reclaim_slug).await?;
     }
 
-    sqlx::query(
+    let body = Body {
+        title: &title,
+        short_text: &short_text,
+        content: &content,
+        content_format: &content_format,
+    };
+    let unchanged = title == existing.title
+        && short_text == existing.short_text
+        && content == existing.content
+        && content_format == existing.content_format
+        && cover_image_id == existing.cover_image_id;
+    let to_draft = overlay && !unchanged;
+    if to_draft {
+        let assignments = Assignments {
+            category_ids: input.category_ids.as_deref(),
+            tags: input.tags.as_deref(),
+            author_ids: input.author_ids.as_deref(),
+        };
+        ensure_live_fields_kept(pool, &existing, &slug, &assignments).await?;
+    }
+
+    // The version check, the row, its draft and its assignments are written
+    // together or not at all.
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let written = sqlx::query(
         "UPDATE articles \
-         SET title = ?, slug = ?, short_text = ?, content = ?, status = ?, publish_at = ?, \
+         SET title = ?, slug = ?, short_text = ?, content = ?, content_format = ?, \
+             content_html = ?, status = ?, publish_at = ?, unpublish_at = ?, \
              cover_image_id = ?, reading_time_minutes = ?, \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
-         WHERE id = ?",
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = ? AND strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) = ?",
     )
-    .bind(&title)
+    .bind(row_title)
     .bind(&slug)
-    .bind(&short_text)
-    .bind(&content)
+    .bind(row_short_text)
+    .bind(row_content)
+    .bind(row_content_format)
+    .bind(&row_content_html)
     .bind(&status)
-    .bind(&publish_at)
-    .bind(&cover_image_id)
+    .bind(publish_at.map(schedule::db_timestamp))
+    .bind(unpublish_at.map(schedule::db_timestamp))
+    .bind(row_cover_image_id)
     .bind(reading_time)
     .bind(id)
-    .execute(pool)
+    .bind(concurrency::db_version(existing.updated_at))
+    .execute(&mut *tx)
     .await?;
+    if written.rows_affected() == 0 {
+        // Somebody else saved since the version check above.
+        let current = get_article_with_draft(pool, id).await?;
+        let version = current.article.updated_at;
+        return Err(concurrency::conflict(&current, version));
+    }
 
-    // Update category assignments when explicitly provided.
+    if to_draft {
+        save_draft(&mut *tx, id, &body, &cover_image_id, user_id).await?;
+    } else if draft.is_some() {
+        // Either the edits now match the live version, or they went to the
+        // row directly — the draft has nothing left to hold.
+        delete_draft(&mut *tx, id).await?;
+    }
+
+    if slug != existing.slug {
+        // Keep the old slug redirecting here, and make sure the new one no
+        // longer redirects anywhere else.
+        slugs::release(&mut *tx, "article", &slug).await?;
+        slugs::record(&mut *tx, "article", id, &existing.slug).await?;
+    }
+
+    // Update category, tag and author assignments when explicitly provided.
+    // None is versioned, so they only change when no draft is pending (see
+    // `ensure_live_fields_kept`).
     if let Some(ref cat_ids) = input.category_ids {
-        set_article_categories(pool, id, cat_ids).await?;
+        set_article_categories(&mut tx, id, cat_ids).await?;
+    }
+    if let Some(ref names) = input.tags {
+        tags::set_tags(&mut tx, "article", id, names).await?;
+    }
+    if let Some(ref author_ids) = input.author_ids {
+        authors::set_authors(&mut tx, id, author_ids).await?;
     }
 
-    create_revision(pool, id, &title, &short_text, &content, user_id).await?;
+    create_revision(&mut *tx, id, &body, user_id, message.as_deref()).await?;
+    tx.commit().await?;
 
     audit::log_action(
         pool,
@@ -288,7 +548,7 @@ pub async fn update_article(
         "update",
         "article",
         id,
-        &json!({ "title": title, "slug": slug, "status": status }),
+        &json!({ "title": title, "slug": slug, "status": status, "draft": overlay }),
     )
     .await?;
 
@@ -296,22 +556,23 @@ pub async fn update_article(
 }
 
 /// Moves an article to the trash. Does not delete the row — it can be restored.
-pub async fn trash_article(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<()> {
+pub async fn trash_article(pool: &SqlitePool, id: &str, user: &User) -> AppResult<()> {
     // Verify the article exists before attempting the update.
     get_article(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "article", id, "trash").await?;
 
     sqlx::query(
         "UPDATE articles \
          SET status = 'trashed', \
              trashed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE id = ?",
     )
     .bind(id)
     .execute(pool)
     .await?;
 
-    audit::log_action(pool, user_id, "trash", "article", id, &json!({})).await?;
+    audit::log_action(pool, &user.id, "trash", "article", id, &json!({})).await?;
 
     Ok(())
 }
@@ -320,8 +581,9 @@ pub async fn trash_article(pool: &SqlitePool, id: &str, user_id: &str) -> AppRes
 ///
 /// Returns `BadRequest` when the article is not currently trashed — restoring a
 /// published article would silently demote it, which is almost certainly a mistake.
-pub async fn restore_article(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<Article> {
+pub async fn restore_article(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Article> {
     let existing = get_article(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "article", id, "restore").await?;
 
     if existing.status != "trashed" {
         return Err(AppError::BadRequest(
@@ -332,34 +594,213 @@ pub async fn restore_article(pool: &SqlitePool, id: &str, user_id: &str) -> AppR
     sqlx::query(
         "UPDATE articles \
          SET status = 'draft', trashed_at = NULL, \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE id = ?",
     )
     .bind(id)
     .execute(pool)
     .await?;
 
-    audit::log_action(pool, user_id, "restore", "article", id, &json!({})).await?;
+    audit::log_action(pool, &user.id, "restore", "article", id, &json!({})).await?;
 
     get_article(pool, id).await
 }
 
 /// Transitions an article to `published` status.
-pub async fn publish_article(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<Article> {
-    // Verify the article exists.
+pub async fn publish_article(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Article> {
+    let existing = get_article(pool, id).await?;
+    ownership::ensure_can_publish(pool, user, "article", id, "publish").await?;
+    visibility::ensure_not_expired("published", existing.unpublish_at)?;
+    let policy = sanitize::policy(pool).await?;
+
+    // A pending draft becomes the live version.
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let draft = get_draft(&mut *tx, id).await?;
+    if let Some(draft) = &draft {
+        apply_draft(&mut tx, id, draft, &policy).await?;
+    }
+
+    sqlx::query(
+        "UPDATE articles \
+         SET status = 'published', review_comment = NULL, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = ?",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    audit::log_action(
+        pool,
+        &user.id,
+        "publish",
+        "article",
+        id,
+        &json!({ "draft": draft.is_some() }),
+    )
+    .await?;
+
+    get_article(pool, id).await
+}
+
+/// Returns an article together with its pending draft, if any.
+pub async fn get_article_with_draft(pool: &SqlitePool, id: &str) -> AppResult<ArticleWithDraft> {
+    let article = get_article(pool, id).await?;
+    let draft = get_draft(pool, id).await?;
+    let categories = categories::categories_for(pool, "article", id).await?;
+    let tags = tags::tags_for(pool, "article", id).await?;
+    let authors = authors::credits(pool, id).await?;
+    Ok(ArticleWithDraft {
+        article,
+        draft,
+        categories,
+        tags,
+        authors,
+    })
+}
+
+/// Throws away an article's pending draft, leaving the live version as is.
+pub async fn discard_draft(pool: &SqlitePool, id: &str, user: &User) -> AppResult<()> {
     get_article(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "article", id, "discard_draft").await?;
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    if !delete_draft(&mut *tx, id).await? {
+        return Err(AppError::NotFound);
+    }
+
+    // What editors see changed, so the version moves on too.
+    touch(&mut *tx, id).await?;
+    tx.commit().await?;
+
+    audit::log_action(pool, &user.id, "discard_draft", "article", id, &json!({})).await?;
+
+    Ok(())
+}
+
+/// Submits a draft article for editorial review. For a published article, its
+/// pending draft is submitted instead and the live version stays up.
+pub async fn submit_article(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Article> {
+    let existing = get_article(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "article", id, "submit_review").await?;
+    let draft = get_draft(pool, id).await?;
+    let target = review::submission_target(&existing.status, draft.is_some())?;
+
+    match target {
+        review::Target::Content => {
+            sqlx::query(
+                "UPDATE articles \
+                 SET status = 'in_review', \
+                     updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+                 WHERE id = ?",
+            )
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        review::Target::Draft => {
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+            sqlx::query(
+                "UPDATE article_drafts \
+                 SET in_review = 1, review_comment = NULL \
+                 WHERE article_id = ?",
+            )
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            touch(&mut *tx, id).await?;
+            tx.commit().await?;
+        }
+    }
+
+    let details = json!({ "draft": target == review::Target::Draft });
+    audit::log_action(pool, &user.id, "submit_review", "article", id, &details).await?;
+
+    get_article(pool, id).await
+}
+
+/// Approves an article in review. It is published right away, or scheduled
+/// when it carries a future `publish_at`. A submitted draft of a published
+/// article becomes its live version.
+pub async fn approve_article(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Article> {
+    let existing = get_article(pool, id).await?;
+    ownership::ensure_can_publish(pool, user, "article", id, "approve").await?;
+    let policy = sanitize::policy(pool).await?;
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let draft = get_draft(&mut *tx, id).await?.filter(|draft| draft.in_review);
+    let target = review::review_target(&existing.status, draft.is_some())?;
+    let status = match target {
+        review::Target::Content => review::approved_status(existing.publish_at),
+        review::Target::Draft => "published",
+    };
+    visibility::ensure_not_expired(status, existing.unpublish_at)?;
+
+    if let (review::Target::Draft, Some(draft)) = (target, &draft) {
+        apply_draft(&mut tx, id, draft, &policy).await?;
+    }
 
     sqlx::query(
         "UPDATE articles \
-         SET status = 'published', \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         SET status = ?, review_comment = NULL, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE id = ?",
     )
+    .bind(status)
     .bind(id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
 
-    audit::log_action(pool, user_id, "publish", "article", id, &json!({})).await?;
+    let details = json!({ "status": status, "draft": target == review::Target::Draft });
+    audit::log_action(pool, &user.id, "approve", "article", id, &details).await?;
+
+    get_article(pool, id).await
+}
+
+/// Rejects an article in review, sending it back to draft with the reviewer's
+/// comment attached. A submitted draft of a published article goes back to its
+/// author the same way, with the comment on the draft.
+pub async fn reject_article(
+    pool: &SqlitePool,
+    id: &str,
+    comment: &str,
+    user: &User,
+) -> AppResult<Article> {
+    let existing = get_article(pool, id).await?;
+    ownership::ensure_can_publish(pool, user, "article", id, "reject").await?;
+    let draft_in_review = get_draft(pool, id).await?.is_some_and(|draft| draft.in_review);
+    let target = review::review_target(&existing.status, draft_in_review)?;
+    let comment = review::rejection_comment(comment)?;
+
+    match target {
+        review::Target::Content => {
+            sqlx::query(
+                "UPDATE articles \
+                 SET status = 'draft', review_comment = ?, \
+                     updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+                 WHERE id = ?",
+            )
+            .bind(&comment)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        review::Target::Draft => {
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+            sqlx::query(
+                "UPDATE article_drafts SET in_review = 0, review_comment = ? WHERE article_id = ?",
+            )
+            .bind(&comment)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            touch(&mut *tx, id).await?;
+            tx.commit().await?;
+        }
+    }
+
+    let details = json!({ "comment": comment, "draft": target == review::Target::Draft });
+    audit::log_action(pool, &user.id, "reject", "article", id, &details).await?;
 
     get_article(pool, id).await
 }
@@ -374,10 +815,11 @@ pub async fn list_revisions(
     get_article(pool, article_id).await?;
 
     let revisions = sqlx::query_as::<_, ArticleRevision>(
-        "SELECT id, article_id, title, short_text, content, author_id, created_at \
+        "SELECT id, article_id, title, short_text, content, content_format, author_id, \
+                created_at, message, version_name \
          FROM article_revisions \
          WHERE article_id = ? \
-         ORDER BY created_at DESC",
+         ORDER BY created_at DESC, rowid DESC",
     )
     .bind(article_id)
     .fetch_all(pool)
@@ -386,18 +828,15 @@ pub async fn list_revisions(
     Ok(revisions)
 }
 
-/// Restores an article to the state captured in a specific revision.
-///
-/// This creates a new revision recording the restore event, rather than
-/// removing revisions, so the full history remains intact.
-pub async fn restore_revision(
+/// Fetches a single revision, which must belong to the given article.
+async fn get_revision(
     pool: &SqlitePool,
     article_id: &str,
     revision_id: &str,
-    user_id: &str,
-) -> AppResult<Article> {
-    let revision = sqlx::query_as::<_, ArticleRevision>(
-        "SELECT id, article_id, title, short_text, content, author_id, created_at \
+) -> AppResult<ArticleRevision> {
+    sqlx::query_as::<_, ArticleRevision>(
+        "SELECT id, article_id, title, short_text, content, content_format, author_id, \
+                created_at, message, version_name \
          FROM article_revisions \
          WHERE id = ? AND article_id = ?",
     )
@@ -405,41 +844,154 @@ pub async fn restore_revision(
     .bind(article_id)
     .fetch_optional(pool)
     .await?
-    .ok_or(AppError::NotFound)?;
+    .ok_or(AppError::NotFound)
+}
+
+/// Compares two versions of an article, each given as a revision id or as
+/// `current` for the article as it stands now. The result also diffs `to`
+/// against the current version, so reviewers can see what is live.
+pub async fn diff_revisions(
+    pool: &SqlitePool,
+    article_id: &str,
+    from: &str,
+    to: &str,
+    options: DiffOptions,
+) -> AppResult<RevisionDiff> {
+    let article = get_article(pool, article_id).await?;
+    let current = Snapshot {
+        revision_id: None,
+        author_id: &article.author_id,
+        created_at: article.updated_at,
+        title: &article.title,
+        short_text: Some(&article.short_text),
+        content: &article.content,
+    };
+
+    let from_revision = load_diff_revision(pool, article_id, from).await?;
+    let to_revision = load_diff_revision(pool, article_id, to).await?;
+    let from = from_revision.as_ref().map_or(current, revision_snapshot);
+    let to = to_revision.as_ref().map_or(current, revision_snapshot);
+
+    Ok(diff::compare(&from, &to, &current, options))
+}
+
+fn revision_snapshot(revision: &ArticleRevision) -> Snapshot<'_> {
+    Snapshot {
+        revision_id: Some(&revision.id),
+        author_id: &revision.author_id,
+        created_at: revision.created_at,
+        title: &revision.title,
+        short_text: Some(&revision.short_text),
+        content: &revision.content,
+    }
+}
+
+/// Loads the revision named in a diff path, or `None` for `current`.
+async fn load_diff_revision(
+    pool: &SqlitePool,
+    article_id: &str,
+    revision_id: &str,
+) -> AppResult<Option<ArticleRevision>> {
+    if revision_id == diff::CURRENT {
+        return Ok(None);
+    }
+    get_revision(pool, article_id, revision_id).await.map(Some)
+}
+
+/// Pins a revision as a named version, exempting it from retention.
+pub async fn pin_revision(
+    pool: &SqlitePool,
+    article_id: &str,
+    revision_id: &str,
+    name: &str,
+    user: &User,
+) -> AppResult<ArticleRevision> {
+    ownership::ensure_can_modify(pool, user, "article", article_id, "pin_revision").await?;
+    revisions::pin(pool, "article", article_id, revision_id, name, &user.id).await?;
+    get_revision(pool, article_id, revision_id).await
+}
+
+/// Removes a revision's version name.
+pub async fn unpin_revision(
+    pool: &SqlitePool,
+    article_id: &str,
+    revision_id: &str,
+    user: &User,
+) -> AppResult<ArticleRevision> {
+    ownership::ensure_can_modify(pool, user, "article", article_id, "unpin_revision").await?;
+    revisions::unpin(pool, "article", article_id, revision_id, &user.id).await?;
+    get_revision(pool, article_id, revision_id).await
+}
+
+/// Restores an article to the state captured in a specific revision.
+///
+/// This creates a new revision recording the restore event, rather than
+/// removing revisions, so the full history remains intact.
+pub async fn restore_revision(
+    pool: &SqlitePool,
+    article_id: &str,
+    revision_id: &str,
+    user: &User,
+) -> AppResult<Article> {
+    let revision = get_revision(pool, article_id, revision_id).await?;
+    ownership::ensure_can_modify(pool, user, "article", article_id, "restore_revision").await?;
+    let message = match &revision.version_name {
+        Some(name) => format!("Restored version '{}'", name),
+        None => format!(
+            "Restored revision from {}",
+            revision.created_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
 
     let input = UpdateArticle {
         title: Some(revision.title),
         short_text: Some(revision.short_text),
         content: Some(revision.content),
+        content_format: Some(revision.content_format),
         slug: None,
         status: None,
         publish_at: None,
+        unpublish_at: None,
         category_ids: None,
+        tags: None,
+        author_ids: None,
+        reclaim_slug: false,
+        message: Some(message),
+        expected_updated_at: None,
         cover_image_id: None,
     };
 
-    update_article(pool, article_id, input, user_id).await
+    update_article(pool, article_id, input, user).await
 }
 
-/// Returns published articles that share at least one category with the given
-/// article. Used to populate a "related articles" section on article detail pages.
+/// Returns published articles that share at least one category or tag with
+/// the given article, those with the most in common first. Used to populate a
+/// "related articles" section on article detail pages.
 pub async fn get_related_articles(
     pool: &SqlitePool,
     article_id: &str,
     limit: i64,
-) -> AppResult<Vec<Article>> {
-    let articles = sqlx::query_as::<_, Article>(
-        "SELECT DISTINCT a.id, a.title, a.slug, a.short_text, a.content, a.status, \
-                a.publish_at, a.author_id, a.created_at, a.updated_at, a.trashed_at, \
-                a.cover_image_id, a.reading_time_minutes \
-         FROM articles a \
-         INNER JOIN article_categories ac ON ac.article_id = a.id \
-         WHERE ac.category_id IN (SELECT category_id FROM article_categories WHERE article_id = ?) \
-           AND a.id != ? \
-           AND a.status = 'published' \
-         ORDER BY a.created_at DESC \
-         LIMIT ?",
-    )
+) -> AppResult<Vec<PublishedArticle>> {
+    let articles = sqlx::query_as::<_, PublishedArticle>(&format!(
+        "SELECT {ARTICLE_COLS} FROM articles \
+         JOIN ( \
+             SELECT article_id, COUNT(*) AS overlap FROM ( \
+                 SELECT ac.article_id FROM article_categories ac \
+                 WHERE ac.category_id IN \
+                     (SELECT category_id FROM article_categories WHERE article_id = ?) \
+                 UNION ALL \
+                 SELECT at.article_id FROM article_tags at \
+                 WHERE at.tag_id IN \
+                     (SELECT tag_id FROM article_tags WHERE article_id = ?) \
+             ) \
+             GROUP BY article_id \
+         ) related ON related.article_id = articles.id \
+         WHERE id != ? \
+           AND {PUBLIC_FILTER} \
+         ORDER BY related.overlap DESC, created_at DESC \
+         LIMIT ?"
+    ))
+    .bind(article_id)
     .bind(article_id)
     .bind(article_id)
     .bind(limit)
@@ -453,7 +1005,7 @@ pub async fn get_related_articles(
 
 /// Validates that a status string is one of the allowed values.
 fn validate_status(status: &str) -> AppResult<()> {
-    const VALID: &[&str] = &["draft", "published", "scheduled"];
+    const VALID: &[&str] = &["draft", "in_review", "published", "scheduled", "archived"];
     if !VALID.contains(&status) {
         return Err(AppError::BadRequest(format!(
             "Invalid status '{}'. Must be one of: {}",
@@ -464,7 +1016,16 @@ fn validate_status(status: &str) -> AppResult<()> {
     Ok(())
 }
 
-/// Estimates reading time from HTML content at ~200 words per minute.
+/// Validates the `?status=` filter of the admin list, which additionally
+/// accepts `trashed`.
+fn validate_status_filter(status: &str) -> AppResult<()> {
+    if status == "trashed" {
+        return Ok(());
+    }
+    validate_status(status)
+}
+
+/// Estimates reading time from rendered HTML at ~200 words per minute.
 ///
 /// Strips HTML tags by walking the characters, then counts whitespace-delimited
 /// words. The minimum returned value is 1 minute to avoid showing "0 min read".
@@ -486,45 +1047,206 @@ fn estimate_reading_time(html: &str) -> i32 {
     ((word_count as f64) / 200.0).ceil().max(1.0) as i32
 }
 
+/// The editable text of an article, as captured by revisions and drafts.
+struct Body<'a> {
+    title: &'a str,
+    short_text: &'a str,
+    content: &'a str,
+    content_format: &'a str,
+}
+
+/// The category, tag and author assignments an update asks for.
+struct Assignments<'a> {
+    category_ids: Option<&'a [String]>,
+    tags: Option<&'a [String]>,
+    author_ids: Option<&'a [String]>,
+}
+
+/// Returns `BadRequest` if an update whose edits go to the draft of a
+/// published article also changes its slug, categories, tags or authors.
+/// Those are not part of the draft, so they would go live without review.
+async fn ensure_live_fields_kept(
+    pool: &SqlitePool,
+    existing: &Article,
+    slug: &str,
+    input: &Assignments<'_>,
+) -> AppResult<()> {
+    let mut changed = Vec::new();
+    if slug != existing.slug {
+        changed.push("slug");
+    }
+    if let Some(cat_ids) = input.category_ids {
+        if categories::would_change(pool, "article", &existing.id, cat_ids).await? {
+            changed.push("categories");
+        }
+    }
+    if let Some(names) = input.tags {
+        if tags::would_change(pool, "article", &existing.id, names).await? {
+            changed.push("tags");
+        }
+    }
+    if let Some(author_ids) = input.author_ids {
+        let credited = authors::credits(pool, &existing.id).await?;
+        if !credited.iter().map(|a| &a.id).eq(author_ids.iter()) {
+            changed.push("authors");
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::BadRequest(format!(
+        "The {} of a published article cannot change while edits to it wait in a draft; \
+         publish or discard the draft first",
+        changed.join(", ")
+    )))
+}
+
 /// Inserts a revision row for the given article's current title, short_text,
 /// and content.
 async fn create_revision(
-    pool: &SqlitePool,
+    conn: impl SqliteExecutor<'_>,
     article_id: &str,
-    title: &str,
-    short_text: &str,
-    content: &str,
+    body: &Body<'_>,
     author_id: &str,
+    message: Option<&str>,
 ) -> AppResult<()> {
     sqlx::query(
         "INSERT INTO article_revisions \
-             (id, article_id, title, short_text, content, author_id) \
-         VALUES (?, ?, ?, ?, ?, ?)",
+             (id, article_id, title, short_text, content, content_format, author_id, message) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(Uuid::new_v4().to_string())
     .bind(article_id)
-    .bind(title)
-    .bind(short_text)
-    .bind(content)
+    .bind(body.title)
+    .bind(body.short_text)
+    .bind(body.content)
+    .bind(body.content_format)
     .bind(author_id)
-    .execute(pool)
+    .bind(message)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the article's pending draft, if any.
+async fn get_draft(
+    conn: impl SqliteExecutor<'_>,
+    article_id: &str,
+) -> AppResult<Option<ArticleDraft>> {
+    let draft = sqlx::query_as::<_, ArticleDraft>(
+        "SELECT article_id, title, short_text, content, content_format, cover_image_id, \
+                author_id, updated_at, in_review, review_comment \
+         FROM article_drafts \
+         WHERE article_id = ?",
+    )
+    .bind(article_id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(draft)
+}
+
+/// Creates or replaces the article's draft.
+async fn save_draft(
+    conn: impl SqliteExecutor<'_>,
+    article_id: &str,
+    body: &Body<'_>,
+    cover_image_id: &Option<String>,
+    author_id: &str,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO article_drafts \
+             (article_id, title, short_text, content, content_format, cover_image_id, \
+              author_id) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) \
+         ON CONFLICT(article_id) DO UPDATE SET \
+             title = excluded.title, \
+             short_text = excluded.short_text, \
+             content = excluded.content, \
+             content_format = excluded.content_format, \
+             cover_image_id = excluded.cover_image_id, \
+             author_id = excluded.author_id, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')",
+    )
+    .bind(article_id)
+    .bind(body.title)
+    .bind(body.short_text)
+    .bind(body.content)
+    .bind(body.content_format)
+    .bind(cover_image_id)
+    .bind(author_id)
+    .execute(conn)
     .await?;
 
     Ok(())
 }
 
+/// Copies the draft onto the live article, rendered with `policy`, and
+/// deletes it.
+async fn apply_draft(
+    conn: &mut SqliteConnection,
+    article_id: &str,
+    draft: &ArticleDraft,
+    policy: &SanitizePolicy,
+) -> AppResult<()> {
+    let content_html = render::render(&draft.content, &draft.content_format, policy);
+    sqlx::query(
+        "UPDATE articles \
+         SET title = ?, short_text = ?, content = ?, content_format = ?, content_html = ?, \
+             cover_image_id = ?, reading_time_minutes = ? \
+         WHERE id = ?",
+    )
+    .bind(&draft.title)
+    .bind(&draft.short_text)
+    .bind(&draft.content)
+    .bind(&draft.content_format)
+    .bind(&content_html)
+    .bind(&draft.cover_image_id)
+    .bind(estimate_reading_time(&content_html))
+    .bind(article_id)
+    .execute(&mut *conn)
+    .await?;
+    delete_draft(conn, article_id).await?;
+
+    Ok(())
+}
+
+/// Moves the article's version on after a change that only touched its draft.
+async fn touch(conn: impl SqliteExecutor<'_>, id: &str) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE articles SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = ?",
+    )
+    .bind(id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Deletes the article's draft. Returns whether there was one.
+async fn delete_draft(conn: impl SqliteExecutor<'_>, article_id: &str) -> AppResult<bool> {
+    let result = sqlx::query("DELETE FROM article_drafts WHERE article_id = ?")
+        .bind(article_id)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Replaces all category assignments for an article.
 ///
 /// We delete-then-insert rather than diffing, which is simpler and correct for
 /// the small cardinalities involved in a CMS category list.
 async fn set_article_categories(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     article_id: &str,
     category_ids: &[String],
 ) -> AppResult<()> {
     sqlx::query("DELETE FROM article_categories WHERE article_id = ?")
         .bind(article_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
     for cat_id in category_ids {
@@ -533,7 +1255,7 @@ async fn set_article_categories(
         )
         .bind(article_id)
         .bind(cat_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
     }
 
@@ -541,11 +1263,13 @@ async fn set_article_categories(
 }
 
 /// Returns `Conflict` if `slug` is already used by an article other than
-/// `exclude_id` (pass `None` when creating, `Some(id)` when updating).
+/// `exclude_id` (pass `None` when creating, `Some(id)` when updating), or if it
+/// is a retired slug of another article and `reclaim` is not set.
 async fn ensure_slug_unique(
     pool: &SqlitePool,
     slug: &str,
     exclude_id: Option<&str>,
+    reclaim: bool,
 ) -> AppResult<()> {
     let existing_id = sqlx::query_scalar::<_, String>(
         "SELECT id FROM articles WHERE slug = ? LIMIT 1",
@@ -554,15 +1278,31 @@ async fn ensure_slug_unique(
     .fetch_optional(pool)
     .await?;
 
-    match (existing_id, exclude_id) {
+    let available = match (existing_id, exclude_id) {
         // No existing row with this slug — unique.
-        (None, _) => Ok(()),
+        (None, _) => true,
         // Existing row belongs to the article we're updating — still unique.
-        (Some(ref found_id), Some(excluded)) if found_id == excluded => Ok(()),
+        (Some(ref found_id), Some(excluded)) if found_id == excluded => true,
         // Slug is taken by a different article.
-        _ => Err(AppError::Conflict(format!(
+        _ => false,
+    };
+    if !available {
+        return Err(AppError::Conflict(format!(
             "An article with slug '{}' already exists",
             slug
-        ))),
+        )));
+    }
+
+    // Retired slugs keep redirecting to their previous owner, so they are not
+    // free for other content unless the caller explicitly reclaims them.
+    match slugs::historic_owner(pool, "article", slug).await? {
+        Some(owner) if Some(owner.as_str()) != exclude_id && !reclaim => {
+            Err(AppError::Conflict(format!(
+                "The slug '{}' belonged to another article and still redirects there; \
+                 set reclaim_slug to take it over",
+                slug
+            )))
+        }
+        _ => Ok(()),
     }
 }
@@ -42,3 +42,28 @@ pub async fn log_action(
 
     Ok(())
 }
+
+/// Appends an `audit_log` row for an action the system took on its own, such
+/// as the background scheduler archiving expired content. These rows have no
+/// `user_id`.
+pub async fn log_system_action(
+    pool: &SqlitePool,
+    action: &str,
+    entity_type: &str,
+    entity_id: &str,
+    details: &serde_json::Value,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO audit_log (id, user_id, action, entity_type, entity_id, details) \
+         VALUES (?, NULL, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(action)
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(details.to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,363 @@
+//! Credited authors and author profiles.
+//!
+//! Any user can be credited on an article's byline, in order, independent of
+//! who created it. Crediting a user gives them an author profile (bio,
+//! avatar, links) addressed by slug. The public API only ever shows users
+//! credited on at least one published article, and only their profile —
+//! never the id, email or role of the underlying account.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+
+use crate::db::models::{AuthorLink, AuthorOption, AuthorProfile, PublicAuthor,
+    UpdateAuthorProfile};
+use crate::error::{AppError, AppResult};
+use crate::helpers::slugify;
+use crate::services::audit;
+use crate::services::visibility::PUBLIC_FILTER;
+
+const MAX_LINKS: usize = 10;
+
+/// `author_profiles` joined with `users`, as stored.
+#[derive(sqlx::FromRow)]
+struct ProfileRow {
+    user_id: String,
+    display_name: String,
+    slug: String,
+    bio: String,
+    avatar_id: Option<String>,
+    links: String,
+    updated_at: DateTime<Utc>,
+}
+
+const PROFILE_SELECT: &str =
+    "SELECT p.user_id, u.display_name, p.slug, p.bio, p.avatar_id, p.links, p.updated_at \
+     FROM author_profiles p JOIN users u ON u.id = p.user_id";
+
+impl ProfileRow {
+    fn links(&self) -> Vec<AuthorLink> {
+        serde_json::from_str(&self.links).unwrap_or_default()
+    }
+
+    fn into_profile(self) -> AuthorProfile {
+        AuthorProfile {
+            links: self.links(),
+            user_id: self.user_id,
+            display_name: self.display_name,
+            slug: self.slug,
+            bio: self.bio,
+            avatar_id: self.avatar_id,
+            updated_at: self.updated_at,
+        }
+    }
+
+    fn into_public(self) -> PublicAuthor {
+        PublicAuthor {
+            links: self.links(),
+            slug: self.slug,
+            display_name: self.display_name,
+            bio: self.bio,
+            avatar_id: self.avatar_id,
+        }
+    }
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns all users that can be credited, ordered by name.
+pub async fn list_author_options(pool: &SqlitePool) -> AppResult<Vec<AuthorOption>> {
+    let users = sqlx::query_as::<_, AuthorOption>(
+        "SELECT id, display_name FROM users ORDER BY display_name COLLATE NOCASE ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(users)
+}
+
+/// Returns the users credited on an article, in byline order.
+pub async fn credits(pool: &SqlitePool, article_id: &str) -> AppResult<Vec<AuthorOption>> {
+    let users = sqlx::query_as::<_, AuthorOption>(
+        "SELECT u.id, u.display_name FROM article_authors aa \
+         JOIN users u ON u.id = aa.user_id \
+         WHERE aa.article_id = ? \
+         ORDER BY aa.position ASC",
+    )
+    .bind(article_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(users)
+}
+
+/// Returns the public profiles of the authors credited on an article, in
+/// byline order.
+pub async fn public_credits(pool: &SqlitePool, article_id: &str) -> AppResult<Vec<PublicAuthor>> {
+    let rows = sqlx::query_as::<_, ProfileRow>(&format!(
+        "{PROFILE_SELECT} \
+         JOIN article_authors aa ON aa.user_id = p.user_id \
+         WHERE aa.article_id = ? \
+         ORDER BY aa.position ASC"
+    ))
+    .bind(article_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(ProfileRow::into_public).collect())
+}
+
+/// Creates profiles for credited authors who have none, as after migration
+/// 017 credited every existing article to its creator.
+pub async fn ensure_credited_profiles(pool: &SqlitePool) -> AppResult<()> {
+    let user_ids = sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT user_id FROM article_authors \
+         WHERE user_id NOT IN (SELECT user_id FROM author_profiles)",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for user_id in user_ids {
+        ensure_profile(&mut *pool.acquire().await?, &user_id).await?;
+    }
+    Ok(())
+}
+
+/// Checks a list of author ids before anything is written: at least one
+/// author, no duplicates, and every id an existing user.
+pub async fn validate_author_ids(pool: &SqlitePool, user_ids: &[String]) -> AppResult<()> {
+    if user_ids.is_empty() {
+        return Err(AppError::BadRequest("An article needs at least one author".into()));
+    }
+    let mut seen = HashSet::new();
+    for user_id in user_ids {
+        if !seen.insert(user_id.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "User '{}' is credited more than once",
+                user_id
+            )));
+        }
+        let exists = sqlx::query_scalar::<_, String>("SELECT id FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        if exists.is_none() {
+            return Err(AppError::BadRequest(format!("User '{}' does not exist", user_id)));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the credited authors of an article, in the given order. Each
+/// author gets a profile if they do not have one yet.
+pub async fn set_authors(
+    conn: &mut SqliteConnection,
+    article_id: &str,
+    user_ids: &[String],
+) -> AppResult<()> {
+    sqlx::query("DELETE FROM article_authors WHERE article_id = ?")
+        .bind(article_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for (position, user_id) in user_ids.iter().enumerate() {
+        ensure_profile(conn, user_id).await?;
+        sqlx::query("INSERT INTO article_authors (article_id, user_id, position) VALUES (?, ?, ?)")
+            .bind(article_id)
+            .bind(user_id)
+            .bind(position as i64)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Returns all authors credited on at least one published article, ordered
+/// by name.
+pub async fn list_public_authors(pool: &SqlitePool) -> AppResult<Vec<PublicAuthor>> {
+    let rows = sqlx::query_as::<_, ProfileRow>(&format!(
+        "{PROFILE_SELECT} \
+         WHERE p.user_id IN ({credited}) \
+         ORDER BY u.display_name COLLATE NOCASE ASC",
+        credited = published_author_ids(),
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(ProfileRow::into_public).collect())
+}
+
+/// Fetches a public author profile by slug. Returns `NotFound` unless the
+/// user is credited on a published article.
+pub async fn get_public_author(pool: &SqlitePool, slug: &str) -> AppResult<PublicAuthor> {
+    sqlx::query_as::<_, ProfileRow>(&format!(
+        "{PROFILE_SELECT} WHERE p.slug = ? AND p.user_id IN ({credited})",
+        credited = published_author_ids(),
+    ))
+    .bind(slug)
+    .fetch_optional(pool)
+    .await?
+    .map(ProfileRow::into_public)
+    .ok_or(AppError::NotFound)
+}
+
+/// Returns a user's author profile, creating an empty one if needed.
+pub async fn get_profile(pool: &SqlitePool, user_id: &str) -> AppResult<AuthorProfile> {
+    ensure_profile(&mut *pool.acquire().await?, user_id).await?;
+    sqlx::query_as::<_, ProfileRow>(&format!("{PROFILE_SELECT} WHERE p.user_id = ?"))
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map(ProfileRow::into_profile)
+        .map_err(Into::into)
+}
+
+/// Updates a user's author profile. Fields absent from `input` keep their
+/// current values. Returns `Conflict` if the slug belongs to someone else.
+pub async fn update_profile(
+    pool: &SqlitePool,
+    user_id: &str,
+    input: UpdateAuthorProfile,
+    acting_user_id: &str,
+) -> AppResult<AuthorProfile> {
+    let existing = get_profile(pool, user_id).await?;
+
+    let slug = match input.slug.as_deref().map(str::trim) {
+        Some(slug) => {
+            let slug = slugify(slug);
+            if slug.is_empty() {
+                return Err(AppError::BadRequest("Author slug must not be empty".into()));
+            }
+            slug
+        }
+        None => existing.slug.clone(),
+    };
+    if slug != existing.slug && slug_owner(pool, &slug).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "An author with slug '{}' already exists",
+            slug
+        )));
+    }
+
+    let avatar_id = match input.avatar_id {
+        Some(a) if a.is_empty() => None,
+        Some(a) => {
+            let exists = sqlx::query_scalar::<_, String>("SELECT id FROM media WHERE id = ?")
+                .bind(&a)
+                .fetch_optional(pool)
+                .await?;
+            if exists.is_none() {
+                return Err(AppError::BadRequest(format!("Avatar '{}' does not exist", a)));
+            }
+            Some(a)
+        }
+        None => existing.avatar_id.clone(),
+    };
+
+    let links = match input.links {
+        Some(links) => validate_links(links)?,
+        None => existing.links,
+    };
+
+    sqlx::query(
+        "UPDATE author_profiles \
+         SET slug = ?, bio = ?, avatar_id = ?, links = ?, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         WHERE user_id = ?",
+    )
+    .bind(&slug)
+    .bind(input.bio.unwrap_or(existing.bio))
+    .bind(&avatar_id)
+    .bind(json!(links).to_string())
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    audit::log_action(pool, acting_user_id, "update_profile", "user", user_id, &json!({ "slug": slug }))
+        .await?;
+
+    get_profile(pool, user_id).await
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// SELECT yielding the ids of users credited on a published article.
+fn published_author_ids() -> String {
+    format!(
+        "SELECT aa.user_id FROM article_authors aa \
+         WHERE aa.article_id IN (SELECT id FROM articles WHERE {PUBLIC_FILTER})"
+    )
+}
+
+async fn slug_owner(conn: impl SqliteExecutor<'_>, slug: &str) -> AppResult<Option<String>> {
+    let owner = sqlx::query_scalar::<_, String>("SELECT user_id FROM author_profiles WHERE slug = ?")
+        .bind(slug)
+        .fetch_optional(conn)
+        .await?;
+    Ok(owner)
+}
+
+/// Creates an empty profile for a user who has none. The slug is derived
+/// from the display name, with a numeric suffix when it is taken.
+async fn ensure_profile(conn: &mut SqliteConnection, user_id: &str) -> AppResult<()> {
+    let has_profile =
+        sqlx::query_scalar::<_, String>("SELECT user_id FROM author_profiles WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if has_profile.is_some() {
+        return Ok(());
+    }
+
+    let display_name = sqlx::query_scalar::<_, String>("SELECT display_name FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let base = match slugify(&display_name) {
+        base if base.is_empty() => "author".to_owned(),
+        base => base,
+    };
+    let mut slug = base.clone();
+    let mut suffix = 2;
+    while slug_owner(&mut *conn, &slug).await?.is_some() {
+        slug = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO author_profiles (user_id, slug) VALUES (?, ?)")
+        .bind(user_id)
+        .bind(&slug)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Trims link labels and URLs and rejects anything but web and mail links,
+/// so a profile cannot carry `javascript:` URLs onto the public site.
+fn validate_links(links: Vec<AuthorLink>) -> AppResult<Vec<AuthorLink>> {
+    if links.len() > MAX_LINKS {
+        return Err(AppError::BadRequest(format!(
+            "An author profile can have at most {MAX_LINKS} links"
+        )));
+    }
+    links
+        .into_iter()
+        .map(|link| {
+            let label = link.label.trim().to_owned();
+            let url = link.url.trim().to_owned();
+            let lower = url.to_ascii_lowercase();
+            if !["https://", "http://", "mailto:"].iter().any(|s| lower.starts_with(s)) {
+                return Err(AppError::BadRequest(format!(
+                    "Link '{}' must be an http(s) or mailto URL",
+                    url
+                )));
+            }
+            let label = if label.is_empty() { url.clone() } else { label };
+            Ok(AuthorLink { label, url })
+        })
+        .collect()
+}
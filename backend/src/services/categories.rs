@@ -1,23 +1,48 @@
 //! Categories CRUD service.
 //!
-//! Categories are a flat taxonomy that can be attached to pages and articles
-//! via join tables. Slug uniqueness is enforced here so all callers benefit
-//! from the check regardless of which entry point is used.
+//! Categories are a curated taxonomy that can be attached to pages and
+//! articles via join tables. They form a tree: listing content by a category
+//! includes everything filed under its descendants. Slug uniqueness is
+//! enforced here so all callers benefit from the check regardless of which
+//! entry point is used.
+
+use std::collections::HashSet;
 
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::db::models::{Category, CreateCategory};
+use crate::db::models::{Category, CategoryUsage, CreateCategory, UpdateCategory};
 use crate::error::{AppError, AppResult};
 use crate::helpers::slugify;
+use crate::services::visibility::PUBLIC_FILTER;
+
+// ─── Column list shared by all SELECT queries ─────────────────────────────────
+
+const CATEGORY_COLS: &str =
+    "id, name, slug, parent_id, description, cover_image_id, sort_order";
+
+/// Returns a SELECT yielding the ids of the category picked by `root` (a
+/// single-column SELECT, typically taking one bind parameter) and of all its
+/// descendants. For use in `category_id IN (...)` filters.
+pub(crate) fn subtree_ids(root: &str) -> String {
+    format!(
+        "WITH RECURSIVE subtree(id) AS ( \
+             {root} \
+             UNION \
+             SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id \
+         ) \
+         SELECT id FROM subtree"
+    )
+}
 
 // ─── Public service functions ─────────────────────────────────────────────────
 
-/// Returns all categories ordered alphabetically by name.
+/// Returns all categories in tree order: by position among siblings, then
+/// alphabetically by name. Clients build the tree from `parent_id`.
 pub async fn list_categories(pool: &SqlitePool) -> AppResult<Vec<Category>> {
-    let categories = sqlx::query_as::<_, Category>(
-        "SELECT id, name, slug FROM categories ORDER BY name ASC",
-    )
+    let categories = sqlx::query_as::<_, Category>(&format!(
+        "SELECT {CATEGORY_COLS} FROM categories ORDER BY sort_order ASC, name ASC"
+    ))
     .fetch_all(pool)
     .await?;
 
@@ -26,16 +51,88 @@ pub async fn list_categories(pool: &SqlitePool) -> AppResult<Vec<Category>> {
 
 /// Fetches a single category by primary key. Returns `NotFound` if absent.
 pub async fn get_category(pool: &SqlitePool, id: &str) -> AppResult<Category> {
-    sqlx::query_as::<_, Category>("SELECT id, name, slug FROM categories WHERE id = ?")
+    sqlx::query_as::<_, Category>(&format!("SELECT {CATEGORY_COLS} FROM categories WHERE id = ?"))
         .bind(id)
         .fetch_optional(pool)
         .await?
         .ok_or(AppError::NotFound)
 }
 
+/// Fetches a single category by slug. Returns `NotFound` if absent.
+pub async fn get_category_by_slug(pool: &SqlitePool, slug: &str) -> AppResult<Category> {
+    sqlx::query_as::<_, Category>(&format!("SELECT {CATEGORY_COLS} FROM categories WHERE slug = ?"))
+        .bind(slug)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)
+}
+
+/// Returns all categories with the number of published pages and articles
+/// in each, counting content filed under descendants too. Ordered like
+/// `list_categories`. Drafts, scheduled and trashed content are not counted,
+/// so the numbers match what a visitor can open.
+pub async fn list_public_categories(pool: &SqlitePool) -> AppResult<Vec<CategoryUsage>> {
+    // `tree` pairs every category with itself and each of its descendants.
+    let categories = sqlx::query_as::<_, CategoryUsage>(&format!(
+        "WITH RECURSIVE tree(ancestor_id, category_id) AS ( \
+             SELECT id, id FROM categories \
+             UNION \
+             SELECT tree.ancestor_id, c.id FROM categories c JOIN tree ON c.parent_id = tree.category_id \
+         ) \
+         SELECT {CATEGORY_COLS}, \
+                (SELECT COUNT(*) FROM pages WHERE {PUBLIC_FILTER} AND id IN \
+                    (SELECT pc.page_id FROM page_categories pc \
+                     JOIN tree ON tree.category_id = pc.category_id \
+                     WHERE tree.ancestor_id = categories.id)) AS page_count, \
+                (SELECT COUNT(*) FROM articles WHERE {PUBLIC_FILTER} AND id IN \
+                    (SELECT ac.article_id FROM article_categories ac \
+                     JOIN tree ON tree.category_id = ac.category_id \
+                     WHERE tree.ancestor_id = categories.id)) AS article_count \
+         FROM categories \
+         ORDER BY sort_order ASC, name ASC"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
+/// Returns the categories assigned to a page or article, ordered by name.
+pub async fn categories_for(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+) -> AppResult<Vec<Category>> {
+    let (table, owner) = join_table(entity_type);
+    let categories = sqlx::query_as::<_, Category>(&format!(
+        "SELECT {CATEGORY_COLS} FROM categories \
+         WHERE id IN (SELECT category_id FROM {table} WHERE {owner} = ?) \
+         ORDER BY name ASC"
+    ))
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
+/// Returns whether assigning `category_ids` would change the categories of a
+/// page or article.
+pub async fn would_change(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    category_ids: &[String],
+) -> AppResult<bool> {
+    let current = categories_for(pool, entity_type, entity_id).await?;
+    let wanted: HashSet<&str> = category_ids.iter().map(String::as_str).collect();
+    Ok(wanted != current.iter().map(|category| category.id.as_str()).collect())
+}
+
 /// Creates a new category.
 ///
-/// When `input.slug` is absent the slug is derived from the name. Returns
+/// When `input.slug` is absent the slug is derived from the name, and a
+/// missing `sort_order` places the category after its siblings. Returns
 /// `Conflict` if the resulting slug is already taken.
 pub async fn create_category(pool: &SqlitePool, input: CreateCategory) -> AppResult<Category> {
     let slug = input
@@ -46,51 +143,126 @@ pub async fn create_category(pool: &SqlitePool, input: CreateCategory) -> AppRes
 
     ensure_slug_unique(pool, &slug, None).await?;
 
+    // Empty strings mean "none", same as omitting the field.
+    let parent_id = input.parent_id.filter(|p| !p.is_empty());
+    if let Some(ref parent_id) = parent_id {
+        get_category(pool, parent_id).await?;
+    }
+    let cover_image_id = input.cover_image_id.filter(|c| !c.is_empty());
+    if let Some(ref cover_image_id) = cover_image_id {
+        ensure_media_exists(pool, cover_image_id).await?;
+    }
+    let sort_order = match input.sort_order {
+        Some(order) => order,
+        None => next_sort_order(pool, parent_id.as_deref()).await?,
+    };
+
     let id = Uuid::new_v4().to_string();
 
-    sqlx::query("INSERT INTO categories (id, name, slug) VALUES (?, ?, ?)")
-        .bind(&id)
-        .bind(&input.name)
-        .bind(&slug)
-        .execute(pool)
-        .await?;
+    sqlx::query(
+        "INSERT INTO categories (id, name, slug, parent_id, description, cover_image_id, sort_order) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&input.name)
+    .bind(&slug)
+    .bind(&parent_id)
+    .bind(input.description.unwrap_or_default())
+    .bind(&cover_image_id)
+    .bind(sort_order)
+    .execute(pool)
+    .await?;
 
     get_category(pool, &id).await
 }
 
-/// Updates the name and/or slug of an existing category.
+/// Updates an existing category. Fields left out of `input` keep their
+/// current values.
 ///
 /// Slug uniqueness is checked only when the slug actually changes so that
 /// an update that only touches the name does not trigger a spurious conflict.
+/// A category cannot be moved beneath itself or one of its descendants.
 pub async fn update_category(
     pool: &SqlitePool,
     id: &str,
-    name: String,
-    slug: Option<String>,
+    input: UpdateCategory,
 ) -> AppResult<Category> {
     let existing = get_category(pool, id).await?;
 
-    let new_slug = slug.unwrap_or_else(|| existing.slug.clone());
+    let new_slug = input.slug.unwrap_or_else(|| existing.slug.clone());
     if new_slug != existing.slug {
         ensure_slug_unique(pool, &new_slug, Some(id)).await?;
     }
 
-    sqlx::query("UPDATE categories SET name = ?, slug = ? WHERE id = ?")
-        .bind(&name)
-        .bind(&new_slug)
-        .bind(id)
-        .execute(pool)
-        .await?;
+    let parent_id = match input.parent_id {
+        Some(p) if p.is_empty() => None,
+        Some(p) => Some(p),
+        None => existing.parent_id.clone(),
+    };
+    if parent_id != existing.parent_id {
+        if let Some(ref new_parent) = parent_id {
+            ensure_no_cycle(pool, id, new_parent).await?;
+        }
+    }
+
+    let cover_image_id = match input.cover_image_id {
+        Some(c) if c.is_empty() => None,
+        Some(c) => {
+            ensure_media_exists(pool, &c).await?;
+            Some(c)
+        }
+        None => existing.cover_image_id.clone(),
+    };
+
+    sqlx::query(
+        "UPDATE categories \
+         SET name = ?, slug = ?, parent_id = ?, description = ?, cover_image_id = ?, sort_order = ? \
+         WHERE id = ?",
+    )
+    .bind(input.name.unwrap_or(existing.name))
+    .bind(&new_slug)
+    .bind(&parent_id)
+    .bind(input.description.unwrap_or(existing.description))
+    .bind(&cover_image_id)
+    .bind(input.sort_order.unwrap_or(existing.sort_order))
+    .bind(id)
+    .execute(pool)
+    .await?;
 
     get_category(pool, id).await
 }
 
-/// Deletes a category. The database schema cascades this to join-table rows
-/// (page_categories, article_categories), so no explicit cleanup is needed here.
-pub async fn delete_category(pool: &SqlitePool, id: &str) -> AppResult<()> {
+/// Deletes a category. Its child categories move up to its parent.
+///
+/// With `reassign_to`, pages and articles filed under the category are filed
+/// under that category instead; otherwise they simply lose it (the join rows
+/// go with the category via ON DELETE CASCADE).
+pub async fn delete_category(
+    pool: &SqlitePool,
+    id: &str,
+    reassign_to: Option<&str>,
+) -> AppResult<()> {
     // Confirm the category exists before attempting deletion so we return a
     // proper 404 rather than silently deleting zero rows.
-    get_category(pool, id).await?;
+    let category = get_category(pool, id).await?;
+
+    if let Some(target_id) = reassign_to {
+        if target_id == id {
+            return Err(AppError::BadRequest(
+                "Content cannot be reassigned to the category being deleted".into(),
+            ));
+        }
+        get_category(pool, target_id).await.map_err(|_| {
+            AppError::BadRequest(format!("Category '{}' does not exist", target_id))
+        })?;
+        reassign_content(pool, id, target_id).await?;
+    }
+
+    sqlx::query("UPDATE categories SET parent_id = ? WHERE parent_id = ?")
+        .bind(&category.parent_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
 
     sqlx::query("DELETE FROM categories WHERE id = ?")
         .bind(id)
@@ -100,8 +272,128 @@ pub async fn delete_category(pool: &SqlitePool, id: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Merges category `id` into `into_id`: its content and its child categories
+/// move to the latter, and it is deleted. Returns the surviving category.
+///
+/// The target must not lie beneath the merged category, since its children
+/// would otherwise end up beneath themselves.
+pub async fn merge_categories(pool: &SqlitePool, id: &str, into_id: &str) -> AppResult<Category> {
+    if id == into_id {
+        return Err(AppError::BadRequest("A category cannot be merged into itself".into()));
+    }
+    get_category(pool, id).await?;
+    let target = get_category(pool, into_id).await?;
+    if ancestor_ids(pool, into_id).await?.iter().any(|a| a == id) {
+        return Err(AppError::BadRequest(
+            "A category cannot be merged into one of its descendants".into(),
+        ));
+    }
+
+    reassign_content(pool, id, into_id).await?;
+
+    sqlx::query("UPDATE categories SET parent_id = ? WHERE parent_id = ?")
+        .bind(into_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query("DELETE FROM categories WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    get_category(pool, &target.id).await
+}
+
 // ─── Internal helpers ─────────────────────────────────────────────────────────
 
+/// Join table and owner column for an entity type.
+fn join_table(entity_type: &str) -> (&'static str, &'static str) {
+    match entity_type {
+        "page" => ("page_categories", "page_id"),
+        _ => ("article_categories", "article_id"),
+    }
+}
+
+/// Files everything filed under `from_id` under `to_id` as well. Content
+/// already in both keeps a single assignment.
+async fn reassign_content(pool: &SqlitePool, from_id: &str, to_id: &str) -> AppResult<()> {
+    for entity_type in ["page", "article"] {
+        let (table, owner) = join_table(entity_type);
+        sqlx::query(&format!(
+            "INSERT OR IGNORE INTO {table} ({owner}, category_id) \
+             SELECT {owner}, ? FROM {table} WHERE category_id = ?"
+        ))
+        .bind(to_id)
+        .bind(from_id)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Returns the ids of all ancestors of a category, nearest first.
+async fn ancestor_ids(pool: &SqlitePool, id: &str) -> AppResult<Vec<String>> {
+    let ids = sqlx::query_scalar::<_, String>(
+        "WITH RECURSIVE up(id, parent_id, depth) AS ( \
+             SELECT id, parent_id, 0 FROM categories WHERE id = ? \
+             UNION \
+             SELECT c.id, c.parent_id, up.depth + 1 FROM categories c JOIN up ON c.id = up.parent_id \
+         ) \
+         SELECT id FROM up WHERE depth > 0 ORDER BY depth ASC",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids)
+}
+
+/// Returns `BadRequest` if moving `category_id` under `new_parent_id` would
+/// create a cycle, and `NotFound` if the new parent does not exist.
+async fn ensure_no_cycle(pool: &SqlitePool, category_id: &str, new_parent_id: &str) -> AppResult<()> {
+    get_category(pool, new_parent_id).await?;
+
+    let creates_cycle = new_parent_id == category_id
+        || ancestor_ids(pool, new_parent_id)
+            .await?
+            .iter()
+            .any(|a| a == category_id);
+
+    if creates_cycle {
+        return Err(AppError::BadRequest(
+            "A category cannot be moved beneath itself or one of its descendants".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns `BadRequest` if no media item with `id` exists.
+async fn ensure_media_exists(pool: &SqlitePool, id: &str) -> AppResult<()> {
+    let found = sqlx::query_scalar::<_, String>("SELECT id FROM media WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    match found {
+        Some(_) => Ok(()),
+        None => Err(AppError::BadRequest(format!("Cover image '{}' does not exist", id))),
+    }
+}
+
+/// Returns the sort order that places a new category after its siblings.
+async fn next_sort_order(pool: &SqlitePool, parent_id: Option<&str>) -> AppResult<i32> {
+    let max_order = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(sort_order) FROM categories WHERE parent_id IS ?",
+    )
+    .bind(parent_id)
+    .fetch_one(pool)
+    .await?
+    .unwrap_or(-1);
+
+    Ok((max_order + 1) as i32)
+}
+
 /// Returns `Conflict` if `slug` is already used by a category other than
 /// `exclude_id` (pass `None` when creating, `Some(id)` when updating).
 async fn ensure_slug_unique(
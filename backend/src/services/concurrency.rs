@@ -0,0 +1,84 @@
+//! Optimistic concurrency for admin edits.
+//!
+//! Pages, articles, apps, menus and the settings table each carry an
+//! `updated_at` that doubles as their version. Admin reads return it as an
+//! `ETag`; updates may send it back in `If-Match` or as `expected_updated_at`,
+//! and are rejected with 409 and the current server version when somebody else
+//! saved in between. Updates that send neither are applied unconditionally.
+//!
+//! The check and the write must not be separable by another save: single-row
+//! updates only write while the row is still at the version they checked
+//! (see [`db_version`]) and report a conflict when nothing was written; menus
+//! and settings, which span several rows, check and write inside one
+//! `BEGIN IMMEDIATE` transaction.
+//!
+//! Versions are written with millisecond precision
+//! (`strftime('%Y-%m-%dT%H:%M:%fZ', 'now')`) so two saves within the same
+//! second still produce different versions.
+
+use axum::http::{header, HeaderMap, HeaderName};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::{AppError, AppResult};
+
+/// Formats a version as a strong entity tag.
+pub fn etag(version: DateTime<Utc>) -> String {
+    format!("\"{}\"", version.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+}
+
+/// Parses the value of an `If-Match` header. `*` matches any version and is
+/// treated like no precondition; otherwise the first tag is used.
+pub fn parse_if_match(value: &str) -> AppResult<Option<DateTime<Utc>>> {
+    let tag = value.split(',').next().unwrap_or("").trim();
+    if tag == "*" {
+        return Ok(None);
+    }
+    let tag = tag.trim_start_matches("W/").trim_matches('"');
+    DateTime::parse_from_rfc3339(tag)
+        .map(|dt| Some(dt.with_timezone(&Utc)))
+        .map_err(|_| AppError::BadRequest("If-Match must be an ETag from this API".into()))
+}
+
+/// The version an update was based on: the `If-Match` header when present,
+/// otherwise the `expected_updated_at` body field.
+pub fn expected_version(
+    headers: &HeaderMap,
+    expected_updated_at: Option<DateTime<Utc>>,
+) -> AppResult<Option<DateTime<Utc>>> {
+    match headers.get(header::IF_MATCH) {
+        Some(value) => {
+            let value = value
+                .to_str()
+                .map_err(|_| AppError::BadRequest("Invalid If-Match header".into()))?;
+            parse_if_match(value)
+        }
+        None => Ok(expected_updated_at),
+    }
+}
+
+/// `ETag` response header for a version.
+pub fn etag_header(version: DateTime<Utc>) -> [(HeaderName, String); 1] {
+    [(header::ETAG, etag(version))]
+}
+
+/// True when the caller expected a version other than `current`.
+pub fn is_stale(expected: Option<DateTime<Utc>>, current: DateTime<Utc>) -> bool {
+    expected.is_some_and(|expected| expected != current)
+}
+
+/// A version as `strftime('%Y-%m-%dT%H:%M:%fZ', updated_at)` renders it, for
+/// the `WHERE` clause of a conditional update. Older rows store whole seconds,
+/// so the column is normalised in SQL rather than compared as stored.
+pub fn db_version(version: DateTime<Utc>) -> String {
+    version.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// Builds the 409 returned for a stale update, carrying the current version
+/// of the resource so the client can show or merge it.
+pub fn conflict<T: Serialize>(current: &T, version: DateTime<Utc>) -> AppError {
+    AppError::VersionConflict {
+        current: serde_json::to_value(current).unwrap_or_default(),
+        etag: etag(version),
+    }
+}
@@ -0,0 +1,398 @@
+//! Revision diffing shared by pages and articles.
+//!
+//! Two snapshots of a field are split into tokens and compared with a
+//! patience diff. By default the comparison runs over the visible text —
+//! HTML tags are stripped and block elements become line breaks — so
+//! reviewers see what a reader would see change. `format=html` diffs the raw
+//! markup instead, keeping each tag as a single token.
+//!
+//! `granularity=word` (the default) reports changes word by word;
+//! `granularity=block` reports whole paragraphs / block elements, which is
+//! easier to scan for large rewrites.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, ChangeTag};
+
+/// Path keyword standing for the current version instead of a revision id.
+pub const CURRENT: &str = "current";
+
+/// What the diff runs over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffFormat {
+    /// Visible text with HTML tags stripped.
+    #[default]
+    Text,
+    /// Raw HTML source.
+    Html,
+}
+
+/// Size of the units compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffGranularity {
+    #[default]
+    Word,
+    Block,
+}
+
+/// `?format=&granularity=` query parameters of the diff endpoints.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct DiffOptions {
+    #[serde(default)]
+    pub format: DiffFormat,
+    #[serde(default)]
+    pub granularity: DiffGranularity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A run of consecutive tokens with the same operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// The diff of a single field.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDiff {
+    pub changed: bool,
+    pub segments: Vec<DiffSegment>,
+}
+
+/// One version of a page or article as seen by the diff. `short_text` is
+/// `None` for pages.
+#[derive(Clone, Copy)]
+pub struct Snapshot<'a> {
+    /// `None` for the current version.
+    pub revision_id: Option<&'a str>,
+    pub author_id: &'a str,
+    pub created_at: DateTime<Utc>,
+    pub title: &'a str,
+    pub short_text: Option<&'a str>,
+    pub content: &'a str,
+}
+
+/// Identifies one side of a comparison in the response.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffSide {
+    /// `None` for the current version.
+    pub revision_id: Option<String>,
+    pub author_id: String,
+    /// Revision time, or the last update for the current version.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Field-by-field diff between two versions.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentDiff {
+    pub title: FieldDiff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_text: Option<FieldDiff>,
+    pub content: FieldDiff,
+}
+
+/// Response of the revision diff endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct RevisionDiff {
+    pub format: DiffFormat,
+    pub granularity: DiffGranularity,
+    pub from: DiffSide,
+    pub to: DiffSide,
+    pub current: DiffSide,
+    /// Changes from `from` to `to`.
+    pub changes: ContentDiff,
+    /// Changes from `to` to the current (live, when published) version.
+    pub against_current: ContentDiff,
+}
+
+/// Diffs `from` against `to`, and `to` against the current version.
+pub fn compare(
+    from: &Snapshot<'_>,
+    to: &Snapshot<'_>,
+    current: &Snapshot<'_>,
+    options: DiffOptions,
+) -> RevisionDiff {
+    RevisionDiff {
+        format: options.format,
+        granularity: options.granularity,
+        from: side(from),
+        to: side(to),
+        current: side(current),
+        changes: diff_snapshots(from, to, options),
+        against_current: diff_snapshots(to, current, options),
+    }
+}
+
+fn side(snapshot: &Snapshot<'_>) -> DiffSide {
+    DiffSide {
+        revision_id: snapshot.revision_id.map(str::to_owned),
+        author_id: snapshot.author_id.to_owned(),
+        created_at: snapshot.created_at,
+    }
+}
+
+fn diff_snapshots(old: &Snapshot<'_>, new: &Snapshot<'_>, options: DiffOptions) -> ContentDiff {
+    ContentDiff {
+        // Titles and summaries are plain text; only the body carries markup.
+        title: diff_plain(old.title, new.title, options),
+        short_text: old
+            .short_text
+            .zip(new.short_text)
+            .map(|(old, new)| diff_plain(old, new, options)),
+        content: diff_field(old.content, new.content, options),
+    }
+}
+
+/// Block-level elements whose boundaries end a block (and a line of text).
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "h1", "h2", "h3", "h4", "h5", "h6", "li", "ul", "ol", "blockquote", "pre",
+    "table", "tr", "figure", "figcaption", "hr", "br", "section", "article",
+];
+
+/// Diffs two versions of an HTML field.
+fn diff_field(old: &str, new: &str, options: DiffOptions) -> FieldDiff {
+    let (old, new) = match options.format {
+        DiffFormat::Text => (html_to_text(old), html_to_text(new)),
+        DiffFormat::Html => (old.to_owned(), new.to_owned()),
+    };
+    diff_tokens(&tokenize(&old, options), &tokenize(&new, options))
+}
+
+/// Diffs two versions of a plain-text field, which never needs stripping.
+fn diff_plain(old: &str, new: &str, options: DiffOptions) -> FieldDiff {
+    let options = DiffOptions { format: DiffFormat::Text, ..options };
+    diff_tokens(&tokenize(old, options), &tokenize(new, options))
+}
+
+fn diff_tokens(old_tokens: &[&str], new_tokens: &[&str]) -> FieldDiff {
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Patience, old_tokens, new_tokens) {
+        for change in op.iter_changes(old_tokens, new_tokens) {
+            let op = match change.tag() {
+                ChangeTag::Equal => DiffOp::Equal,
+                ChangeTag::Insert => DiffOp::Insert,
+                ChangeTag::Delete => DiffOp::Delete,
+            };
+            match segments.last_mut() {
+                Some(last) if last.op == op => last.text.push_str(change.value()),
+                _ => segments.push(DiffSegment { op, text: change.value().to_owned() }),
+            }
+        }
+    }
+
+    FieldDiff {
+        changed: segments.iter().any(|s| s.op != DiffOp::Equal),
+        segments,
+    }
+}
+
+/// Splits `input` into the units to compare. Concatenating the tokens always
+/// reproduces the input exactly, so segments can be rendered back verbatim.
+fn tokenize(input: &str, options: DiffOptions) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+
+    match (options.format, options.granularity) {
+        // Lines of the stripped text.
+        (DiffFormat::Text, DiffGranularity::Block) => {
+            for (i, c) in input.char_indices() {
+                if c == '\n' {
+                    tokens.push(&input[start..=i]);
+                    start = i + 1;
+                }
+            }
+        }
+        // Runs of whitespace and of non-whitespace.
+        (DiffFormat::Text, DiffGranularity::Word) => {
+            let mut prev_ws = None;
+            for (i, c) in input.char_indices() {
+                let ws = c.is_whitespace();
+                if prev_ws.is_some_and(|p| p != ws) {
+                    tokens.push(&input[start..i]);
+                    start = i;
+                }
+                prev_ws = Some(ws);
+            }
+        }
+        // Tags as single tokens, text between them split into words.
+        (DiffFormat::Html, DiffGranularity::Word) => {
+            let mut in_tag = false;
+            let mut prev_ws = None;
+            for (i, c) in input.char_indices() {
+                if in_tag {
+                    if c == '>' {
+                        tokens.push(&input[start..=i]);
+                        start = i + 1;
+                        in_tag = false;
+                        prev_ws = None;
+                    }
+                    continue;
+                }
+                if c == '<' {
+                    if start < i {
+                        tokens.push(&input[start..i]);
+                    }
+                    start = i;
+                    in_tag = true;
+                    continue;
+                }
+                let ws = c.is_whitespace();
+                if prev_ws.is_some_and(|p| p != ws) {
+                    tokens.push(&input[start..i]);
+                    start = i;
+                }
+                prev_ws = Some(ws);
+            }
+        }
+        // Everything up to and including the end of each block element.
+        (DiffFormat::Html, DiffGranularity::Block) => {
+            let mut tag_start = None;
+            for (i, c) in input.char_indices() {
+                match c {
+                    '<' => tag_start = Some(i),
+                    '>' => {
+                        if let Some(from) = tag_start.take() {
+                            if ends_block(&input[from..=i]) {
+                                tokens.push(&input[start..=i]);
+                                start = i + 1;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if start < input.len() {
+        tokens.push(&input[start..]);
+    }
+    tokens
+}
+
+/// True for closing block tags (`</p>`) and void block tags (`<br>`, `<hr/>`).
+fn ends_block(tag: &str) -> bool {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/');
+    let (closing, name) = match inner.strip_prefix('/') {
+        Some(name) => (true, name),
+        None => (false, inner),
+    };
+    let name = name.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+    BLOCK_TAGS.contains(&name.as_str()) && (closing || name == "br" || name == "hr")
+}
+
+/// Reduces HTML to its visible text: tags are removed, block boundaries
+/// become line breaks, common entities are decoded and whitespace inside each
+/// line is collapsed.
+fn html_to_text(html: &str) -> String {
+    let mut raw = String::with_capacity(html.len());
+    let mut tag: Option<usize> = None;
+    for (i, c) in html.char_indices() {
+        match (tag, c) {
+            (None, '<') => tag = Some(i),
+            (Some(from), '>') => {
+                if ends_block(&html[from..=i]) {
+                    raw.push('\n');
+                }
+                tag = None;
+            }
+            (None, _) => raw.push(c),
+            (Some(_), _) => {}
+        }
+    }
+
+    let decoded = raw
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    let mut text = String::with_capacity(decoded.len());
+    for line in decoded.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(format: DiffFormat, granularity: DiffGranularity) -> DiffOptions {
+        DiffOptions { format, granularity }
+    }
+
+    #[test]
+    fn tokenize_splits_words_and_whitespace() {
+        let text_words = options(DiffFormat::Text, DiffGranularity::Word);
+        let input = "Hello,  big\nworld ";
+        let tokens = tokenize(input, text_words);
+        assert_eq!(tokens, ["Hello,", "  ", "big", "\n", "world", " "]);
+        assert_eq!(tokens.concat(), input);
+
+        let text_blocks = options(DiffFormat::Text, DiffGranularity::Block);
+        assert_eq!(tokenize("one two\nthree\nfour", text_blocks), ["one two\n", "three\n", "four"]);
+
+        let html_words = options(DiffFormat::Html, DiffGranularity::Word);
+        let input = "<p class=\"x\">Hi there</p><br>";
+        let tokens = tokenize(input, html_words);
+        assert_eq!(tokens, ["<p class=\"x\">", "Hi", " ", "there", "</p>", "<br>"]);
+        assert_eq!(tokens.concat(), input);
+
+        let html_blocks = options(DiffFormat::Html, DiffGranularity::Block);
+        assert_eq!(
+            tokenize("<p>a <b>b</b></p><p>c</p>tail", html_blocks),
+            ["<p>a <b>b</b></p>", "<p>c</p>", "tail"]
+        );
+    }
+
+    #[test]
+    fn html_to_text_decodes_entities() {
+        assert_eq!(
+            html_to_text("Fish &amp; chips&nbsp;&lt;3 &quot;yum&quot; it&#39;s"),
+            "Fish & chips <3 \"yum\" it's\n"
+        );
+        // `&amp;` is decoded last, so escaped entities stay literal.
+        assert_eq!(html_to_text("&amp;lt;"), "&lt;\n");
+    }
+
+    #[test]
+    fn html_to_text_breaks_lines_at_blocks() {
+        let html = "<h2>Title</h2><p>First   <em>para</em> graph</p><ul><li>a</li><li>b</li></ul>\
+                    line<br/>break<span>inline</span>";
+        assert_eq!(html_to_text(html), "Title\nFirst para graph\na\nb\nline\nbreakinline\n");
+    }
+
+    #[test]
+    fn diff_reports_word_changes() {
+        let options = DiffOptions::default();
+        let diff = diff_field("<p>the quick fox</p>", "<p>the slow fox</p>", options);
+        assert!(diff.changed);
+        let ops: Vec<_> = diff.segments.iter().map(|s| (s.op, s.text.as_str())).collect();
+        assert_eq!(
+            ops,
+            [
+                (DiffOp::Equal, "the "),
+                (DiffOp::Delete, "quick"),
+                (DiffOp::Insert, "slow"),
+                (DiffOp::Equal, " fox\n"),
+            ]
+        );
+        assert!(!diff_field("<p>same</p>", "<div>same</div>", options).changed);
+    }
+}
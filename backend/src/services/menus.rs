@@ -5,11 +5,14 @@
 //! delete-then-insert is simpler and correct for the small cardinalities
 //! typical of navigation menus.
 
+use chrono::{DateTime, Utc};
+use serde_json::json;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::db::models::{Menu, MenuItem, MenuItemInput};
 use crate::error::{AppError, AppResult};
+use crate::services::concurrency;
 
 // ─── Public service functions ─────────────────────────────────────────────────
 
@@ -17,7 +20,7 @@ use crate::error::{AppError, AppResult};
 ///
 /// Returns `NotFound` when no menu with the given name exists.
 pub async fn get_menu(pool: &SqlitePool, name: &str) -> AppResult<(Menu, Vec<MenuItem>)> {
-    let menu = sqlx::query_as::<_, Menu>("SELECT id, name FROM menus WHERE name = ?")
+    let menu = sqlx::query_as::<_, Menu>("SELECT id, name, updated_at FROM menus WHERE name = ?")
         .bind(name)
         .fetch_optional(pool)
         .await?
@@ -42,25 +45,43 @@ pub async fn get_menu(pool: &SqlitePool, name: &str) -> AppResult<(Menu, Vec<Men
 /// can bootstrap menus by writing to them without a separate creation step.
 /// Existing item IDs from `MenuItemInput.id` are preserved so the frontend
 /// can correlate items across saves; new items receive fresh UUIDs.
+///
+/// When `expected_updated_at` is given and the menu has changed since, nothing
+/// is written and the current menu is returned in the conflict error. The
+/// check and the writes share one transaction, so concurrent saves cannot
+/// both pass it. Returns the menu's new version.
 pub async fn update_menu(
     pool: &SqlitePool,
     name: &str,
     items: Vec<MenuItemInput>,
+    expected_updated_at: Option<DateTime<Utc>>,
     user_id: &str,
-) -> AppResult<()> {
+) -> AppResult<DateTime<Utc>> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
     // Retrieve or create the menu row.
-    let menu_id = match sqlx::query_scalar::<_, String>("SELECT id FROM menus WHERE name = ?")
+    let existing = sqlx::query_as::<_, Menu>("SELECT id, name, updated_at FROM menus WHERE name = ?")
         .bind(name)
-        .fetch_optional(pool)
-        .await?
-    {
-        Some(id) => id,
+        .fetch_optional(&mut *tx)
+        .await?;
+    let menu_id = match existing {
+        Some(menu) => {
+            if concurrency::is_stale(expected_updated_at, menu.updated_at) {
+                let (menu, items) = get_menu(pool, name).await?;
+                let version = menu.updated_at;
+                return Err(concurrency::conflict(
+                    &json!({ "menu": menu, "items": items }),
+                    version,
+                ));
+            }
+            menu.id
+        }
         None => {
             let new_id = Uuid::new_v4().to_string();
             sqlx::query("INSERT INTO menus (id, name) VALUES (?, ?)")
                 .bind(&new_id)
                 .bind(name)
-                .execute(pool)
+                .execute(&mut *tx)
                 .await?;
             new_id
         }
@@ -69,7 +90,7 @@ pub async fn update_menu(
     // Delete all existing items for this menu before inserting the new set.
     sqlx::query("DELETE FROM menu_items WHERE menu_id = ?")
         .bind(&menu_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
     for item in items {
@@ -85,19 +106,29 @@ pub async fn update_menu(
         .bind(&item.link_target)
         .bind(&item.parent_id)
         .bind(item.sort_order)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
     }
 
+    let version = sqlx::query_scalar::<_, DateTime<Utc>>(
+        "UPDATE menus SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = ? \
+         RETURNING updated_at",
+    )
+    .bind(&menu_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
     crate::services::audit::log_action(
         pool,
         user_id,
         "update",
         "menu",
         &menu_id,
-        &serde_json::json!({ "name": name }),
+        &json!({ "name": name }),
     )
     .await?;
 
-    Ok(())
+    Ok(version)
 }
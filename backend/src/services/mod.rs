@@ -1,9 +1,27 @@
+pub mod api_tokens;
 pub mod apps;
 pub mod articles;
 pub mod audit;
+pub mod authors;
 pub mod categories;
+pub mod concurrency;
+pub mod diff;
 pub mod media;
 pub mod menus;
+pub mod ownership;
 pub mod pages;
+pub mod redirects;
+pub mod registration;
+pub mod render;
+pub mod review;
+pub mod revisions;
+pub mod role_mapping;
+pub mod roles;
+pub mod sanitize;
+pub mod schedule;
 pub mod search;
+pub mod series;
 pub mod settings;
+pub mod slugs;
+pub mod tags;
+pub mod visibility;
@@ -0,0 +1,247 @@
+//! Who may change a page or article.
+//!
+//! Roles with `content.edit_any` may change any content. Roles with
+//! `content.edit_own` may only change content the user wrote — a page's
+//! author, or an article's creator or one of its credited authors — and
+//! content they were added to as a collaborator. Publishing,
+//! approving and rejecting follow `content.publish_any` and
+//! `content.publish_own` the same way, except that collaborators do not
+//! count as authors. Every refusal is recorded in the audit log as
+//! `permission_denied`, with the attempted action in the details.
+
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::auth::permissions::{self, CONTENT_EDIT_ANY, CONTENT_EDIT_OWN};
+use crate::db::models::{Collaborator, User};
+use crate::error::{AppError, AppResult};
+use crate::services::{audit, review};
+
+/// Collaborator table and owner column for an entity type.
+fn join_table(entity_type: &str) -> (&'static str, &'static str) {
+    match entity_type {
+        "page" => ("page_collaborators", "page_id"),
+        _ => ("article_collaborators", "article_id"),
+    }
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns true when `user` wrote the page or article: its author for pages;
+/// its creator or a credited author for articles.
+pub async fn is_author(
+    pool: &SqlitePool,
+    user: &User,
+    entity_type: &str,
+    entity_id: &str,
+) -> AppResult<bool> {
+    let sql = match entity_type {
+        "page" => "SELECT 1 FROM pages WHERE id = ? AND author_id = ?",
+        _ => {
+            "SELECT 1 FROM articles WHERE id = ?1 AND author_id = ?2 \
+             UNION ALL \
+             SELECT 1 FROM article_authors WHERE article_id = ?1 AND user_id = ?2"
+        }
+    };
+    let found = sqlx::query_scalar::<_, i64>(sql)
+        .bind(entity_id)
+        .bind(&user.id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(found.is_some())
+}
+
+/// Returns true when `user` may change the page or article.
+pub async fn can_modify(
+    pool: &SqlitePool,
+    user: &User,
+    entity_type: &str,
+    entity_id: &str,
+) -> AppResult<bool> {
+    if permissions::has(user, CONTENT_EDIT_ANY) {
+        return Ok(true);
+    }
+    if !permissions::has(user, CONTENT_EDIT_OWN) {
+        return Ok(false);
+    }
+    if is_author(pool, user, entity_type, entity_id).await? {
+        return Ok(true);
+    }
+    let (table, owner) = join_table(entity_type);
+    let granted = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT user_id FROM {table} WHERE {owner} = ? AND user_id = ?"
+    ))
+    .bind(entity_id)
+    .bind(&user.id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(granted.is_some())
+}
+
+/// Returns `Forbidden` unless `user` may change the page or article, logging
+/// the refused `action`.
+pub async fn ensure_can_modify(
+    pool: &SqlitePool,
+    user: &User,
+    entity_type: &str,
+    entity_id: &str,
+    action: &str,
+) -> AppResult<()> {
+    if can_modify(pool, user, entity_type, entity_id).await? {
+        return Ok(());
+    }
+    deny(pool, user, entity_type, entity_id, action).await
+}
+
+/// Returns `Forbidden` unless `user` may publish, approve or reject the page
+/// or article, logging the refused `action`.
+pub async fn ensure_can_publish(
+    pool: &SqlitePool,
+    user: &User,
+    entity_type: &str,
+    entity_id: &str,
+    action: &str,
+) -> AppResult<()> {
+    let own = is_author(pool, user, entity_type, entity_id).await?;
+    if review::can_publish(user, own) {
+        return Ok(());
+    }
+    deny(pool, user, entity_type, entity_id, action).await
+}
+
+/// Returns the collaborators of a page or article, ordered by name.
+pub async fn list_collaborators(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+) -> AppResult<Vec<Collaborator>> {
+    let (table, owner) = join_table(entity_type);
+    let collaborators = sqlx::query_as::<_, Collaborator>(&format!(
+        "SELECT c.user_id, u.display_name, c.granted_by, c.created_at \
+         FROM {table} c JOIN users u ON u.id = c.user_id \
+         WHERE c.{owner} = ? \
+         ORDER BY u.display_name COLLATE NOCASE ASC"
+    ))
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(collaborators)
+}
+
+/// Gives `user_id` edit access to a page or article. Only its authors and
+/// users who may edit any content may do this; adding an existing
+/// collaborator again is a no-op.
+pub async fn add_collaborator(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    user_id: &str,
+    acting_user: &User,
+) -> AppResult<Vec<Collaborator>> {
+    ensure_can_share(pool, acting_user, entity_type, entity_id, "add_collaborator").await?;
+
+    let exists = sqlx::query_scalar::<_, String>("SELECT id FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::BadRequest(format!("User '{}' does not exist", user_id)));
+    }
+
+    let (table, owner) = join_table(entity_type);
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {table} ({owner}, user_id, granted_by) VALUES (?, ?, ?)"
+    ))
+    .bind(entity_id)
+    .bind(user_id)
+    .bind(&acting_user.id)
+    .execute(pool)
+    .await?;
+
+    audit::log_action(
+        pool,
+        &acting_user.id,
+        "add_collaborator",
+        entity_type,
+        entity_id,
+        &json!({ "user_id": user_id }),
+    )
+    .await?;
+
+    list_collaborators(pool, entity_type, entity_id).await
+}
+
+/// Takes edit access to a page or article away from `user_id`. Only its
+/// authors and users who may edit any content may do this. Returns
+/// `NotFound` if the user was not a collaborator.
+pub async fn remove_collaborator(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    user_id: &str,
+    acting_user: &User,
+) -> AppResult<Vec<Collaborator>> {
+    ensure_can_share(pool, acting_user, entity_type, entity_id, "remove_collaborator").await?;
+
+    let (table, owner) = join_table(entity_type);
+    let result = sqlx::query(&format!("DELETE FROM {table} WHERE {owner} = ? AND user_id = ?"))
+        .bind(entity_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    audit::log_action(
+        pool,
+        &acting_user.id,
+        "remove_collaborator",
+        entity_type,
+        entity_id,
+        &json!({ "user_id": user_id }),
+    )
+    .await?;
+
+    list_collaborators(pool, entity_type, entity_id).await
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// Collaborators may edit but not pass access on; that is left to the
+/// authors and users who may edit any content.
+async fn ensure_can_share(
+    pool: &SqlitePool,
+    user: &User,
+    entity_type: &str,
+    entity_id: &str,
+    action: &str,
+) -> AppResult<()> {
+    if permissions::has(user, CONTENT_EDIT_ANY)
+        || (permissions::has(user, CONTENT_EDIT_OWN)
+            && is_author(pool, user, entity_type, entity_id).await?)
+    {
+        return Ok(());
+    }
+    deny(pool, user, entity_type, entity_id, action).await
+}
+
+async fn deny(
+    pool: &SqlitePool,
+    user: &User,
+    entity_type: &str,
+    entity_id: &str,
+    action: &str,
+) -> AppResult<()> {
+    audit::log_action(
+        pool,
+        &user.id,
+        "permission_denied",
+        entity_type,
+        entity_id,
+        &json!({ "action": action }),
+    )
+    .await?;
+    Err(AppError::Forbidden)
+}
@@ -4,15 +4,29 @@
 //! call these functions and never touch the database directly — a deliberate
 //! boundary that keeps handlers thin and business logic testable.
 
+use std::collections::HashMap;
+
 use serde_json::json;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
-use crate::db::models::{CreatePage, Page, PageRevision, PaginatedResponse,
-    PaginationParams, UpdatePage};
+use crate::db::models::{Breadcrumb, CreatePage, Page, PageDraft, PageRevision, PageTree,
+    PageTreeNode, PageWithDraft, PaginatedResponse, PaginationParams, PublicPage, UpdatePage, User};
 use crate::error::{AppError, AppResult};
 use crate::helpers::slugify;
-use crate::services::audit;
+use crate::services::sanitize::SanitizePolicy;
+use crate::services::visibility::{self, PUBLIC_FILTER};
+use crate::services::diff::{self, DiffOptions, RevisionDiff, Snapshot};
+use crate::services::{
+    audit, categories, concurrency, ownership, render, review, revisions, sanitize, schedule, slugs, tags,
+};
+
+// ─── Column list shared by all SELECT queries ─────────────────────────────────
+
+pub(crate) const PAGE_COLS: &str =
+    "id, title, slug, content, content_format, content_html, status, publish_at, unpublish_at, \
+     author_id, created_at, updated_at, trashed_at, template, parent_id, sort_order, path, \
+     review_comment";
 
 // ─── Public service functions ─────────────────────────────────────────────────
 
@@ -32,15 +46,17 @@ pub async fn list_pages(
     // Two almost-identical queries depending on whether a status is requested.
     // Keeping them as separate query! calls (rather than building SQL strings)
     // means sqlx can type-check them at compile time.
+    if let Some(status) = status_filter {
+        validate_status_filter(status)?;
+    }
+
     let (rows, total) = if let Some(status) = status_filter {
-        let rows = sqlx::query_as::<_, Page>(
-            "SELECT id, title, slug, content, status, publish_at, author_id, \
-                    created_at, updated_at, trashed_at, template \
-             FROM pages \
+        let rows = sqlx::query_as::<_, Page>(&format!(
+            "SELECT {PAGE_COLS} FROM pages \
              WHERE status = ? \
              ORDER BY updated_at DESC \
-             LIMIT ? OFFSET ?",
-        )
+             LIMIT ? OFFSET ?"
+        ))
         .bind(status)
         .bind(per_page)
         .bind(offset)
@@ -54,14 +70,12 @@ pub async fn list_pages(
 
         (rows, total)
     } else {
-        let rows = sqlx::query_as::<_, Page>(
-            "SELECT id, title, slug, content, status, publish_at, author_id, \
-                    created_at, updated_at, trashed_at, template \
-             FROM pages \
+        let rows = sqlx::query_as::<_, Page>(&format!(
+            "SELECT {PAGE_COLS} FROM pages \
              WHERE status != 'trashed' \
              ORDER BY updated_at DESC \
-             LIMIT ? OFFSET ?",
-        )
+             LIMIT ? OFFSET ?"
+        ))
         .bind(per_page)
         .bind(offset)
         .fetch_all(pool)
@@ -83,33 +97,185 @@ pub async fn list_pages(
     })
 }
 
+/// Returns a paginated list of published pages in the category with
+/// `category_id` or any of its descendants, ordered by title.
+pub async fn list_published_pages_in_category(
+    pool: &SqlitePool,
+    category_id: &str,
+    params: &PaginationParams,
+) -> AppResult<PaginatedResponse<Page>> {
+    let per_page = params.per_page() as i64;
+    let offset = params.offset() as i64;
+    let subtree = categories::subtree_ids("SELECT ?");
+
+    let rows = sqlx::query_as::<_, Page>(&format!(
+        "SELECT {PAGE_COLS} FROM pages \
+         WHERE id IN (SELECT page_id FROM page_categories WHERE category_id IN ({subtree})) \
+           AND {PUBLIC_FILTER} \
+         ORDER BY title COLLATE NOCASE ASC \
+         LIMIT ? OFFSET ?"
+    ))
+    .bind(category_id)
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    let total = sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM pages \
+         WHERE id IN (SELECT page_id FROM page_categories WHERE category_id IN ({subtree})) \
+           AND {PUBLIC_FILTER}"
+    ))
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(PaginatedResponse {
+        data: rows,
+        total,
+        page: params.page.unwrap_or(1).max(1),
+        per_page: params.per_page(),
+    })
+}
+
 /// Fetches a single page by primary key. Returns `NotFound` if absent.
 pub async fn get_page(pool: &SqlitePool, id: &str) -> AppResult<Page> {
-    sqlx::query_as::<_, Page>(
-        "SELECT id, title, slug, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, template \
-         FROM pages WHERE id = ?",
-    )
+    sqlx::query_as::<_, Page>(&format!("SELECT {PAGE_COLS} FROM pages WHERE id = ?"))
     .bind(id)
     .fetch_optional(pool)
     .await?
     .ok_or(AppError::NotFound)
 }
 
-/// Fetches a published page by its slug. Used by the public API — only
-/// `published` pages are visible without authentication.
-pub async fn get_page_by_slug(pool: &SqlitePool, slug: &str) -> AppResult<Page> {
-    sqlx::query_as::<_, Page>(
-        "SELECT id, title, slug, content, status, publish_at, author_id, \
-                created_at, updated_at, trashed_at, template \
-         FROM pages WHERE slug = ? AND status = 'published'",
-    )
-    .bind(slug)
+/// Fetches a published page by its full hierarchical path, e.g.
+/// `guides/install/linux`. Leading and trailing slashes are ignored, so a
+/// top-level page resolves by its bare slug exactly as before.
+pub async fn get_page_by_path(pool: &SqlitePool, path: &str) -> AppResult<Page> {
+    sqlx::query_as::<_, Page>(&format!(
+        "SELECT {PAGE_COLS} FROM pages WHERE path = ? AND {PUBLIC_FILTER}"
+    ))
+    .bind(path.trim_matches('/'))
     .fetch_optional(pool)
     .await?
     .ok_or(AppError::NotFound)
 }
 
+/// Public path resolver: like `get_page_by_path`, but when the path no longer
+/// matches returns `MovedPermanently` pointing at the page's current API URL.
+///
+/// The last path segment is treated as a slug. Because slugs are globally
+/// unique, a page that was moved elsewhere in the tree is still found by its
+/// slug; failing that, the slug history is consulted for renamed pages.
+pub async fn resolve_page_path(pool: &SqlitePool, path: &str) -> AppResult<Page> {
+    match get_page_by_path(pool, path).await {
+        Err(AppError::NotFound) => {}
+        other => return other,
+    }
+
+    let path = path.trim_matches('/');
+    let slug = path.rsplit('/').next().unwrap_or_default();
+
+    let mut current = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT path FROM pages WHERE slug = ? AND {PUBLIC_FILTER}"
+    ))
+    .bind(slug)
+    .fetch_optional(pool)
+    .await?;
+
+    if current.is_none() {
+        if let Some(owner) = slugs::historic_owner(pool, "page", slug).await? {
+            current = sqlx::query_scalar::<_, String>(&format!(
+                "SELECT path FROM pages WHERE id = ? AND {PUBLIC_FILTER}"
+            ))
+            .bind(&owner)
+            .fetch_optional(pool)
+            .await?;
+        }
+    }
+
+    match current {
+        Some(current) if current != path => {
+            Err(AppError::MovedPermanently(format!("/api/pages/{current}")))
+        }
+        _ => Err(AppError::NotFound),
+    }
+}
+
+
+/// Resolves a public path like `resolve_page_path` and returns the page
+/// together with its categories and tags.
+pub async fn get_public_page(pool: &SqlitePool, path: &str) -> AppResult<PublicPage> {
+    let page = resolve_page_path(pool, path).await?;
+    let categories = categories::categories_for(pool, "page", &page.id).await?;
+    let tags = tags::tags_for(pool, "page", &page.id).await?;
+    Ok(PublicPage {
+        page,
+        categories,
+        tags,
+    })
+}
+
+/// Builds the tree of published pages and, when `path` is given, the
+/// breadcrumb trail from the root down to that page.
+///
+/// Published pages whose parent is not published are left out together with
+/// their whole subtree — a visitor could not reach them through the tree
+/// anyway, and showing them as top-level would misrepresent the structure.
+pub async fn get_page_tree(pool: &SqlitePool, path: Option<&str>) -> AppResult<PageTree> {
+    let pages = sqlx::query_as::<_, Page>(&format!(
+        "SELECT {PAGE_COLS} FROM pages \
+         WHERE {PUBLIC_FILTER} \
+         ORDER BY sort_order ASC, title ASC"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    let mut children: HashMap<Option<String>, Vec<&Page>> = HashMap::new();
+    for page in &pages {
+        children.entry(page.parent_id.clone()).or_default().push(page);
+    }
+
+    fn build(parent: Option<String>, children: &HashMap<Option<String>, Vec<&Page>>) -> Vec<PageTreeNode> {
+        children
+            .get(&parent)
+            .map(|siblings| {
+                siblings
+                    .iter()
+                    .map(|p| PageTreeNode {
+                        id: p.id.clone(),
+                        title: p.title.clone(),
+                        slug: p.slug.clone(),
+                        path: p.path.clone(),
+                        sort_order: p.sort_order,
+                        children: build(Some(p.id.clone()), children),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    let tree = build(None, &children);
+
+    let breadcrumbs = match path {
+        Some(path) => {
+            let page = get_page_by_path(pool, path).await?;
+            // Unpublished ancestors are skipped so draft titles never leak.
+            let mut trail: Vec<Breadcrumb> = ancestors(pool, &page.id)
+                .await?
+                .into_iter()
+                .rev()
+                .filter(|p| visibility::is_public(&p.status, p.unpublish_at))
+                .map(|p| Breadcrumb { id: p.id, title: p.title, path: p.path })
+                .collect();
+            trail.push(Breadcrumb { id: page.id, title: page.title, path: page.path });
+            trail
+        }
+        None => Vec::new(),
+    };
+
+    Ok(PageTree { tree, breadcrumbs })
+}
+
 /// Creates a new page, including an initial revision and optional category
 /// assignments.
 ///
@@ -118,8 +284,10 @@ pub async fn get_page_by_slug(pool: &SqlitePool, slug: &str) -> AppResult<Page>
 pub async fn create_page(
     pool: &SqlitePool,
     input: CreatePage,
-    author_id: &str,
+    author: &User,
 ) -> AppResult<Page> {
+    let author_id = author.id.as_str();
+
     // Derive or validate the slug.
     let slug = input
         .slug
@@ -127,36 +295,90 @@ pub async fn create_page(
         .map(|s| s.to_owned())
         .unwrap_or_else(|| slugify(&input.title));
 
-    ensure_slug_unique(pool, &slug, None).await?;
+    ensure_slug_unique(pool, &slug, None, input.reclaim_slug).await?;
 
     let id = Uuid::new_v4().to_string();
     let content = input.content.unwrap_or_default();
+    let content_format = input
+        .content_format
+        .unwrap_or_else(|| render::DEFAULT_FORMAT.to_owned());
+    render::validate_format(&content_format)?;
+    if let Some(ref names) = input.tags {
+        tags::validate_names(names)?;
+    }
+    let policy = sanitize::policy(pool).await?;
+    let content = render::sanitize_source(&content, &content_format, &policy);
     let status = input.status.unwrap_or_else(|| "draft".to_owned());
     validate_status(&status)?;
+    review::ensure_can_set_status(author, true, None, &status)?;
+    let tz = schedule::site_timezone(pool).await?;
+    let publish_at = input.publish_at.map(|at| schedule::resolve(at, tz)).transpose()?;
+    let unpublish_at = input.unpublish_at.map(|at| schedule::resolve(at, tz)).transpose()?;
+    schedule::ensure_publish_time(&status, publish_at)?;
+    visibility::validate_window(publish_at, unpublish_at)?;
+    visibility::ensure_not_expired(&status, unpublish_at)?;
     let template = input.template.unwrap_or_else(|| "default".to_owned());
 
+    // An empty parent_id means "top level", same as omitting it.
+    let parent = match input.parent_id.as_deref().filter(|p| !p.is_empty()) {
+        Some(parent_id) => Some(get_parent(pool, parent_id).await?),
+        None => None,
+    };
+    let parent_id = parent.as_ref().map(|p| p.id.clone());
+    let path = match &parent {
+        Some(p) => format!("{}/{}", p.path, slug),
+        None => slug.clone(),
+    };
+
+    // New pages go to the end of their sibling list unless placed explicitly.
+    let sort_order = match input.sort_order {
+        Some(order) => order,
+        None => next_sort_order(pool, parent_id.as_deref()).await?,
+    };
+
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
     sqlx::query(
-        "INSERT INTO pages (id, title, slug, content, status, publish_at, author_id, template) \
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        "INSERT INTO pages \
+             (id, title, slug, content, content_format, content_html, status, publish_at, \
+              unpublish_at, author_id, template, parent_id, sort_order, path) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(&id)
     .bind(&input.title)
     .bind(&slug)
     .bind(&content)
+    .bind(&content_format)
+    .bind(render::render(&content, &content_format, &policy))
     .bind(&status)
-    .bind(&input.publish_at)
+    .bind(publish_at.map(schedule::db_timestamp))
+    .bind(unpublish_at.map(schedule::db_timestamp))
     .bind(author_id)
     .bind(&template)
-    .execute(pool)
+    .bind(&parent_id)
+    .bind(sort_order)
+    .bind(&path)
+    .execute(&mut *tx)
     .await?;
 
+    // A reclaimed slug stops redirecting to its previous owner.
+    slugs::release(&mut *tx, "page", &slug).await?;
+
     // Attach categories when provided.
     if let Some(ref cat_ids) = input.category_ids {
-        set_page_categories(pool, &id, cat_ids).await?;
+        set_page_categories(&mut tx, &id, cat_ids).await?;
+    }
+    if let Some(ref names) = input.tags {
+        tags::set_tags(&mut tx, "page", &id, names).await?;
     }
 
     // Record the initial revision so history starts from creation.
-    create_revision(pool, &id, &input.title, &content, author_id).await?;
+    let body = Body {
+        title: &input.title,
+        content: &content,
+        content_format: &content_format,
+    };
+    create_revision(&mut *tx, &id, &body, author_id, None).await?;
+    tx.commit().await?;
 
     audit::log_action(
         pool,
@@ -164,7 +386,7 @@ pub async fn create_page(
         "create",
         "page",
         &id,
-        &json!({ "title": input.title, "slug": slug, "status": status }),
+        &json!({ "title": input.title, "slug": slug, "status": status, "path": path }),
     )
     .await?;
 
@@ -175,54 +397,191 @@ pub async fn create_page(
 ///
 /// Fields absent from `input` keep their current values. A new revision is
 /// created for every successful update so the full edit history is preserved.
+///
+/// While the page stays published, title and content are saved to its draft
+/// overlay and the live version is left untouched; everything else applies
+/// immediately. The slug, parent, template, categories and tags cannot change
+/// along with such edits, or while a draft is pending. Once the page is no
+/// longer published, a pending draft is folded back into the row.
+///
+/// When `expected_updated_at` no longer matches, nothing is written and the
+/// current version (with its draft) is returned in the conflict error.
 pub async fn update_page(
     pool: &SqlitePool,
     id: &str,
     input: UpdatePage,
-    user_id: &str,
+    user: &User,
 ) -> AppResult<Page> {
+    let user_id = user.id.as_str();
     let existing = get_page(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "page", id, "update").await?;
+    let draft = get_draft(pool, id).await?;
 
-    // Merge supplied values with existing ones.
-    let title = input.title.unwrap_or_else(|| existing.title.clone());
-    let content = input.content.unwrap_or_else(|| existing.content.clone());
+    if concurrency::is_stale(input.expected_updated_at, existing.updated_at) {
+        let version = existing.updated_at;
+        let current = get_page_with_draft(pool, id).await?;
+        return Err(concurrency::conflict(&current, version));
+    }
+
+    // Merge supplied values with the working copy: the draft when there is
+    // one, otherwise the row itself.
+    let (base_title, base_content, base_content_format) = match &draft {
+        Some(d) => (&d.title, &d.content, &d.content_format),
+        None => (&existing.title, &existing.content, &existing.content_format),
+    };
+    let title = input.title.unwrap_or_else(|| base_title.clone());
+    let content = input.content.unwrap_or_else(|| base_content.clone());
+    let content_format = input
+        .content_format
+        .unwrap_or_else(|| base_content_format.clone());
+    render::validate_format(&content_format)?;
+    if let Some(ref names) = input.tags {
+        tags::validate_names(names)?;
+    }
+    let policy = sanitize::policy(pool).await?;
+    let content = render::sanitize_source(&content, &content_format, &policy);
     let status = input.status.unwrap_or_else(|| existing.status.clone());
     validate_status(&status)?;
-    let publish_at = if input.publish_at.is_some() {
-        input.publish_at
-    } else {
-        existing.publish_at
+    let own = ownership::is_author(pool, user, "page", id).await?;
+    review::ensure_can_set_status(user, own, Some(&existing.status), &status)?;
+    let message = revisions::normalize_message(input.message)?;
+    let tz = schedule::site_timezone(pool).await?;
+    let publish_at = match input.publish_at {
+        Some(at) => Some(schedule::resolve(at, tz)?),
+        None => existing.publish_at,
     };
+    let unpublish_at = match input.unpublish_at {
+        Some(at) => at.map(|at| schedule::resolve(at, tz)).transpose()?,
+        None => existing.unpublish_at,
+    };
+    let window_changed =
+        publish_at != existing.publish_at || unpublish_at != existing.unpublish_at;
+    review::ensure_can_reschedule(user, own, &existing.status, window_changed)?;
+    schedule::ensure_publish_time(&status, publish_at)?;
+    visibility::validate_window(publish_at, unpublish_at)?;
+    visibility::ensure_not_expired(&status, unpublish_at)?;
     let template = input.template.unwrap_or_else(|| existing.template.clone());
+    let sort_order = input.sort_order.unwrap_or(existing.sort_order);
+
+    // Live content keeps serving its published version; edits go to the draft.
+    let overlay = existing.status == "published" && status == "published";
+    let (row_title, row_content, row_content_format, row_content_html) = if overlay {
+        (
+            &existing.title,
+            &existing.content,
+            &existing.content_format,
+            existing.content_html.clone(),
+        )
+    } else {
+        (&title, &content, &content_format, render::render(&content, &content_format, &policy))
+    };
+
+    // `Some("")` detaches the page to the top level; `None` leaves it in place.
+    let parent_id = match input.parent_id {
+        Some(p) if p.is_empty() => None,
+        Some(p) => Some(p),
+        None => existing.parent_id.clone(),
+    };
+    if parent_id != existing.parent_id {
+        if let Some(ref new_parent) = parent_id {
+            validate_parent(pool, id, new_parent).await?;
+        }
+    }
 
     // Compute the new slug and check uniqueness only when it changed.
     let slug = input.slug.unwrap_or_else(|| existing.slug.clone());
     if slug != existing.slug {
-        ensure_slug_unique(pool, &slug, Some(id)).await?;
+        ensure_slug_unique(pool, &slug, Some(id), input.reclaim_slug).await?;
     }
 
-    sqlx::query(
+    let body = Body {
+        title: &title,
+        content: &content,
+        content_format: &content_format,
+    };
+    let unchanged = title == existing.title
+        && content == existing.content
+        && content_format == existing.content_format;
+    let to_draft = overlay && !unchanged;
+    if to_draft {
+        let placement = Placement {
+            slug: &slug,
+            parent_id: parent_id.as_deref(),
+            template: &template,
+        };
+        let assignments = Assignments {
+            category_ids: input.category_ids.as_deref(),
+            tags: input.tags.as_deref(),
+        };
+        ensure_live_fields_kept(pool, &existing, &placement, &assignments).await?;
+    }
+
+    // The version check, the row, its draft and its assignments are written
+    // together or not at all.
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let written = sqlx::query(
         "UPDATE pages \
-         SET title = ?, slug = ?, content = ?, status = ?, publish_at = ?, template = ?, \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
-         WHERE id = ?",
+         SET title = ?, slug = ?, content = ?, content_format = ?, content_html = ?, \
+             status = ?, publish_at = ?, unpublish_at = ?, \
+             template = ?, parent_id = ?, sort_order = ?, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = ? AND strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) = ?",
     )
-    .bind(&title)
+    .bind(row_title)
     .bind(&slug)
-    .bind(&content)
+    .bind(row_content)
+    .bind(row_content_format)
+    .bind(&row_content_html)
     .bind(&status)
-    .bind(&publish_at)
+    .bind(publish_at.map(schedule::db_timestamp))
+    .bind(unpublish_at.map(schedule::db_timestamp))
     .bind(&template)
+    .bind(&parent_id)
+    .bind(sort_order)
     .bind(id)
-    .execute(pool)
+    .bind(concurrency::db_version(existing.updated_at))
+    .execute(&mut *tx)
     .await?;
+    if written.rows_affected() == 0 {
+        // Somebody else saved since the version check above.
+        let current = get_page_with_draft(pool, id).await?;
+        let version = current.page.updated_at;
+        return Err(concurrency::conflict(&current, version));
+    }
+
+    if to_draft {
+        save_draft(&mut *tx, id, &body, user_id).await?;
+    } else if draft.is_some() {
+        // Either the edits now match the live version, or they went to the
+        // row directly — the draft has nothing left to hold.
+        delete_draft(&mut *tx, id).await?;
+    }
+
+    // A new slug or a new parent changes this page's path and, with it, the
+    // path of every descendant.
+    if slug != existing.slug || parent_id != existing.parent_id {
+        refresh_subtree_paths(&mut *tx, id).await?;
+    }
 
-    // Update category assignments when explicitly provided.
+    if slug != existing.slug {
+        // Keep the old slug redirecting here, and make sure the new one no
+        // longer redirects anywhere else.
+        slugs::release(&mut *tx, "page", &slug).await?;
+        slugs::record(&mut *tx, "page", id, &existing.slug).await?;
+    }
+
+    // Update category and tag assignments when explicitly provided. Neither
+    // is versioned, so they only change when no draft is pending (see
+    // `ensure_live_fields_kept`).
     if let Some(ref cat_ids) = input.category_ids {
-        set_page_categories(pool, id, cat_ids).await?;
+        set_page_categories(&mut tx, id, cat_ids).await?;
+    }
+    if let Some(ref names) = input.tags {
+        tags::set_tags(&mut tx, "page", id, names).await?;
     }
 
-    create_revision(pool, id, &title, &content, user_id).await?;
+    create_revision(&mut *tx, id, &body, user_id, message.as_deref()).await?;
+    tx.commit().await?;
 
     audit::log_action(
         pool,
@@ -230,7 +589,7 @@ pub async fn update_page(
         "update",
         "page",
         id,
-        &json!({ "title": title, "slug": slug, "status": status }),
+        &json!({ "title": title, "slug": slug, "status": status, "draft": overlay }),
     )
     .await?;
 
@@ -238,22 +597,23 @@ pub async fn update_page(
 }
 
 /// Moves a page to the trash. Does not delete the row — it can be restored.
-pub async fn trash_page(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<()> {
+pub async fn trash_page(pool: &SqlitePool, id: &str, user: &User) -> AppResult<()> {
     // Verify the page exists before attempting the update.
     get_page(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "page", id, "trash").await?;
 
     sqlx::query(
         "UPDATE pages \
          SET status = 'trashed', \
              trashed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE id = ?",
     )
     .bind(id)
     .execute(pool)
     .await?;
 
-    audit::log_action(pool, user_id, "trash", "page", id, &json!({})).await?;
+    audit::log_action(pool, &user.id, "trash", "page", id, &json!({})).await?;
 
     Ok(())
 }
@@ -262,8 +622,9 @@ pub async fn trash_page(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult
 ///
 /// Returns `BadRequest` when the page is not currently trashed — restoring a
 /// published page would silently demote it, which is almost certainly a mistake.
-pub async fn restore_page(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<Page> {
+pub async fn restore_page(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Page> {
     let existing = get_page(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "page", id, "restore").await?;
 
     if existing.status != "trashed" {
         return Err(AppError::BadRequest(
@@ -274,34 +635,209 @@ pub async fn restore_page(pool: &SqlitePool, id: &str, user_id: &str) -> AppResu
     sqlx::query(
         "UPDATE pages \
          SET status = 'draft', trashed_at = NULL, \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE id = ?",
     )
     .bind(id)
     .execute(pool)
     .await?;
 
-    audit::log_action(pool, user_id, "restore", "page", id, &json!({})).await?;
+    audit::log_action(pool, &user.id, "restore", "page", id, &json!({})).await?;
 
     get_page(pool, id).await
 }
 
 /// Transitions a page to `published` status.
-pub async fn publish_page(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<Page> {
-    // Verify the page exists.
+pub async fn publish_page(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Page> {
+    let existing = get_page(pool, id).await?;
+    ownership::ensure_can_publish(pool, user, "page", id, "publish").await?;
+    visibility::ensure_not_expired("published", existing.unpublish_at)?;
+    let policy = sanitize::policy(pool).await?;
+
+    // A pending draft becomes the live version.
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let draft = get_draft(&mut *tx, id).await?;
+    if let Some(draft) = &draft {
+        apply_draft(&mut tx, id, draft, &policy).await?;
+    }
+
+    sqlx::query(
+        "UPDATE pages \
+         SET status = 'published', review_comment = NULL, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+         WHERE id = ?",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    audit::log_action(
+        pool,
+        &user.id,
+        "publish",
+        "page",
+        id,
+        &json!({ "draft": draft.is_some() }),
+    )
+    .await?;
+
+    get_page(pool, id).await
+}
+
+/// Returns a page together with its pending draft, if any.
+pub async fn get_page_with_draft(pool: &SqlitePool, id: &str) -> AppResult<PageWithDraft> {
+    let page = get_page(pool, id).await?;
+    let draft = get_draft(pool, id).await?;
+    let categories = categories::categories_for(pool, "page", id).await?;
+    let tags = tags::tags_for(pool, "page", id).await?;
+    Ok(PageWithDraft {
+        page,
+        draft,
+        categories,
+        tags,
+    })
+}
+
+/// Throws away a page's pending draft, leaving the live version as is.
+pub async fn discard_draft(pool: &SqlitePool, id: &str, user: &User) -> AppResult<()> {
     get_page(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "page", id, "discard_draft").await?;
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    if !delete_draft(&mut *tx, id).await? {
+        return Err(AppError::NotFound);
+    }
+
+    // What editors see changed, so the version moves on too.
+    touch(&mut *tx, id).await?;
+    tx.commit().await?;
+
+    audit::log_action(pool, &user.id, "discard_draft", "page", id, &json!({})).await?;
+
+    Ok(())
+}
+
+/// Submits a draft page for editorial review. For a published page, its
+/// pending draft is submitted instead and the live version stays up.
+pub async fn submit_page(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Page> {
+    let existing = get_page(pool, id).await?;
+    ownership::ensure_can_modify(pool, user, "page", id, "submit_review").await?;
+    let draft = get_draft(pool, id).await?;
+    let target = review::submission_target(&existing.status, draft.is_some())?;
+
+    match target {
+        review::Target::Content => {
+            sqlx::query(
+                "UPDATE pages \
+                 SET status = 'in_review', \
+                     updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+                 WHERE id = ?",
+            )
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        review::Target::Draft => {
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+            sqlx::query(
+                "UPDATE page_drafts SET in_review = 1, review_comment = NULL WHERE page_id = ?",
+            )
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            touch(&mut *tx, id).await?;
+            tx.commit().await?;
+        }
+    }
+
+    let details = json!({ "draft": target == review::Target::Draft });
+    audit::log_action(pool, &user.id, "submit_review", "page", id, &details).await?;
+
+    get_page(pool, id).await
+}
+
+/// Approves a page in review. It is published right away, or scheduled
+/// when it carries a future `publish_at`. A submitted draft of a published
+/// page becomes its live version.
+pub async fn approve_page(pool: &SqlitePool, id: &str, user: &User) -> AppResult<Page> {
+    let existing = get_page(pool, id).await?;
+    ownership::ensure_can_publish(pool, user, "page", id, "approve").await?;
+    let policy = sanitize::policy(pool).await?;
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let draft = get_draft(&mut *tx, id).await?.filter(|draft| draft.in_review);
+    let target = review::review_target(&existing.status, draft.is_some())?;
+    let status = match target {
+        review::Target::Content => review::approved_status(existing.publish_at),
+        review::Target::Draft => "published",
+    };
+    visibility::ensure_not_expired(status, existing.unpublish_at)?;
+
+    if let (review::Target::Draft, Some(draft)) = (target, &draft) {
+        apply_draft(&mut tx, id, draft, &policy).await?;
+    }
 
     sqlx::query(
         "UPDATE pages \
-         SET status = 'published', \
-             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         SET status = ?, review_comment = NULL, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
          WHERE id = ?",
     )
+    .bind(status)
     .bind(id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
 
-    audit::log_action(pool, user_id, "publish", "page", id, &json!({})).await?;
+    let details = json!({ "status": status, "draft": target == review::Target::Draft });
+    audit::log_action(pool, &user.id, "approve", "page", id, &details).await?;
+
+    get_page(pool, id).await
+}
+
+/// Rejects a page in review, sending it back to draft with the reviewer's
+/// comment attached. A submitted draft of a published page goes back to its
+/// author the same way, with the comment on the draft.
+pub async fn reject_page(
+    pool: &SqlitePool,
+    id: &str,
+    comment: &str,
+    user: &User,
+) -> AppResult<Page> {
+    let existing = get_page(pool, id).await?;
+    ownership::ensure_can_publish(pool, user, "page", id, "reject").await?;
+    let draft_in_review = get_draft(pool, id).await?.is_some_and(|draft| draft.in_review);
+    let target = review::review_target(&existing.status, draft_in_review)?;
+    let comment = review::rejection_comment(comment)?;
+
+    match target {
+        review::Target::Content => {
+            sqlx::query(
+                "UPDATE pages \
+                 SET status = 'draft', review_comment = ?, \
+                     updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+                 WHERE id = ?",
+            )
+            .bind(&comment)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        review::Target::Draft => {
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+            sqlx::query(
+                "UPDATE page_drafts SET in_review = 0, review_comment = ? WHERE page_id = ?",
+            )
+            .bind(&comment)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            touch(&mut *tx, id).await?;
+            tx.commit().await?;
+        }
+    }
+
+    let details = json!({ "comment": comment, "draft": target == review::Target::Draft });
+    audit::log_action(pool, &user.id, "reject", "page", id, &details).await?;
 
     get_page(pool, id).await
 }
@@ -313,10 +849,11 @@ pub async fn list_revisions(pool: &SqlitePool, page_id: &str) -> AppResult<Vec<P
     get_page(pool, page_id).await?;
 
     let revisions = sqlx::query_as::<_, PageRevision>(
-        "SELECT id, page_id, title, content, author_id, created_at \
+        "SELECT id, page_id, title, content, content_format, author_id, created_at, \
+                message, version_name \
          FROM page_revisions \
          WHERE page_id = ? \
-         ORDER BY created_at DESC",
+         ORDER BY created_at DESC, rowid DESC",
     )
     .bind(page_id)
     .fetch_all(pool)
@@ -325,18 +862,15 @@ pub async fn list_revisions(pool: &SqlitePool, page_id: &str) -> AppResult<Vec<P
     Ok(revisions)
 }
 
-/// Restores a page to the state captured in a specific revision.
-///
-/// This creates a new revision recording the restore event, rather than
-/// removing revisions, so the full history remains intact.
-pub async fn restore_revision(
+/// Fetches a single revision, which must belong to the given page.
+async fn get_revision(
     pool: &SqlitePool,
     page_id: &str,
     revision_id: &str,
-    user_id: &str,
-) -> AppResult<Page> {
-    let revision = sqlx::query_as::<_, PageRevision>(
-        "SELECT id, page_id, title, content, author_id, created_at \
+) -> AppResult<PageRevision> {
+    sqlx::query_as::<_, PageRevision>(
+        "SELECT id, page_id, title, content, content_format, author_id, created_at, \
+                message, version_name \
          FROM page_revisions \
          WHERE id = ? AND page_id = ?",
     )
@@ -344,26 +878,250 @@ pub async fn restore_revision(
     .bind(page_id)
     .fetch_optional(pool)
     .await?
-    .ok_or(AppError::NotFound)?;
+    .ok_or(AppError::NotFound)
+}
+
+/// Compares two versions of a page, each given as a revision id or as
+/// `current` for the page as it stands now. The result also diffs `to`
+/// against the current version, so reviewers can see what is live.
+pub async fn diff_revisions(
+    pool: &SqlitePool,
+    page_id: &str,
+    from: &str,
+    to: &str,
+    options: DiffOptions,
+) -> AppResult<RevisionDiff> {
+    let page = get_page(pool, page_id).await?;
+    let current = Snapshot {
+        revision_id: None,
+        author_id: &page.author_id,
+        created_at: page.updated_at,
+        title: &page.title,
+        short_text: None,
+        content: &page.content,
+    };
+
+    let from_revision = load_diff_revision(pool, page_id, from).await?;
+    let to_revision = load_diff_revision(pool, page_id, to).await?;
+    let from = from_revision.as_ref().map_or(current, revision_snapshot);
+    let to = to_revision.as_ref().map_or(current, revision_snapshot);
+
+    Ok(diff::compare(&from, &to, &current, options))
+}
+
+fn revision_snapshot(revision: &PageRevision) -> Snapshot<'_> {
+    Snapshot {
+        revision_id: Some(&revision.id),
+        author_id: &revision.author_id,
+        created_at: revision.created_at,
+        title: &revision.title,
+        short_text: None,
+        content: &revision.content,
+    }
+}
+
+/// Loads the revision named in a diff path, or `None` for `current`.
+async fn load_diff_revision(
+    pool: &SqlitePool,
+    page_id: &str,
+    revision_id: &str,
+) -> AppResult<Option<PageRevision>> {
+    if revision_id == diff::CURRENT {
+        return Ok(None);
+    }
+    get_revision(pool, page_id, revision_id).await.map(Some)
+}
+
+/// Pins a revision as a named version, exempting it from retention.
+pub async fn pin_revision(
+    pool: &SqlitePool,
+    page_id: &str,
+    revision_id: &str,
+    name: &str,
+    user: &User,
+) -> AppResult<PageRevision> {
+    ownership::ensure_can_modify(pool, user, "page", page_id, "pin_revision").await?;
+    revisions::pin(pool, "page", page_id, revision_id, name, &user.id).await?;
+    get_revision(pool, page_id, revision_id).await
+}
+
+/// Removes a revision's version name.
+pub async fn unpin_revision(
+    pool: &SqlitePool,
+    page_id: &str,
+    revision_id: &str,
+    user: &User,
+) -> AppResult<PageRevision> {
+    ownership::ensure_can_modify(pool, user, "page", page_id, "unpin_revision").await?;
+    revisions::unpin(pool, "page", page_id, revision_id, &user.id).await?;
+    get_revision(pool, page_id, revision_id).await
+}
+
+/// Restores a page to the state captured in a specific revision.
+///
+/// This creates a new revision recording the restore event, rather than
+/// removing revisions, so the full history remains intact.
+pub async fn restore_revision(
+    pool: &SqlitePool,
+    page_id: &str,
+    revision_id: &str,
+    user: &User,
+) -> AppResult<Page> {
+    let revision = get_revision(pool, page_id, revision_id).await?;
+    ownership::ensure_can_modify(pool, user, "page", page_id, "restore_revision").await?;
+    let message = match &revision.version_name {
+        Some(name) => format!("Restored version '{}'", name),
+        None => format!(
+            "Restored revision from {}",
+            revision.created_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
 
     let input = UpdatePage {
         title: Some(revision.title),
         content: Some(revision.content),
+        content_format: Some(revision.content_format),
         slug: None,
         status: None,
         publish_at: None,
+        unpublish_at: None,
         category_ids: None,
+        tags: None,
+        reclaim_slug: false,
+        message: Some(message),
+        expected_updated_at: None,
         template: None,
+        parent_id: None,
+        sort_order: None,
     };
 
-    update_page(pool, page_id, input, user_id).await
+    update_page(pool, page_id, input, user).await
+}
+
+/// Recomputes `path` for every page, walking down from the top-level pages.
+///
+/// Needed after pages are permanently deleted: the `ON DELETE SET NULL` on
+/// `parent_id` promotes orphaned children to the top level, but SQLite cannot
+/// rewrite their materialised paths on its own.
+pub async fn refresh_all_paths(pool: &SqlitePool) -> AppResult<()> {
+    sqlx::query(
+        "WITH RECURSIVE tree(page_id, page_path) AS ( \
+             SELECT id, slug FROM pages WHERE parent_id IS NULL \
+             UNION ALL \
+             SELECT c.id, tree.page_path || '/' || c.slug \
+             FROM pages c JOIN tree ON c.parent_id = tree.page_id \
+         ) \
+         UPDATE pages \
+         SET path = (SELECT page_path FROM tree WHERE page_id = pages.id) \
+         WHERE id IN (SELECT page_id FROM tree) \
+           AND path != (SELECT page_path FROM tree WHERE page_id = pages.id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
 }
 
 // ─── Internal helpers ─────────────────────────────────────────────────────────
 
+/// Returns the ancestors of a page, nearest parent first.
+async fn ancestors(pool: &SqlitePool, page_id: &str) -> AppResult<Vec<Page>> {
+    // The depth guard is purely defensive: cycles are rejected on write, but a
+    // hand-edited database must not be able to hang the query.
+    let rows = sqlx::query_as::<_, Page>(&format!(
+        "WITH RECURSIVE chain(page_id, depth) AS ( \
+             SELECT parent_id, 1 FROM pages WHERE id = ? \
+             UNION ALL \
+             SELECT p.parent_id, chain.depth + 1 \
+             FROM pages p JOIN chain ON p.id = chain.page_id \
+             WHERE p.parent_id IS NOT NULL AND chain.depth < 100 \
+         ) \
+         SELECT {PAGE_COLS} FROM pages JOIN chain ON pages.id = chain.page_id \
+         ORDER BY chain.depth ASC"
+    ))
+    .bind(page_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Fetches the page to nest another page under. Returns `BadRequest` unless it
+/// exists and is not in the trash.
+async fn get_parent(pool: &SqlitePool, parent_id: &str) -> AppResult<Page> {
+    match get_page(pool, parent_id).await {
+        Ok(parent) if parent.trashed_at.is_none() => Ok(parent),
+        Ok(_) => Err(AppError::BadRequest(
+            "A page cannot be placed beneath a page in the trash".to_owned(),
+        )),
+        Err(AppError::NotFound) => Err(AppError::BadRequest(format!(
+            "Parent page '{parent_id}' does not exist"
+        ))),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns `BadRequest` unless `new_parent_id` names a page that can hold
+/// others (see `get_parent`) and making it the parent of `page_id` would not
+/// create a cycle, i.e. the new parent is not the page itself or one of its
+/// descendants.
+async fn validate_parent(pool: &SqlitePool, page_id: &str, new_parent_id: &str) -> AppResult<()> {
+    get_parent(pool, new_parent_id).await?;
+
+    let creates_cycle = new_parent_id == page_id
+        || ancestors(pool, new_parent_id)
+            .await?
+            .iter()
+            .any(|p| p.id == page_id);
+
+    if creates_cycle {
+        return Err(AppError::BadRequest(
+            "A page cannot be moved beneath itself or one of its descendants".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Recomputes `path` for a page and all of its descendants in one statement,
+/// based on the current path of the page's parent.
+async fn refresh_subtree_paths(conn: impl SqliteExecutor<'_>, page_id: &str) -> AppResult<()> {
+    sqlx::query(
+        "WITH RECURSIVE tree(page_id, page_path) AS ( \
+             SELECT p.id, \
+                    COALESCE((SELECT parent.path || '/' FROM pages parent \
+                              WHERE parent.id = p.parent_id), '') || p.slug \
+             FROM pages p WHERE p.id = ? \
+             UNION ALL \
+             SELECT c.id, tree.page_path || '/' || c.slug \
+             FROM pages c JOIN tree ON c.parent_id = tree.page_id \
+         ) \
+         UPDATE pages \
+         SET path = (SELECT page_path FROM tree WHERE page_id = pages.id) \
+         WHERE id IN (SELECT page_id FROM tree)",
+    )
+    .bind(page_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the sort position just after the last sibling under `parent_id`.
+async fn next_sort_order(pool: &SqlitePool, parent_id: Option<&str>) -> AppResult<i32> {
+    let max_order = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(sort_order) FROM pages WHERE parent_id IS ?",
+    )
+    .bind(parent_id)
+    .fetch_one(pool)
+    .await?
+    .unwrap_or(-1);
+
+    Ok((max_order + 1) as i32)
+}
+
 /// Validates that a status string is one of the allowed values.
 fn validate_status(status: &str) -> AppResult<()> {
-    const VALID: &[&str] = &["draft", "published", "scheduled"];
+    const VALID: &[&str] = &["draft", "in_review", "published", "scheduled", "archived"];
     if !VALID.contains(&status) {
         return Err(AppError::BadRequest(format!(
             "Invalid status '{}'. Must be one of: {}",
@@ -374,41 +1132,201 @@ fn validate_status(status: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Validates the `?status=` filter of the admin list, which additionally
+/// accepts `trashed`.
+fn validate_status_filter(status: &str) -> AppResult<()> {
+    if status == "trashed" {
+        return Ok(());
+    }
+    validate_status(status)
+}
+
+/// The editable text of a page, as captured by revisions and drafts.
+struct Body<'a> {
+    title: &'a str,
+    content: &'a str,
+    content_format: &'a str,
+}
+
+/// Where a page sits in the tree and how it is shown, as an update asks.
+struct Placement<'a> {
+    slug: &'a str,
+    parent_id: Option<&'a str>,
+    template: &'a str,
+}
+
+/// The category and tag assignments an update asks for.
+struct Assignments<'a> {
+    category_ids: Option<&'a [String]>,
+    tags: Option<&'a [String]>,
+}
+
+/// Returns `BadRequest` if an update whose edits go to the draft of a
+/// published page also changes its slug, parent, template, categories or
+/// tags. Those are not part of the draft, so they would go live without
+/// review.
+async fn ensure_live_fields_kept(
+    pool: &SqlitePool,
+    existing: &Page,
+    placement: &Placement<'_>,
+    input: &Assignments<'_>,
+) -> AppResult<()> {
+    let mut changed = Vec::new();
+    if placement.slug != existing.slug {
+        changed.push("slug");
+    }
+    if placement.parent_id != existing.parent_id.as_deref() {
+        changed.push("parent");
+    }
+    if placement.template != existing.template {
+        changed.push("template");
+    }
+    if let Some(cat_ids) = input.category_ids {
+        if categories::would_change(pool, "page", &existing.id, cat_ids).await? {
+            changed.push("categories");
+        }
+    }
+    if let Some(names) = input.tags {
+        if tags::would_change(pool, "page", &existing.id, names).await? {
+            changed.push("tags");
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::BadRequest(format!(
+        "The {} of a published page cannot change while edits to it wait in a draft; \
+         publish or discard the draft first",
+        changed.join(", ")
+    )))
+}
+
 /// Inserts a revision row for the given page's current title and content.
 async fn create_revision(
-    pool: &SqlitePool,
+    conn: impl SqliteExecutor<'_>,
     page_id: &str,
-    title: &str,
-    content: &str,
+    body: &Body<'_>,
     author_id: &str,
+    message: Option<&str>,
 ) -> AppResult<()> {
     sqlx::query(
-        "INSERT INTO page_revisions (id, page_id, title, content, author_id) \
-         VALUES (?, ?, ?, ?, ?)",
+        "INSERT INTO page_revisions \
+             (id, page_id, title, content, content_format, author_id, message) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(Uuid::new_v4().to_string())
     .bind(page_id)
-    .bind(title)
-    .bind(content)
+    .bind(body.title)
+    .bind(body.content)
+    .bind(body.content_format)
     .bind(author_id)
-    .execute(pool)
+    .bind(message)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the page's pending draft, if any.
+async fn get_draft(conn: impl SqliteExecutor<'_>, page_id: &str) -> AppResult<Option<PageDraft>> {
+    let draft = sqlx::query_as::<_, PageDraft>(
+        "SELECT page_id, title, content, content_format, author_id, updated_at, \
+                in_review, review_comment \
+         FROM page_drafts \
+         WHERE page_id = ?",
+    )
+    .bind(page_id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(draft)
+}
+
+/// Creates or replaces the page's draft.
+async fn save_draft(
+    conn: impl SqliteExecutor<'_>,
+    page_id: &str,
+    body: &Body<'_>,
+    author_id: &str,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO page_drafts (page_id, title, content, content_format, author_id) \
+         VALUES (?, ?, ?, ?, ?) \
+         ON CONFLICT(page_id) DO UPDATE SET \
+             title = excluded.title, \
+             content = excluded.content, \
+             content_format = excluded.content_format, \
+             author_id = excluded.author_id, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')",
+    )
+    .bind(page_id)
+    .bind(body.title)
+    .bind(body.content)
+    .bind(body.content_format)
+    .bind(author_id)
+    .execute(conn)
     .await?;
 
     Ok(())
 }
 
+/// Copies the draft onto the live page, rendered with `policy`, and deletes it.
+async fn apply_draft(
+    conn: &mut SqliteConnection,
+    page_id: &str,
+    draft: &PageDraft,
+    policy: &SanitizePolicy,
+) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE pages \
+         SET title = ?, content = ?, content_format = ?, content_html = ? \
+         WHERE id = ?",
+    )
+    .bind(&draft.title)
+    .bind(&draft.content)
+    .bind(&draft.content_format)
+    .bind(render::render(&draft.content, &draft.content_format, policy))
+    .bind(page_id)
+    .execute(&mut *conn)
+    .await?;
+    delete_draft(conn, page_id).await?;
+
+    Ok(())
+}
+
+/// Moves the page's version on after a change that only touched its draft.
+async fn touch(conn: impl SqliteExecutor<'_>, id: &str) -> AppResult<()> {
+    sqlx::query("UPDATE pages SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = ?")
+        .bind(id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes the page's draft. Returns whether there was one.
+async fn delete_draft(conn: impl SqliteExecutor<'_>, page_id: &str) -> AppResult<bool> {
+    let result = sqlx::query("DELETE FROM page_drafts WHERE page_id = ?")
+        .bind(page_id)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Replaces all category assignments for a page.
 ///
 /// We delete-then-insert rather than diffing, which is simpler and correct for
 /// the small cardinalities involved in a CMS category list.
 async fn set_page_categories(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     page_id: &str,
     category_ids: &[String],
 ) -> AppResult<()> {
     sqlx::query("DELETE FROM page_categories WHERE page_id = ?")
         .bind(page_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
     for cat_id in category_ids {
@@ -417,7 +1335,7 @@ async fn set_page_categories(
         )
         .bind(page_id)
         .bind(cat_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
     }
 
@@ -425,11 +1343,13 @@ async fn set_page_categories(
 }
 
 /// Returns `Conflict` if `slug` is already used by a page other than
-/// `exclude_id` (pass `None` when creating, `Some(id)` when updating).
+/// `exclude_id` (pass `None` when creating, `Some(id)` when updating), or if it
+/// is a retired slug of another page and `reclaim` is not set.
 async fn ensure_slug_unique(
     pool: &SqlitePool,
     slug: &str,
     exclude_id: Option<&str>,
+    reclaim: bool,
 ) -> AppResult<()> {
     let existing_id = sqlx::query_scalar::<_, String>(
         "SELECT id FROM pages WHERE slug = ? LIMIT 1",
@@ -438,15 +1358,31 @@ async fn ensure_slug_unique(
     .fetch_optional(pool)
     .await?;
 
-    match (existing_id, exclude_id) {
+    let available = match (existing_id, exclude_id) {
         // No existing row with this slug — unique.
-        (None, _) => Ok(()),
+        (None, _) => true,
         // Existing row belongs to the page we're updating — still unique.
-        (Some(ref found_id), Some(excluded)) if found_id == excluded => Ok(()),
+        (Some(ref found_id), Some(excluded)) if found_id == excluded => true,
         // Slug is taken by a different page.
-        _ => Err(AppError::Conflict(format!(
+        _ => false,
+    };
+    if !available {
+        return Err(AppError::Conflict(format!(
             "A page with slug '{}' already exists",
             slug
-        ))),
+        )));
+    }
+
+    // Retired slugs keep redirecting to their previous owner, so they are not
+    // free for other content unless the caller explicitly reclaims them.
+    match slugs::historic_owner(pool, "page", slug).await? {
+        Some(owner) if Some(owner.as_str()) != exclude_id && !reclaim => {
+            Err(AppError::Conflict(format!(
+                "The slug '{}' belonged to another page and still redirects there; \
+                 set reclaim_slug to take it over",
+                slug
+            )))
+        }
+        _ => Ok(()),
     }
 }
@@ -0,0 +1,557 @@
+//! Redirect rules service.
+//!
+//! Rules are managed by admins and evaluated by the fallback router for every
+//! request that did not match an API route, before it is proxied to the
+//! frontend. Because that path is hot, the compiled rule set is kept in a
+//! `RedirectCache` that handlers invalidate after every write, and hits are
+//! counted in memory and written out by the housekeeping task.
+//!
+//! Precedence is fixed so that adding a rule never silently changes which
+//! rule wins elsewhere: exact rules first, then prefix rules (longest source
+//! first), then regex rules in creation order.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde_json::json;
+use sqlx::SqlitePool;
+use tokio::sync::{Mutex, RwLock};
+use uuid::Uuid;
+
+use crate::db::models::{CreateRedirect, Redirect, UpdateRedirect};
+use crate::error::{AppError, AppResult};
+use crate::services::{audit, schedule};
+
+// ─── Column list shared by all SELECT queries ─────────────────────────────────
+
+const REDIRECT_COLS: &str =
+    "id, match_type, source, target, status_code, hit_count, last_hit_at, \
+     created_at, updated_at";
+
+// ─── Matching ─────────────────────────────────────────────────────────────────
+
+/// A redirect rule ready for matching, with its regex compiled up front.
+#[derive(Debug)]
+pub struct CompiledRedirect {
+    pub id: String,
+    pub status_code: u16,
+    target: String,
+    matcher: Matcher,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Exact(String),
+    Prefix(String),
+    Regex(Regex),
+}
+
+/// The outcome of matching a request path against the rule set.
+#[derive(Debug, PartialEq)]
+pub struct RedirectMatch {
+    pub rule_id: String,
+    pub status_code: u16,
+    /// Resolved `Location`; empty for 410 rules.
+    pub location: String,
+}
+
+impl CompiledRedirect {
+    fn compile(rule: &Redirect) -> AppResult<Self> {
+        let matcher = match rule.match_type.as_str() {
+            "exact" => Matcher::Exact(rule.source.clone()),
+            "prefix" => Matcher::Prefix(rule.source.clone()),
+            "regex" => Matcher::Regex(Regex::new(&rule.source).map_err(|e| {
+                AppError::BadRequest(format!("Invalid regular expression: {e}"))
+            })?),
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid match_type '{}'. Must be one of: exact, prefix, regex",
+                    other
+                )))
+            }
+        };
+
+        Ok(Self {
+            id: rule.id.clone(),
+            status_code: rule.status_code as u16,
+            target: rule.target.clone(),
+            matcher,
+        })
+    }
+
+    /// Returns the resolved target when `path` matches this rule.
+    fn apply(&self, path: &str) -> Option<String> {
+        match &self.matcher {
+            Matcher::Exact(source) => (path == source).then(|| self.target.clone()),
+            Matcher::Prefix(source) => {
+                strip_prefix_segment(path, source).map(|rest| format!("{}{}", self.target, rest))
+            }
+            Matcher::Regex(re) => re.captures(path).map(|caps| {
+                let mut target = String::new();
+                caps.expand(&self.target, &mut target);
+                target
+            }),
+        }
+    }
+
+    fn precedence(&self) -> (u8, std::cmp::Reverse<usize>) {
+        match &self.matcher {
+            Matcher::Exact(_) => (0, std::cmp::Reverse(0)),
+            Matcher::Prefix(source) => (1, std::cmp::Reverse(source.len())),
+            Matcher::Regex(_) => (2, std::cmp::Reverse(0)),
+        }
+    }
+}
+
+/// Strips `source` from the front of `path` when it ends on a segment
+/// boundary, so `/old` covers `/old` and `/old/faq` but not `/older`.
+fn strip_prefix_segment<'a>(path: &'a str, source: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(source)?;
+    (rest.is_empty() || rest.starts_with('/') || source.ends_with('/')).then_some(rest)
+}
+
+/// Finds the first rule matching `path` and resolves its location. The
+/// original query string is carried over unless the target sets its own.
+pub fn match_path(
+    rules: &[CompiledRedirect],
+    path: &str,
+    query: Option<&str>,
+) -> Option<RedirectMatch> {
+    rules.iter().find_map(|rule| {
+        let mut location = rule.apply(path)?;
+        if rule.status_code == 410 {
+            location.clear();
+        } else if let Some(q) = query.filter(|q| !q.is_empty()) {
+            if !location.contains('?') {
+                location = format!("{location}?{q}");
+            }
+        }
+        Some(RedirectMatch {
+            rule_id: rule.id.clone(),
+            status_code: rule.status_code,
+            location,
+        })
+    })
+}
+
+/// Lazily loaded, shared copy of the compiled rule set, and the hits counted
+/// since they were last written to the database.
+#[derive(Clone, Default)]
+pub struct RedirectCache {
+    rules: Arc<RwLock<Option<Arc<Vec<CompiledRedirect>>>>>,
+    hits: Arc<Mutex<HashMap<String, PendingHits>>>,
+}
+
+/// Hits on one rule that are not in the database yet.
+struct PendingHits {
+    count: i64,
+    last_hit_at: DateTime<Utc>,
+}
+
+impl RedirectCache {
+    /// Returns the compiled rules, loading them from the database on first
+    /// use after an invalidation. Rules that no longer compile are skipped
+    /// with a warning rather than disabling every redirect.
+    pub async fn rules(&self, pool: &SqlitePool) -> AppResult<Arc<Vec<CompiledRedirect>>> {
+        if let Some(rules) = self.rules.read().await.as_ref() {
+            return Ok(rules.clone());
+        }
+
+        let mut guard = self.rules.write().await;
+        if let Some(rules) = guard.as_ref() {
+            return Ok(rules.clone());
+        }
+
+        let rows = sqlx::query_as::<_, Redirect>(&format!(
+            "SELECT {REDIRECT_COLS} FROM redirects ORDER BY created_at ASC, rowid ASC"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        let mut compiled: Vec<CompiledRedirect> = rows
+            .iter()
+            .filter_map(|rule| match CompiledRedirect::compile(rule) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    tracing::warn!(rule_id = %rule.id, "Skipping redirect rule: {e}");
+                    None
+                }
+            })
+            .collect();
+        // Stable sort keeps creation order within each precedence class.
+        compiled.sort_by_key(|rule| rule.precedence());
+
+        let compiled = Arc::new(compiled);
+        *guard = Some(compiled.clone());
+        Ok(compiled)
+    }
+
+    /// Drops the cached rules so the next lookup reloads them.
+    pub async fn invalidate(&self) {
+        *self.rules.write().await = None;
+    }
+
+    /// Counts a hit on rule `id`. Serving a redirect never waits for a
+    /// database write; the count is stored by the next `flush_hits`.
+    pub async fn record_hit(&self, id: &str) {
+        let now = Utc::now();
+        self.hits
+            .lock()
+            .await
+            .entry(id.to_owned())
+            .and_modify(|pending| {
+                pending.count += 1;
+                pending.last_hit_at = now;
+            })
+            .or_insert(PendingHits { count: 1, last_hit_at: now });
+    }
+
+    /// Adds the hits counted since the last flush to the rules' counters in
+    /// one transaction. Hits on rules deleted in the meantime are dropped, as
+    /// are the counted hits if the write fails or the server stops first.
+    pub async fn flush_hits(&self, pool: &SqlitePool) -> AppResult<()> {
+        let hits = std::mem::take(&mut *self.hits.lock().await);
+        if hits.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+        for (id, pending) in &hits {
+            sqlx::query(
+                "UPDATE redirects \
+                 SET hit_count = hit_count + ?, \
+                     last_hit_at = ? \
+                 WHERE id = ?",
+            )
+            .bind(pending.count)
+            .bind(schedule::db_timestamp(pending.last_hit_at))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns all redirect rules, newest first.
+///
+/// When `stale_days` is set, only rules that have not been hit within that
+/// many days (including never-hit rules older than that) are returned —
+/// candidates for removal.
+pub async fn list_redirects(
+    pool: &SqlitePool,
+    stale_days: Option<u32>,
+) -> AppResult<Vec<Redirect>> {
+    let rows = if let Some(days) = stale_days {
+        let cutoff = format!("-{days} days");
+        sqlx::query_as::<_, Redirect>(&format!(
+            "SELECT {REDIRECT_COLS} FROM redirects \
+             WHERE COALESCE(last_hit_at, created_at) \
+                   < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?) \
+             ORDER BY COALESCE(last_hit_at, created_at) ASC"
+        ))
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await?
+    } else {
+        sqlx::query_as::<_, Redirect>(&format!(
+            "SELECT {REDIRECT_COLS} FROM redirects ORDER BY created_at DESC"
+        ))
+        .fetch_all(pool)
+        .await?
+    };
+
+    Ok(rows)
+}
+
+/// Fetches a single redirect rule by primary key. Returns `NotFound` if absent.
+pub async fn get_redirect(pool: &SqlitePool, id: &str) -> AppResult<Redirect> {
+    sqlx::query_as::<_, Redirect>(&format!(
+        "SELECT {REDIRECT_COLS} FROM redirects WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound)
+}
+
+/// Creates a new redirect rule after validating it compiles.
+pub async fn create_redirect(
+    pool: &SqlitePool,
+    input: CreateRedirect,
+    user_id: &str,
+) -> AppResult<Redirect> {
+    let id = Uuid::new_v4().to_string();
+    let rule = Redirect {
+        id: id.clone(),
+        match_type: input.match_type,
+        source: input.source,
+        target: input.target.unwrap_or_default(),
+        status_code: input.status_code.unwrap_or(301),
+        hit_count: 0,
+        last_hit_at: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+    validate_rule(&rule)?;
+    ensure_source_unique(pool, &rule.match_type, &rule.source, None).await?;
+
+    sqlx::query(
+        "INSERT INTO redirects (id, match_type, source, target, status_code) \
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&rule.match_type)
+    .bind(&rule.source)
+    .bind(&rule.target)
+    .bind(rule.status_code)
+    .execute(pool)
+    .await?;
+
+    audit::log_action(
+        pool,
+        user_id,
+        "create",
+        "redirect",
+        &id,
+        &json!({ "source": rule.source, "target": rule.target, "status_code": rule.status_code }),
+    )
+    .await?;
+
+    get_redirect(pool, &id).await
+}
+
+/// Applies a partial update to an existing redirect rule. Hit statistics are
+/// kept so a tweaked rule does not look dead.
+pub async fn update_redirect(
+    pool: &SqlitePool,
+    id: &str,
+    input: UpdateRedirect,
+    user_id: &str,
+) -> AppResult<Redirect> {
+    let mut rule = get_redirect(pool, id).await?;
+    let previous = (rule.match_type.clone(), rule.source.clone());
+
+    if let Some(match_type) = input.match_type {
+        rule.match_type = match_type;
+    }
+    if let Some(source) = input.source {
+        rule.source = source;
+    }
+    if let Some(target) = input.target {
+        rule.target = target;
+    }
+    if let Some(status_code) = input.status_code {
+        rule.status_code = status_code;
+    }
+    validate_rule(&rule)?;
+    if (rule.match_type.clone(), rule.source.clone()) != previous {
+        ensure_source_unique(pool, &rule.match_type, &rule.source, Some(id)).await?;
+    }
+
+    sqlx::query(
+        "UPDATE redirects \
+         SET match_type = ?, source = ?, target = ?, status_code = ?, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         WHERE id = ?",
+    )
+    .bind(&rule.match_type)
+    .bind(&rule.source)
+    .bind(&rule.target)
+    .bind(rule.status_code)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    audit::log_action(
+        pool,
+        user_id,
+        "update",
+        "redirect",
+        id,
+        &json!({ "source": rule.source, "target": rule.target, "status_code": rule.status_code }),
+    )
+    .await?;
+
+    get_redirect(pool, id).await
+}
+
+/// Deletes a redirect rule.
+pub async fn delete_redirect(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<()> {
+    let rule = get_redirect(pool, id).await?;
+
+    sqlx::query("DELETE FROM redirects WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    audit::log_action(
+        pool,
+        user_id,
+        "delete",
+        "redirect",
+        id,
+        &json!({ "source": rule.source, "hit_count": rule.hit_count }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// Checks that a rule is well-formed: known status code, a target for
+/// redirecting statuses, a path-shaped source for exact/prefix rules, a regex
+/// that compiles, and a target the rule would not redirect again.
+fn validate_rule(rule: &Redirect) -> AppResult<()> {
+    if ![301, 302, 410].contains(&rule.status_code) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid status_code {}. Must be one of: 301, 302, 410",
+            rule.status_code
+        )));
+    }
+    if rule.status_code != 410 && rule.target.trim().is_empty() {
+        return Err(AppError::BadRequest(
+            "A target is required for 301 and 302 redirects".to_owned(),
+        ));
+    }
+    if rule.match_type != "regex" && !rule.source.starts_with('/') {
+        return Err(AppError::BadRequest(
+            "Exact and prefix sources must start with '/'".to_owned(),
+        ));
+    }
+    let compiled = CompiledRedirect::compile(rule)?;
+    if rule.status_code != 410 && redirects_to_itself(&compiled) {
+        return Err(AppError::BadRequest(format!(
+            "The target '{}' is matched by the rule's own source, which would loop",
+            rule.target
+        )));
+    }
+    Ok(())
+}
+
+/// Returns true when a rule's own target matches it again: an exact rule
+/// pointing at its source, a prefix rule pointing at or under its source, or
+/// a regex rule whose literal target matches the expression. Targets on
+/// other hosts never loop.
+fn redirects_to_itself(rule: &CompiledRedirect) -> bool {
+    if !rule.target.starts_with('/') || rule.target.starts_with("//") {
+        return false;
+    }
+    let target_path = rule.target.split(['?', '#']).next().unwrap_or_default();
+    match &rule.matcher {
+        Matcher::Regex(_) if rule.target.contains('$') => false,
+        _ => rule.apply(target_path).is_some(),
+    }
+}
+
+/// Returns `Conflict` if another rule already uses the same match type and
+/// source — only one of them could ever win.
+async fn ensure_source_unique(
+    pool: &SqlitePool,
+    match_type: &str,
+    source: &str,
+    exclude_id: Option<&str>,
+) -> AppResult<()> {
+    let existing_id = sqlx::query_scalar::<_, String>(
+        "SELECT id FROM redirects WHERE match_type = ? AND source = ? LIMIT 1",
+    )
+    .bind(match_type)
+    .bind(source)
+    .fetch_optional(pool)
+    .await?;
+
+    match (existing_id, exclude_id) {
+        (None, _) => Ok(()),
+        (Some(ref found_id), Some(excluded)) if found_id == excluded => Ok(()),
+        _ => Err(AppError::Conflict(format!(
+            "A {} redirect for '{}' already exists",
+            match_type, source
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, match_type: &str, source: &str, target: &str, status: i32) -> CompiledRedirect {
+        CompiledRedirect::compile(&Redirect {
+            id: id.to_owned(),
+            match_type: match_type.to_owned(),
+            source: source.to_owned(),
+            target: target.to_owned(),
+            status_code: status,
+            hit_count: 0,
+            last_hit_at: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_match_path() {
+        let mut rules = vec![
+            rule("re", "regex", r"^/blog/(\d+)/(?P<slug>[^/]+)$", "/articles/${slug}", 301),
+            rule("short", "prefix", "/old", "/new", 302),
+            rule("long", "prefix", "/old/docs/", "/guides/", 301),
+            rule("exact", "exact", "/about-us", "/about", 301),
+            rule("gone", "exact", "/legacy", "", 410),
+        ];
+        rules.sort_by_key(|r| r.precedence());
+
+        let m = match_path(&rules, "/about-us", Some("ref=x")).unwrap();
+        assert_eq!((m.rule_id.as_str(), m.location.as_str()), ("exact", "/about?ref=x"));
+
+        let m = match_path(&rules, "/old/docs/install", None).unwrap();
+        assert_eq!((m.rule_id.as_str(), m.location.as_str()), ("long", "/guides/install"));
+
+        let m = match_path(&rules, "/old/faq", None).unwrap();
+        assert_eq!((m.status_code, m.location.as_str()), (302, "/new/faq"));
+
+        let m = match_path(&rules, "/blog/2019/hello-world", None).unwrap();
+        assert_eq!(m.location, "/articles/hello-world");
+
+        let m = match_path(&rules, "/legacy", Some("a=b")).unwrap();
+        assert_eq!((m.status_code, m.location.as_str()), (410, ""));
+
+        assert!(match_path(&rules, "/articles/x", None).is_none());
+        // Prefixes only match whole path segments.
+        assert!(match_path(&rules, "/older", None).is_none());
+        let m = match_path(&rules, "/old", None).unwrap();
+        assert_eq!(m.location, "/new");
+    }
+
+    #[test]
+    fn test_self_targeting_rules_are_rejected() {
+        let redirect = |match_type: &str, source: &str, target: &str| Redirect {
+            id: "r".to_owned(),
+            match_type: match_type.to_owned(),
+            source: source.to_owned(),
+            target: target.to_owned(),
+            status_code: 301,
+            hit_count: 0,
+            last_hit_at: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+
+        assert!(validate_rule(&redirect("exact", "/about", "/about")).is_err());
+        assert!(validate_rule(&redirect("exact", "/about", "/about?x=1")).is_err());
+        assert!(validate_rule(&redirect("prefix", "/old", "/old")).is_err());
+        assert!(validate_rule(&redirect("prefix", "/old", "/old/new")).is_err());
+        assert!(validate_rule(&redirect("regex", "^/a.*$", "/abc")).is_err());
+
+        assert!(validate_rule(&redirect("exact", "/about", "/about-us")).is_ok());
+        assert!(validate_rule(&redirect("prefix", "/old", "/older")).is_ok());
+        assert!(validate_rule(&redirect("prefix", "/old", "https://example.com/old")).is_ok());
+        assert!(validate_rule(&redirect("regex", r"^/blog/(\d+)$", "/blog/$1/")).is_ok());
+    }
+}
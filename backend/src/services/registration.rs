@@ -0,0 +1,622 @@
+//! Registration service: who gets an account on first login, and invitations.
+//!
+//! The `registration_policy` setting is `open`, `invite_only` or
+//! `domain_allowlist`. A pending invitation for the user's email lets them in
+//! under any policy, with the invitation's role; everyone else admitted gets
+//! `registration_default_role`. The very first user becomes admin; that check
+//! and the insert run in one `BEGIN IMMEDIATE` transaction, so two
+//! simultaneous first logins cannot both get it.
+//!
+//! Emails are only trusted for invitations and the domain allowlist when the
+//! identity provider says they are verified.
+//!
+//! A role from the identity provider's claims (see `services::role_mapping`)
+//! overrides the invitation's or default role, and is re-applied to existing
+//! users on every login.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::auth::permissions::ADMIN_ROLE;
+use crate::auth::session;
+use crate::db::models::{CreateInvitation, Invitation, User};
+use crate::error::{AppError, AppResult};
+use crate::services::{audit, roles};
+
+pub const POLICY_KEY: &str = "registration_policy";
+pub const ALLOWED_DOMAINS_KEY: &str = "registration_allowed_domains";
+pub const DEFAULT_ROLE_KEY: &str = "registration_default_role";
+
+/// Role given to users who register without an invitation while
+/// `registration_default_role` is unset — what earlier versions gave everyone.
+const DEFAULT_ROLE: &str = "editor";
+
+const INVITATION_SELECT: &str =
+    "SELECT id, email, role, invited_by, created_at, claimed_by, claimed_at FROM invitations";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Policy {
+    Open,
+    InviteOnly,
+    DomainAllowlist,
+}
+
+impl Policy {
+    fn parse(value: &str) -> AppResult<Self> {
+        match value.trim() {
+            "open" => Ok(Policy::Open),
+            "invite_only" => Ok(Policy::InviteOnly),
+            "domain_allowlist" => Ok(Policy::DomainAllowlist),
+            other => Err(AppError::BadRequest(format!(
+                "Invalid {POLICY_KEY} '{other}': expected open, invite_only or domain_allowlist"
+            ))),
+        }
+    }
+}
+
+/// Who is logging in, as reported by the identity provider.
+#[derive(Debug)]
+pub struct Identity {
+    pub external_id: String,
+    pub email: String,
+    pub display_name: String,
+    /// True only when the provider says the email is verified.
+    pub email_verified: bool,
+    /// The role the provider's claims map to, if any.
+    pub role: Option<String>,
+}
+
+/// How a new user got their account; recorded in the audit log.
+enum Admission {
+    Bootstrap,
+    Invitation(String),
+    Open,
+    Domain,
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Validates a registration setting before it is stored. Other keys pass.
+pub fn validate_setting(key: &str, value: &str) -> AppResult<()> {
+    match key {
+        POLICY_KEY => Policy::parse(value).map(drop),
+        ALLOWED_DOMAINS_KEY => parse_domains(value).map(drop),
+        _ => Ok(()),
+    }
+}
+
+/// Returns `BadRequest` unless `value`, a `registration_default_role`, names
+/// an existing role.
+pub async fn check_default_role(pool: &SqlitePool, value: &str) -> AppResult<()> {
+    roles::get_role(pool, value).await.map_err(|_| {
+        AppError::BadRequest(format!("Invalid role '{value}' in {DEFAULT_ROLE_KEY}"))
+    })?;
+    Ok(())
+}
+
+/// Returns the role users who register without an invitation get.
+pub async fn default_role(conn: impl SqliteExecutor<'_>) -> AppResult<String> {
+    let role = sqlx::query_scalar::<_, String>("SELECT value FROM site_settings WHERE key = ?")
+        .bind(DEFAULT_ROLE_KEY)
+        .fetch_optional(conn)
+        .await?;
+    Ok(role.unwrap_or_else(|| DEFAULT_ROLE.to_owned()))
+}
+
+/// Returns the id of the user for `identity`, creating the account if the
+/// registration policy allows it. Known users are let in whatever the policy,
+/// with their email, display name and mapped role refreshed. Returns
+/// `Forbidden` when a new user is not admitted.
+pub async fn sign_in(pool: &SqlitePool, identity: &Identity) -> AppResult<String> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
+    let existing = sqlx::query_as::<_, (String, String)>(
+        "SELECT id, role FROM users WHERE external_id = ? AND NOT is_service",
+    )
+    .bind(&identity.external_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some((id, old_role)) = existing {
+        let mut role = identity.role.as_deref().unwrap_or(&old_role);
+        if role != old_role && roles::is_last_admin(&mut *tx, &identity.external_id).await? {
+            tracing::warn!(
+                "Kept {} as admin: the mapped role '{role}' would leave no admin",
+                identity.external_id
+            );
+            role = &old_role;
+        }
+        sqlx::query(
+            "UPDATE users \
+             SET email = ?, display_name = ?, role = ?, \
+                 last_login = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             WHERE id = ?",
+        )
+        .bind(&identity.email)
+        .bind(&identity.display_name)
+        .bind(role)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if role != old_role {
+            apply_idp_role_change(pool, &id, &old_role, role).await?;
+        }
+        return Ok(id);
+    }
+
+    let email = identity.email.trim().to_lowercase();
+    let trusted_email = identity.email_verified && email.contains('@');
+
+    let has_users = sqlx::query_scalar::<_, i64>("SELECT EXISTS (SELECT 1 FROM users)")
+        .fetch_one(&mut *tx)
+        .await?
+        != 0;
+    let invitation = if trusted_email {
+        sqlx::query_as::<_, (String, String)>(
+            "SELECT id, role FROM invitations WHERE email = ? AND claimed_at IS NULL",
+        )
+        .bind(&email)
+        .fetch_optional(&mut *tx)
+        .await?
+    } else {
+        None
+    };
+
+    let (role, admission) = if !has_users {
+        (ADMIN_ROLE.to_owned(), Admission::Bootstrap)
+    } else if let Some((invitation_id, role)) = invitation {
+        (identity.role.clone().unwrap_or(role), Admission::Invitation(invitation_id))
+    } else {
+        let settings = load_settings(&mut tx).await?;
+        let policy = settings
+            .get(POLICY_KEY)
+            .and_then(|v| Policy::parse(v).ok())
+            .unwrap_or(Policy::InviteOnly);
+        let domains = settings
+            .get(ALLOWED_DOMAINS_KEY)
+            .and_then(|v| parse_domains(v).ok())
+            .unwrap_or_default();
+        let role = match &identity.role {
+            Some(role) => role.clone(),
+            None => default_role(&mut *tx).await?,
+        };
+        match policy {
+            Policy::Open => (role, Admission::Open),
+            Policy::DomainAllowlist if trusted_email && domain_allowed(&email, &domains) => {
+                (role, Admission::Domain)
+            }
+            _ => {
+                tracing::info!(
+                    "Registration refused for {} ({})",
+                    identity.external_id,
+                    identity.email
+                );
+                return Err(AppError::Forbidden);
+            }
+        }
+    };
+
+    let user_id = Uuid::new_v4().to_string();
+    sqlx::query(
+        "INSERT INTO users (id, external_id, email, display_name, role) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&user_id)
+    .bind(&identity.external_id)
+    .bind(&identity.email)
+    .bind(&identity.display_name)
+    .bind(&role)
+    .execute(&mut *tx)
+    .await?;
+
+    if let Admission::Invitation(invitation_id) = &admission {
+        sqlx::query(
+            "UPDATE invitations \
+             SET claimed_by = ?, claimed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             WHERE id = ?",
+        )
+        .bind(&user_id)
+        .bind(invitation_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    let mut via = match &admission {
+        Admission::Bootstrap => json!({ "role": role, "via": "bootstrap" }),
+        Admission::Invitation(id) => {
+            json!({ "role": role, "via": "invitation", "invitation_id": id })
+        }
+        Admission::Open => json!({ "role": role, "via": "open" }),
+        Admission::Domain => json!({ "role": role, "via": "domain" }),
+    };
+    if identity.role.is_some() && !matches!(admission, Admission::Bootstrap) {
+        via["role_from_idp"] = json!(true);
+    }
+    audit::log_action(pool, &user_id, "register", "user", &user_id, &via).await?;
+
+    Ok(user_id)
+}
+
+/// Returns all invitations, pending ones first, newest first.
+pub async fn list_invitations(pool: &SqlitePool) -> AppResult<Vec<Invitation>> {
+    let invitations = sqlx::query_as::<_, Invitation>(&format!(
+        "{INVITATION_SELECT} ORDER BY claimed_at IS NOT NULL, created_at DESC"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invitations)
+}
+
+/// Invites `email` to join with `role`. Returns `Conflict` if the address
+/// already has an account or a pending invitation.
+pub async fn create_invitation(
+    pool: &SqlitePool,
+    input: CreateInvitation,
+    user_id: &str,
+) -> AppResult<Invitation> {
+    let email = input.email.trim().to_lowercase();
+    if email.len() < 3 || !email.contains('@') || email.contains(char::is_whitespace) {
+        return Err(AppError::BadRequest(format!("Invalid email address '{}'", input.email)));
+    }
+    roles::get_role(pool, &input.role)
+        .await
+        .map_err(|_| AppError::BadRequest(format!("Invalid role '{}'", input.role)))?;
+
+    let registered = sqlx::query_scalar::<_, String>(
+        "SELECT id FROM users WHERE lower(email) = ? LIMIT 1",
+    )
+    .bind(&email)
+    .fetch_optional(pool)
+    .await?;
+    if registered.is_some() {
+        return Err(AppError::Conflict(format!("{} already has an account", email)));
+    }
+    let pending = sqlx::query_scalar::<_, String>(
+        "SELECT id FROM invitations WHERE email = ? AND claimed_at IS NULL",
+    )
+    .bind(&email)
+    .fetch_optional(pool)
+    .await?;
+    if pending.is_some() {
+        return Err(AppError::Conflict(format!("{} has already been invited", email)));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO invitations (id, email, role, invited_by) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&email)
+        .bind(&input.role)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    audit::log_action(
+        pool,
+        user_id,
+        "create",
+        "invitation",
+        &id,
+        &json!({ "email": email, "role": input.role }),
+    )
+    .await?;
+
+    get_invitation(pool, &id).await
+}
+
+/// Withdraws a pending invitation. Returns `NotFound` if there is no such
+/// invitation or it has already been claimed.
+pub async fn revoke_invitation(pool: &SqlitePool, id: &str, user: &User) -> AppResult<()> {
+    let invitation = get_invitation(pool, id).await?;
+    if invitation.claimed_at.is_some() {
+        return Err(AppError::NotFound);
+    }
+
+    sqlx::query("DELETE FROM invitations WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    audit::log_action(
+        pool,
+        &user.id,
+        "delete",
+        "invitation",
+        id,
+        &json!({ "email": invitation.email, "role": invitation.role }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// Records a role the identity provider's claims gave a returning user, and
+/// signs them out elsewhere if it took permissions away — as when an admin
+/// changes the role.
+async fn apply_idp_role_change(
+    pool: &SqlitePool,
+    user_id: &str,
+    old_role: &str,
+    role: &str,
+) -> AppResult<()> {
+    audit::log_action(
+        pool,
+        user_id,
+        "idp_role_change",
+        "user",
+        user_id,
+        &json!({ "role": role, "previous_role": old_role }),
+    )
+    .await?;
+
+    let permissions = roles::role_permissions(pool, role).await?;
+    if roles::is_demotion(pool, old_role, &permissions).await? {
+        session::revoke_user_sessions(pool, user_id, None, user_id).await?;
+    }
+    Ok(())
+}
+
+async fn get_invitation(pool: &SqlitePool, id: &str) -> AppResult<Invitation> {
+    sqlx::query_as::<_, Invitation>(&format!("{INVITATION_SELECT} WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)
+}
+
+async fn load_settings(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> AppResult<HashMap<String, String>> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        "SELECT key, value FROM site_settings WHERE key IN (?, ?)",
+    )
+    .bind(POLICY_KEY)
+    .bind(ALLOWED_DOMAINS_KEY)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Parses a comma-separated list of email domains, lowercased. A leading `@`
+/// is allowed and dropped.
+fn parse_domains(value: &str) -> AppResult<Vec<String>> {
+    let mut domains = Vec::new();
+    for entry in value.split(',') {
+        let domain = entry.trim().trim_start_matches('@').to_lowercase();
+        if domain.is_empty() {
+            continue;
+        }
+        let valid = domain.contains('.')
+            && domain.split('.').all(|label| !label.is_empty())
+            && domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+        if !valid {
+            return Err(AppError::BadRequest(format!(
+                "Invalid domain '{}' in {ALLOWED_DOMAINS_KEY}",
+                entry.trim()
+            )));
+        }
+        domains.push(domain);
+    }
+    Ok(domains)
+}
+
+/// Returns true when the part of `email` after the `@` is one of `domains`.
+fn domain_allowed(email: &str, domains: &[String]) -> bool {
+    email
+        .rsplit_once('@')
+        .is_some_and(|(_, domain)| domains.iter().any(|d| d == domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    /// A migrated database in a temporary file, removed when dropped. A file
+    /// rather than `:memory:` so the pool has several connections that can
+    /// race each other.
+    struct TestDb {
+        pool: SqlitePool,
+        path: PathBuf,
+    }
+
+    impl TestDb {
+        async fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("pawtal-test-{}.db", Uuid::new_v4()));
+            let url = format!("sqlite:{}?mode=rwc", path.display());
+            let pool = crate::db::create_pool(&url).await.unwrap();
+            TestDb { pool, path }
+        }
+
+        async fn set(&self, key: &str, value: &str) {
+            sqlx::query("INSERT OR REPLACE INTO site_settings (key, value) VALUES (?, ?)")
+                .bind(key)
+                .bind(value)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+        }
+
+        async fn role_of(&self, user_id: &str) -> String {
+            sqlx::query_scalar("SELECT role FROM users WHERE id = ?")
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await
+                .unwrap()
+        }
+    }
+
+    impl Drop for TestDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let mut path = self.path.clone().into_os_string();
+                path.push(suffix);
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    async fn add_session(db: &TestDb, user_id: &str) {
+        sqlx::query(
+            "INSERT INTO sessions (id, user_id, token, expires_at) \
+             VALUES (?, ?, ?, strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+1 day'))",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(Uuid::new_v4().to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    async fn session_count(db: &TestDb, user_id: &str) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+    }
+
+    fn identity(sub: &str, email: &str, email_verified: bool) -> Identity {
+        Identity {
+            external_id: sub.to_owned(),
+            email: email.to_owned(),
+            display_name: sub.to_owned(),
+            email_verified,
+            role: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn open_registration_gives_the_default_role() {
+        let db = TestDb::new().await;
+
+        let first = sign_in(&db.pool, &identity("a", "a@example.com", true)).await.unwrap();
+        assert_eq!(db.role_of(&first).await, ADMIN_ROLE);
+
+        // Upgraded sites keep admitting anyone, as editors.
+        let second = sign_in(&db.pool, &identity("b", "b@example.com", false)).await.unwrap();
+        assert_eq!(db.role_of(&second).await, "editor");
+
+        db.set(DEFAULT_ROLE_KEY, "contributor").await;
+        let third = sign_in(&db.pool, &identity("c", "c@example.com", true)).await.unwrap();
+        assert_eq!(db.role_of(&third).await, "contributor");
+
+        // Returning users keep their account and role.
+        let again = sign_in(&db.pool, &identity("b", "b@example.com", true)).await.unwrap();
+        assert_eq!(again, second);
+        assert_eq!(db.role_of(&again).await, "editor");
+    }
+
+    #[tokio::test]
+    async fn invite_only_admits_invited_verified_emails() {
+        let db = TestDb::new().await;
+        let admin = sign_in(&db.pool, &identity("a", "a@example.com", true)).await.unwrap();
+        db.set(POLICY_KEY, "invite_only").await;
+        let input = CreateInvitation {
+            email: "Bob@Example.com".to_owned(),
+            role: "contributor".to_owned(),
+        };
+        let invitation = create_invitation(&db.pool, input, &admin).await.unwrap();
+
+        let refused = sign_in(&db.pool, &identity("c", "c@example.com", true)).await;
+        assert!(matches!(refused, Err(AppError::Forbidden)));
+        // The invitation is only claimed with an email the provider verified.
+        let refused = sign_in(&db.pool, &identity("b", "bob@example.com", false)).await;
+        assert!(matches!(refused, Err(AppError::Forbidden)));
+
+        let bob = sign_in(&db.pool, &identity("b", "bob@example.com", true)).await.unwrap();
+        assert_eq!(db.role_of(&bob).await, "contributor");
+        let claimed = get_invitation(&db.pool, &invitation.id).await.unwrap();
+        assert_eq!(claimed.claimed_by.as_deref(), Some(bob.as_str()));
+
+        // Existing users are let in whatever the policy.
+        assert_eq!(sign_in(&db.pool, &identity("a", "", false)).await.unwrap(), admin);
+    }
+
+    #[tokio::test]
+    async fn simultaneous_first_logins_make_one_admin() {
+        let db = TestDb::new().await;
+
+        let logins = (0..5).map(|n| {
+            let pool = db.pool.clone();
+            tokio::spawn(async move {
+                let sub = format!("user{n}");
+                sign_in(&pool, &identity(&sub, &format!("{sub}@example.com"), true)).await
+            })
+        });
+        for login in logins.collect::<Vec<_>>() {
+            login.await.unwrap().unwrap();
+        }
+
+        let admins: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = ?")
+            .bind(ADMIN_ROLE)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(admins, 1);
+    }
+
+    #[tokio::test]
+    async fn unmatched_users_lose_roles_the_mapping_gave() {
+        use crate::services::role_mapping;
+
+        let db = TestDb::new().await;
+        let admin = sign_in(&db.pool, &identity("a", "a@example.com", true)).await.unwrap();
+        db.set(DEFAULT_ROLE_KEY, "contributor").await;
+        db.set(
+            role_mapping::MAPPINGS_KEY,
+            r#"[{ "claim": "groups", "value": "editors", "role": "editor" }]"#,
+        )
+        .await;
+        let input = CreateInvitation {
+            email: "c@example.com".to_owned(),
+            role: "editor".to_owned(),
+        };
+        create_invitation(&db.pool, input, &admin).await.unwrap();
+
+        let login = |sub: &'static str, groups: Value| {
+            let pool = db.pool.clone();
+            async move {
+                let claims = json!({ "groups": groups }).as_object().unwrap().clone();
+                let mut identity = identity(sub, &format!("{sub}@example.com"), true);
+                identity.role = role_mapping::resolve(&pool, &claims, sub).await?;
+                sign_in(&pool, &identity).await
+            }
+        };
+
+        let b = login("b", json!(["editors"])).await.unwrap();
+        assert_eq!(db.role_of(&b).await, "editor");
+        add_session(&db, &b).await;
+
+        // Leaving the group takes the mapped role away, and with it the
+        // sessions that still carry it.
+        login("b", json!([])).await.unwrap();
+        assert_eq!(db.role_of(&b).await, "contributor");
+        assert_eq!(session_count(&db, &b).await, 0);
+
+        // A role given in Pawtal stays.
+        let c = login("c", json!([])).await.unwrap();
+        login("c", json!([])).await.unwrap();
+        assert_eq!(db.role_of(&c).await, "editor");
+
+        // A required mapping refuses the login and ends the user's sessions.
+        db.set(role_mapping::REQUIRED_KEY, "true").await;
+        add_session(&db, &b).await;
+        assert!(matches!(login("b", json!([])).await, Err(AppError::Forbidden)));
+        assert_eq!(session_count(&db, &b).await, 0);
+        assert_eq!(login("a", json!([])).await.unwrap(), admin);
+    }
+}
@@ -0,0 +1,62 @@
+//! Content formats and server-side rendering.
+//!
+//! Pages and articles keep their body as written in `content`, tagged with a
+//! `content_format` of `html` or `markdown`. Every write renders it to HTML,
+//! sanitizes the result (see `services::sanitize`) and stores it in
+//! `content_html`, which is what the public site displays, what search
+//! indexes and what reading time is estimated from. Editors keep working on
+//! the source, which for HTML is sanitized too.
+
+use pulldown_cmark::{html, Options, Parser};
+
+use crate::error::{AppError, AppResult};
+use crate::services::sanitize::SanitizePolicy;
+
+/// Format used when a create request does not specify one.
+pub const DEFAULT_FORMAT: &str = "html";
+
+const VALID_FORMATS: &[&str] = &["html", "markdown"];
+
+/// Returns `BadRequest` for unknown content formats.
+pub fn validate_format(format: &str) -> AppResult<()> {
+    if VALID_FORMATS.contains(&format) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "Invalid content_format '{}'. Must be one of: {}",
+            format,
+            VALID_FORMATS.join(", ")
+        )))
+    }
+}
+
+/// Returns the source to store for `content` written in `format`: HTML is
+/// sanitized so the editor never holds markup the site would not serve;
+/// Markdown is kept as written.
+pub fn sanitize_source(content: &str, format: &str, policy: &SanitizePolicy) -> String {
+    match format {
+        "markdown" => content.to_owned(),
+        _ => policy.clean(content),
+    }
+}
+
+/// Renders `content` written in `format` to sanitized HTML.
+pub fn render(content: &str, format: &str, policy: &SanitizePolicy) -> String {
+    match format {
+        "markdown" => policy.clean(&markdown_to_html(content)),
+        _ => policy.clean(content),
+    }
+}
+
+/// CommonMark plus the GitHub extensions people expect: tables,
+/// strikethrough, task lists and footnotes. Raw HTML in the source passes
+/// through here and is dealt with by the sanitizer.
+fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let mut out = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut out, Parser::new_ext(markdown, options));
+    out
+}
@@ -0,0 +1,197 @@
+//! Editorial review policy shared by pages and articles.
+//!
+//! Contributors write drafts and submit them for review (`in_review`). Users
+//! whose role grants publishing approve them — which publishes or schedules
+//! the content — or reject them back to `draft` with a comment. Only they may
+//! put content live directly: `content.publish_any` for anyone's content,
+//! `content.publish_own` for content they wrote. The state transitions
+//! themselves live in the pages and articles services.
+//!
+//! Edits to published content wait in a draft overlay, so it is the draft that
+//! goes through review there: the live version stays up meanwhile.
+
+use chrono::{DateTime, Utc};
+
+use crate::auth::permissions::{self, CONTENT_PUBLISH_ANY, CONTENT_PUBLISH_OWN};
+use crate::db::models::User;
+use crate::error::{AppError, AppResult};
+
+/// Statuses that make content visible (now or at `publish_at`) and therefore
+/// require review rights to enter or leave.
+const LIVE_STATUSES: &[&str] = &["published", "scheduled"];
+
+/// Returns true when the user may approve, reject and publish content;
+/// `own` says whether they wrote it.
+pub fn can_publish(user: &User, own: bool) -> bool {
+    permissions::has(user, CONTENT_PUBLISH_ANY)
+        || (own && permissions::has(user, CONTENT_PUBLISH_OWN))
+}
+
+/// Rejects a create or update that would put content live, or take it down,
+/// without review.
+///
+/// `current` is the existing status (`None` on create) and `own` whether the
+/// user wrote the content. Keeping an already published item published is
+/// allowed, so contributors can still fix typos on live content.
+pub fn ensure_can_set_status(
+    user: &User,
+    own: bool,
+    current: Option<&str>,
+    requested: &str,
+) -> AppResult<()> {
+    if current == Some(requested) {
+        return Ok(());
+    }
+    let going_live = LIVE_STATUSES.contains(&requested);
+    let leaving_live = current.is_some_and(|current| LIVE_STATUSES.contains(&current));
+    if (going_live || leaving_live) && !can_publish(user, own) {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Rejects a change to the publish or unpublish time of content that is
+/// scheduled or published unless the user may publish it: moving the window
+/// in which it is live is a publishing decision, even when the status stays
+/// the same.
+pub fn ensure_can_reschedule(
+    user: &User,
+    own: bool,
+    current: &str,
+    window_changed: bool,
+) -> AppResult<()> {
+    if window_changed && LIVE_STATUSES.contains(&current) && !can_publish(user, own) {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Status an approved item moves to: `scheduled` when it carries a future
+/// publish time, otherwise `published`.
+pub fn approved_status(publish_at: Option<DateTime<Utc>>) -> &'static str {
+    match publish_at {
+        Some(at) if at > Utc::now() => "scheduled",
+        _ => "published",
+    }
+}
+
+/// What a review step applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The item itself, through its `draft` and `in_review` statuses.
+    Content,
+    /// The pending draft overlay of a published item.
+    Draft,
+}
+
+/// Decides what submitting an item for review submits: a draft item itself,
+/// or the pending draft of a published one.
+pub fn submission_target(status: &str, has_draft: bool) -> AppResult<Target> {
+    match status {
+        "draft" => Ok(Target::Content),
+        "published" if has_draft => Ok(Target::Draft),
+        _ => Err(AppError::BadRequest(
+            "Only drafts and pending edits to published content can be submitted for review"
+                .to_owned(),
+        )),
+    }
+}
+
+/// Decides what approving or rejecting an item acts on, ensuring something is
+/// waiting for review: the item in `in_review`, or its submitted draft.
+pub fn review_target(status: &str, draft_in_review: bool) -> AppResult<Target> {
+    match status {
+        "in_review" => Ok(Target::Content),
+        "published" if draft_in_review => Ok(Target::Draft),
+        _ => Err(AppError::BadRequest(
+            "Only content that is in review can be approved or rejected".to_owned(),
+        )),
+    }
+}
+
+/// Validates and normalises a rejection comment, which is mandatory so the
+/// author knows what to change.
+pub fn rejection_comment(comment: &str) -> AppResult<String> {
+    let comment = comment.trim();
+    if comment.is_empty() {
+        return Err(AppError::BadRequest(
+            "A comment is required when rejecting".to_owned(),
+        ));
+    }
+    Ok(comment.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(permissions: &[&str]) -> User {
+        User {
+            id: "u1".to_owned(),
+            external_id: "ext-u1".to_owned(),
+            email: "u1@example.com".to_owned(),
+            display_name: "U1".to_owned(),
+            role: "custom".to_owned(),
+            created_at: Utc::now(),
+            last_login: Utc::now(),
+            is_service: false,
+            permissions: permissions.iter().map(|p| (*p).to_owned()).collect(),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn only_publishers_move_content_in_or_out_of_live_statuses() {
+        let contributor = user(&[]);
+        let editor = user(&[CONTENT_PUBLISH_ANY]);
+
+        assert!(ensure_can_set_status(&contributor, true, None, "draft").is_ok());
+        assert!(ensure_can_set_status(&contributor, true, Some("draft"), "in_review").is_ok());
+        assert!(ensure_can_set_status(&contributor, true, Some("published"), "published").is_ok());
+
+        for (current, requested) in [
+            (None, "published"),
+            (Some("draft"), "scheduled"),
+            (Some("published"), "draft"),
+            (Some("published"), "archived"),
+            (Some("scheduled"), "draft"),
+            (Some("published"), "scheduled"),
+        ] {
+            assert!(matches!(
+                ensure_can_set_status(&contributor, true, current, requested),
+                Err(AppError::Forbidden)
+            ));
+            assert!(ensure_can_set_status(&editor, true, current, requested).is_ok());
+        }
+    }
+
+    #[test]
+    fn only_publishers_move_the_live_window() {
+        let contributor = user(&[]);
+        let editor = user(&[CONTENT_PUBLISH_OWN]);
+
+        for current in ["published", "scheduled"] {
+            assert!(ensure_can_reschedule(&contributor, true, current, false).is_ok());
+            assert!(matches!(
+                ensure_can_reschedule(&contributor, true, current, true),
+                Err(AppError::Forbidden)
+            ));
+            assert!(ensure_can_reschedule(&editor, true, current, true).is_ok());
+        }
+        assert!(ensure_can_reschedule(&contributor, true, "draft", true).is_ok());
+    }
+
+    #[test]
+    fn drafts_of_published_content_go_through_review() {
+        assert_eq!(submission_target("draft", false).unwrap(), Target::Content);
+        assert_eq!(submission_target("published", true).unwrap(), Target::Draft);
+        assert!(submission_target("published", false).is_err());
+        assert!(submission_target("in_review", false).is_err());
+        assert!(submission_target("scheduled", true).is_err());
+
+        assert_eq!(review_target("in_review", false).unwrap(), Target::Content);
+        assert_eq!(review_target("published", true).unwrap(), Target::Draft);
+        assert!(review_target("published", false).is_err());
+        assert!(review_target("draft", false).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Revision history policy shared by pages and articles.
+//!
+//! Every update stores a full snapshot, optionally labelled with a message.
+//! Revisions can be pinned as named versions ("Launch copy", "v2"), and the
+//! background task thins old unpinned revisions according to the retention
+//! policy in site settings:
+//!
+//! - everything newer than `revision_keep_all_days` is kept;
+//! - up to `revision_keep_daily_days`, the last revision of each day is kept;
+//! - up to `revision_keep_weekly_days`, the last revision of each week is
+//!   kept; older ones are deleted (`0` keeps weekly revisions forever).
+//!
+//! Pinned revisions and the latest revision of each item are never deleted.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use crate::services::audit;
+
+/// Settings keys holding the retention windows, in days.
+pub const KEEP_ALL_DAYS_KEY: &str = "revision_keep_all_days";
+pub const KEEP_DAILY_DAYS_KEY: &str = "revision_keep_daily_days";
+pub const KEEP_WEEKLY_DAYS_KEY: &str = "revision_keep_weekly_days";
+
+const MAX_MESSAGE_LEN: usize = 500;
+const MAX_VERSION_NAME_LEN: usize = 100;
+
+/// Revision table and owner column for an entity type.
+fn revision_table(entity_type: &str) -> (&'static str, &'static str) {
+    match entity_type {
+        "page" => ("page_revisions", "page_id"),
+        _ => ("article_revisions", "article_id"),
+    }
+}
+
+/// Trims a revision message, treating blank as absent.
+pub fn normalize_message(message: Option<String>) -> AppResult<Option<String>> {
+    let Some(message) = message else {
+        return Ok(None);
+    };
+    let message = message.trim();
+    if message.chars().count() > MAX_MESSAGE_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Revision message must be at most {MAX_MESSAGE_LEN} characters"
+        )));
+    }
+    Ok((!message.is_empty()).then(|| message.to_owned()))
+}
+
+/// Pins a revision under `name`, replacing any name it already had.
+pub async fn pin(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    revision_id: &str,
+    name: &str,
+    user_id: &str,
+) -> AppResult<()> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_VERSION_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Version name must be 1 to {MAX_VERSION_NAME_LEN} characters"
+        )));
+    }
+
+    let (table, owner) = revision_table(entity_type);
+    let taken = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT id FROM {table} WHERE {owner} = ? AND version_name = ? AND id != ?"
+    ))
+    .bind(entity_id)
+    .bind(name)
+    .bind(revision_id)
+    .fetch_optional(pool)
+    .await?;
+    if taken.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Another revision is already named '{}'",
+            name
+        )));
+    }
+
+    set_version_name(pool, entity_type, entity_id, revision_id, Some(name)).await?;
+
+    audit::log_action(
+        pool,
+        user_id,
+        "pin_revision",
+        entity_type,
+        entity_id,
+        &json!({ "revision_id": revision_id, "name": name }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Removes a revision's name, making it subject to retention again.
+pub async fn unpin(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    revision_id: &str,
+    user_id: &str,
+) -> AppResult<()> {
+    set_version_name(pool, entity_type, entity_id, revision_id, None).await?;
+
+    audit::log_action(
+        pool,
+        user_id,
+        "unpin_revision",
+        entity_type,
+        entity_id,
+        &json!({ "revision_id": revision_id }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn set_version_name(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    revision_id: &str,
+    name: Option<&str>,
+) -> AppResult<()> {
+    let (table, owner) = revision_table(entity_type);
+    let result = sqlx::query(&format!(
+        "UPDATE {table} SET version_name = ? WHERE id = ? AND {owner} = ?"
+    ))
+    .bind(name)
+    .bind(revision_id)
+    .bind(entity_id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(())
+}
+
+// ─── Retention ────────────────────────────────────────────────────────────────
+
+/// Retention windows in days, read from site settings.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub keep_all_days: u32,
+    pub keep_daily_days: u32,
+    /// `0` keeps one revision per week forever.
+    pub keep_weekly_days: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_all_days: 7,
+            keep_daily_days: 30,
+            keep_weekly_days: 365,
+        }
+    }
+}
+
+/// Validates a retention setting value: a whole number of days.
+pub fn parse_days(key: &str, value: &str) -> AppResult<u32> {
+    value.trim().parse::<u32>().map_err(|_| {
+        AppError::BadRequest(format!("{key} must be a whole number of days"))
+    })
+}
+
+/// Reads the retention policy from site settings. Missing or malformed values
+/// fall back to the defaults, and the windows are made to nest (daily never
+/// ends before keep-all, weekly never before daily).
+pub async fn retention_policy(pool: &SqlitePool) -> AppResult<RetentionPolicy> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        "SELECT key, value FROM site_settings WHERE key IN (?, ?, ?)",
+    )
+    .bind(KEEP_ALL_DAYS_KEY)
+    .bind(KEEP_DAILY_DAYS_KEY)
+    .bind(KEEP_WEEKLY_DAYS_KEY)
+    .fetch_all(pool)
+    .await?;
+    let values: HashMap<String, String> = rows.into_iter().collect();
+
+    let defaults = RetentionPolicy::default();
+    let get = |key: &str, default: u32| {
+        values
+            .get(key)
+            .and_then(|v| parse_days(key, v).ok())
+            .unwrap_or(default)
+    };
+
+    let keep_all_days = get(KEEP_ALL_DAYS_KEY, defaults.keep_all_days);
+    let keep_daily_days = get(KEEP_DAILY_DAYS_KEY, defaults.keep_daily_days).max(keep_all_days);
+    let keep_weekly_days = match get(KEEP_WEEKLY_DAYS_KEY, defaults.keep_weekly_days) {
+        0 => 0,
+        days => days.max(keep_daily_days),
+    };
+
+    Ok(RetentionPolicy {
+        keep_all_days,
+        keep_daily_days,
+        keep_weekly_days,
+    })
+}
+
+/// Deletes unpinned revisions the policy no longer keeps. Returns the number
+/// of revisions removed across pages and articles.
+pub async fn prune(pool: &SqlitePool, policy: RetentionPolicy) -> AppResult<u64> {
+    let mut removed = 0;
+
+    for entity_type in ["page", "article"] {
+        let (table, owner) = revision_table(entity_type);
+        // Each revision is ranked within its item overall, within its day and
+        // within its week (newest first). Outside the keep-all window only the
+        // newest of each day survives; outside the daily window only the
+        // newest of each week, until the weekly window ends too.
+        let result = sqlx::query(&format!(
+            "DELETE FROM {table} WHERE id IN ( \
+                 SELECT id FROM ( \
+                     SELECT id, version_name, created_at, \
+                         ROW_NUMBER() OVER ( \
+                             PARTITION BY {owner} \
+                             ORDER BY created_at DESC, rowid DESC) AS item_rank, \
+                         ROW_NUMBER() OVER ( \
+                             PARTITION BY {owner}, strftime('%Y-%m-%d', created_at) \
+                             ORDER BY created_at DESC, rowid DESC) AS day_rank, \
+                         ROW_NUMBER() OVER ( \
+                             PARTITION BY {owner}, strftime('%Y-%W', created_at) \
+                             ORDER BY created_at DESC, rowid DESC) AS week_rank \
+                     FROM {table} \
+                 ) \
+                 WHERE version_name IS NULL \
+                   AND item_rank > 1 \
+                   AND created_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?1) \
+                   AND ( \
+                       (created_at >= strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?2) \
+                           AND day_rank > 1) \
+                       OR (created_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?2) \
+                           AND (week_rank > 1 \
+                               OR (?3 > 0 \
+                                   AND created_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?4)))) \
+                   ) \
+             )"
+        ))
+        .bind(format!("-{} days", policy.keep_all_days))
+        .bind(format!("-{} days", policy.keep_daily_days))
+        .bind(policy.keep_weekly_days)
+        .bind(format!("-{} days", policy.keep_weekly_days))
+        .execute(pool)
+        .await?;
+
+        removed += result.rows_affected();
+    }
+
+    Ok(removed)
+}
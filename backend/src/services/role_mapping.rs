@@ -0,0 +1,288 @@
+//! Identity provider role mapping: roles decided by the IdP's claims.
+//!
+//! The `idp_role_mappings` setting is a JSON list of rules, tried in order:
+//!
+//! ```json
+//! [{ "claim": "groups", "value": "pawtal-admins", "role": "admin" }]
+//! ```
+//!
+//! A rule matches when the claim equals `value` or, for a list, contains it.
+//! Claims come from the ID token and userinfo; a `claim` containing dots that
+//! is not itself a claim name is followed into nested objects, as in
+//! `realm_access.roles`. The first matching rule's role is applied on every
+//! login, replacing the user's role (see `registration::sign_in`). With
+//! `idp_role_mapping_required` set to `true`, logins matching no rule are
+//! refused and the user's other sessions ended. Otherwise those users keep a
+//! role given in Pawtal, but a role the mapping gave them falls back to
+//! `registration_default_role`, so leaving a group takes its role away. No
+//! rules, no mapping.
+//!
+//! Neither takes the admin role from the last admin: they keep it and can
+//! still sign in to fix the mapping.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+
+use crate::auth::session;
+use crate::error::{AppError, AppResult};
+use crate::services::{registration, roles};
+
+pub const MAPPINGS_KEY: &str = "idp_role_mappings";
+pub const REQUIRED_KEY: &str = "idp_role_mapping_required";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    claim: String,
+    value: String,
+    role: String,
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Validates a role mapping setting before it is stored. Other keys pass.
+pub fn validate_setting(key: &str, value: &str) -> AppResult<()> {
+    match key {
+        MAPPINGS_KEY => parse_rules(value).map(drop),
+        REQUIRED_KEY => match value {
+            "true" | "false" => Ok(()),
+            other => Err(AppError::BadRequest(format!(
+                "Invalid {REQUIRED_KEY} '{other}': expected true or false"
+            ))),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Returns `BadRequest` unless every role the rules in `value` give exists.
+pub async fn check_roles(pool: &SqlitePool, value: &str) -> AppResult<()> {
+    for rule in parse_rules(value)? {
+        roles::get_role(pool, &rule.role).await.map_err(|_| {
+            AppError::BadRequest(format!("Invalid role '{}' in {MAPPINGS_KEY}", rule.role))
+        })?;
+    }
+    Ok(())
+}
+
+/// Returns true when some rule in the stored mapping gives `role`.
+pub async fn maps_to(pool: &SqlitePool, role: &str) -> AppResult<bool> {
+    let rules = load_rules(pool).await?;
+    Ok(rules.iter().any(|rule| rule.role == role))
+}
+
+/// Returns the role the identity provider's `claims` about `external_id` map
+/// to, or `None` when mapping is off or no rule matches and matching is
+/// optional. A user matching no rule whose role came from the mapping gets
+/// the default role instead. Returns `Forbidden`, ending the user's sessions,
+/// when matching is required and no rule matches, unless the user is the last
+/// admin.
+pub async fn resolve(
+    pool: &SqlitePool,
+    claims: &Map<String, Value>,
+    external_id: &str,
+) -> AppResult<Option<String>> {
+    let rules = load_rules(pool).await?;
+    if rules.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(rule) = rules.iter().find(|rule| rule_matches(rule, claims)) {
+        return Ok(Some(rule.role.clone()));
+    }
+
+    let required = sqlx::query_scalar::<_, String>("SELECT value FROM site_settings WHERE key = ?")
+        .bind(REQUIRED_KEY)
+        .fetch_optional(pool)
+        .await?
+        .is_some_and(|v| v == "true");
+    if required && roles::is_last_admin(pool, external_id).await? {
+        tracing::warn!("Admitted {external_id}, the last admin, though no role mapping matches");
+    } else if required {
+        tracing::info!("Login refused: no role mapping matches the identity provider's claims");
+        if let Some((user_id, _)) = find_user(pool, external_id).await? {
+            session::revoke_user_sessions(pool, &user_id, None, &user_id).await?;
+        }
+        return Err(AppError::Forbidden);
+    }
+
+    match find_user(pool, external_id).await? {
+        Some((user_id, role)) if granted_by_mapping(pool, &user_id, &role).await? => {
+            Ok(Some(registration::default_role(pool).await?))
+        }
+        _ => Ok(None),
+    }
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+async fn load_rules(pool: &SqlitePool) -> AppResult<Vec<Rule>> {
+    let value = sqlx::query_scalar::<_, String>("SELECT value FROM site_settings WHERE key = ?")
+        .bind(MAPPINGS_KEY)
+        .fetch_optional(pool)
+        .await?;
+    // Stored values were validated on write; an unreadable one maps nothing.
+    Ok(value.and_then(|v| parse_rules(&v).ok()).unwrap_or_default())
+}
+
+/// Returns the id and role of the user signing in as `external_id`, if known.
+async fn find_user(pool: &SqlitePool, external_id: &str) -> AppResult<Option<(String, String)>> {
+    let user = sqlx::query_as::<_, (String, String)>(
+        "SELECT id, role FROM users WHERE external_id = ? AND NOT is_service",
+    )
+    .bind(external_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(user)
+}
+
+/// Returns true when the mapping, rather than an admin or an invitation, gave
+/// the user `role`: the audit log's latest change of their role came from
+/// the identity provider's claims.
+async fn granted_by_mapping(pool: &SqlitePool, user_id: &str, role: &str) -> AppResult<bool> {
+    let granted = sqlx::query_scalar::<_, bool>(
+        "SELECT (action = 'idp_role_change' OR json_extract(details, '$.role_from_idp') = 1) \
+                AND json_extract(details, '$.role') = ? \
+         FROM audit_log \
+         WHERE entity_type = 'user' AND entity_id = ? \
+           AND action IN ('register', 'update_role', 'idp_role_change') \
+         ORDER BY created_at DESC, rowid DESC LIMIT 1",
+    )
+    .bind(role)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(granted.unwrap_or(false))
+}
+
+fn parse_rules(value: &str) -> AppResult<Vec<Rule>> {
+    if value.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let rules: Vec<Rule> = serde_json::from_str(value).map_err(|e| {
+        AppError::BadRequest(format!("Invalid {MAPPINGS_KEY}: {e}"))
+    })?;
+    if let Some(rule) = rules
+        .iter()
+        .find(|r| r.claim.trim().is_empty() || r.value.is_empty() || r.role.trim().is_empty())
+    {
+        return Err(AppError::BadRequest(format!(
+            "Invalid {MAPPINGS_KEY}: claim, value and role must not be empty ({rule:?})"
+        )));
+    }
+    Ok(rules)
+}
+
+fn rule_matches(rule: &Rule, claims: &Map<String, Value>) -> bool {
+    lookup(claims, &rule.claim).is_some_and(|value| value_matches(value, &rule.value))
+}
+
+/// Finds a claim by name, or else by following a dotted path.
+fn lookup<'a>(claims: &'a Map<String, Value>, claim: &str) -> Option<&'a Value> {
+    if let Some(value) = claims.get(claim) {
+        return Some(value);
+    }
+    let mut parts = claim.split('.');
+    let mut value = claims.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+fn value_matches(value: &Value, expected: &str) -> bool {
+    match value {
+        Value::String(s) => s == expected,
+        Value::Array(items) => items.iter().any(|item| value_matches(item, expected)),
+        Value::Bool(_) | Value::Number(_) => {
+            serde_json::from_str::<Value>(expected).is_ok_and(|parsed| parsed == *value)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rules_match_claims() {
+        let claims = json!({
+            "groups": ["staff", "pawtal-admins"],
+            "department": "news",
+            "email_verified": true,
+            "realm_access": { "roles": ["editor"] },
+            "https://example.com/team": "web",
+        });
+        let claims = claims.as_object().unwrap();
+        let rule = |claim: &str, value: &str| Rule {
+            claim: claim.into(),
+            value: value.into(),
+            role: "admin".into(),
+        };
+
+        assert!(rule_matches(&rule("groups", "pawtal-admins"), claims));
+        assert!(!rule_matches(&rule("groups", "pawtal"), claims));
+        assert!(rule_matches(&rule("department", "news"), claims));
+        assert!(rule_matches(&rule("email_verified", "true"), claims));
+        assert!(rule_matches(&rule("realm_access.roles", "editor"), claims));
+        assert!(rule_matches(&rule("https://example.com/team", "web"), claims));
+        assert!(!rule_matches(&rule("missing", "news"), claims));
+    }
+
+    #[test]
+    fn rules_match_standard_claims() {
+        // What the callback hands over: the ID token's claims, then userinfo's.
+        let mut claims = json!({
+            "iss": "https://idp.example/",
+            "sub": "f3a1c2",
+            "aud": "pawtal",
+            "exp": 1_700_000_300,
+            "nonce": "n-1",
+            "email": "old@example.com",
+            "groups": ["staff"],
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let userinfo = json!({
+            "sub": "f3a1c2",
+            "email": "ada@example.com",
+            "email_verified": true,
+            "name": "Ada Lovelace",
+            "preferred_username": "ada",
+            "groups": ["staff", "pawtal-admins"],
+        });
+        claims.extend(userinfo.as_object().unwrap().clone());
+        let rule = |claim: &str, value: &str| Rule {
+            claim: claim.into(),
+            value: value.into(),
+            role: "admin".into(),
+        };
+
+        assert!(rule_matches(&rule("email", "ada@example.com"), &claims));
+        assert!(!rule_matches(&rule("email", "old@example.com"), &claims));
+        assert!(rule_matches(&rule("email_verified", "true"), &claims));
+        assert!(rule_matches(&rule("name", "Ada Lovelace"), &claims));
+        assert!(rule_matches(&rule("preferred_username", "ada"), &claims));
+        assert!(rule_matches(&rule("sub", "f3a1c2"), &claims));
+        assert!(rule_matches(&rule("aud", "pawtal"), &claims));
+        assert!(rule_matches(&rule("groups", "pawtal-admins"), &claims));
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        assert!(validate_setting(MAPPINGS_KEY, "[]").is_ok());
+        assert!(validate_setting(
+            MAPPINGS_KEY,
+            r#"[{"claim": "groups", "value": "pawtal-admins", "role": "admin"}]"#
+        )
+        .is_ok());
+        let missing_value = r#"[{"claim": "groups", "role": "admin"}]"#;
+        assert!(validate_setting(MAPPINGS_KEY, missing_value).is_err());
+        let empty_claim = r#"[{"claim": "", "value": "x", "role": "admin"}]"#;
+        assert!(validate_setting(MAPPINGS_KEY, empty_claim).is_err());
+        assert!(validate_setting(REQUIRED_KEY, "yes").is_err());
+    }
+}
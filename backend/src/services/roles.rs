@@ -0,0 +1,333 @@
+//! Roles service.
+//!
+//! Roles are named sets of permissions from `auth::permissions`. The
+//! built-in roles (admin, editor, author, contributor, viewer) are seeded by
+//! a migration and cannot be changed; admins may add their own roles and
+//! assign any role to a user.
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{SqliteExecutor, SqlitePool};
+
+use crate::auth::permissions::{self, ADMIN_ROLE};
+use crate::auth::session;
+use crate::db::models::{CreateRole, PermissionInfo, Role, UpdateRole, User};
+use crate::error::{AppError, AppResult};
+use crate::services::{audit, registration, role_mapping};
+
+const MAX_NAME_LEN: usize = 40;
+
+/// `roles` as stored, with the permissions still encoded as JSON.
+#[derive(sqlx::FromRow)]
+struct RoleRow {
+    name: String,
+    description: String,
+    permissions: String,
+    builtin: bool,
+    user_count: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+const ROLE_SELECT: &str =
+    "SELECT name, description, permissions, builtin, \
+            (SELECT COUNT(*) FROM users WHERE users.role = roles.name) AS user_count, \
+            created_at, updated_at \
+     FROM roles";
+
+impl RoleRow {
+    fn into_role(self) -> Role {
+        Role {
+            permissions: decode(&self.name, &self.permissions),
+            name: self.name,
+            description: self.description,
+            builtin: self.builtin,
+            user_count: self.user_count,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns the permission set roles are built from.
+pub fn list_permissions() -> Vec<PermissionInfo> {
+    permissions::ALL
+        .iter()
+        .map(|&(name, description)| PermissionInfo { name, description })
+        .collect()
+}
+
+/// Returns all roles, built-in ones first, then by name.
+pub async fn list_roles(pool: &SqlitePool) -> AppResult<Vec<Role>> {
+    let rows = sqlx::query_as::<_, RoleRow>(&format!(
+        "{ROLE_SELECT} ORDER BY builtin DESC, name ASC"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(RoleRow::into_role).collect())
+}
+
+/// Fetches a single role by name. Returns `NotFound` if absent.
+pub async fn get_role(pool: &SqlitePool, name: &str) -> AppResult<Role> {
+    sqlx::query_as::<_, RoleRow>(&format!("{ROLE_SELECT} WHERE name = ?"))
+        .bind(name)
+        .fetch_optional(pool)
+        .await?
+        .map(RoleRow::into_role)
+        .ok_or(AppError::NotFound)
+}
+
+/// Returns the permissions a role grants; empty for unknown roles.
+pub async fn role_permissions(pool: &SqlitePool, name: &str) -> AppResult<Vec<String>> {
+    let stored = sqlx::query_scalar::<_, String>("SELECT permissions FROM roles WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+    Ok(stored.map(|s| decode(name, &s)).unwrap_or_default())
+}
+
+/// Creates a custom role. Returns `Conflict` if the name is taken.
+pub async fn create_role(pool: &SqlitePool, input: CreateRole, user_id: &str) -> AppResult<Role> {
+    let name = validate_name(&input.name)?;
+    let permissions = validate_permissions(input.permissions)?;
+
+    let taken = sqlx::query_scalar::<_, String>("SELECT name FROM roles WHERE name = ?")
+        .bind(&name)
+        .fetch_optional(pool)
+        .await?;
+    if taken.is_some() {
+        return Err(AppError::Conflict(format!("A role named '{}' already exists", name)));
+    }
+
+    sqlx::query("INSERT INTO roles (name, description, permissions) VALUES (?, ?, ?)")
+        .bind(&name)
+        .bind(input.description.unwrap_or_default().trim())
+        .bind(json!(permissions).to_string())
+        .execute(pool)
+        .await?;
+
+    audit::log_action(pool, user_id, "create", "role", &name, &json!({ "permissions": permissions }))
+        .await?;
+
+    get_role(pool, &name).await
+}
+
+/// Changes a custom role's description and/or permissions. Users holding the
+/// role get the new permissions with their next request.
+pub async fn update_role(
+    pool: &SqlitePool,
+    name: &str,
+    input: UpdateRole,
+    user_id: &str,
+) -> AppResult<Role> {
+    let existing = get_role(pool, name).await?;
+    ensure_custom(&existing)?;
+
+    let permissions = match input.permissions {
+        Some(permissions) => validate_permissions(permissions)?,
+        None => existing.permissions,
+    };
+    let description = match input.description {
+        Some(description) => description.trim().to_owned(),
+        None => existing.description,
+    };
+
+    sqlx::query(
+        "UPDATE roles \
+         SET description = ?, permissions = ?, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         WHERE name = ?",
+    )
+    .bind(&description)
+    .bind(json!(permissions).to_string())
+    .bind(name)
+    .execute(pool)
+    .await?;
+
+    audit::log_action(pool, user_id, "update", "role", name, &json!({ "permissions": permissions }))
+        .await?;
+
+    get_role(pool, name).await
+}
+
+/// Deletes a custom role. Returns `Conflict` while users still hold it, or
+/// pending invitations, new registrations or the role mapping grant it.
+pub async fn delete_role(pool: &SqlitePool, name: &str, user_id: &str) -> AppResult<()> {
+    let existing = get_role(pool, name).await?;
+    ensure_custom(&existing)?;
+    if existing.user_count > 0 {
+        return Err(AppError::Conflict(format!(
+            "The role '{}' is still assigned to {} user(s)",
+            name, existing.user_count
+        )));
+    }
+    let invited = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM invitations WHERE role = ? AND claimed_at IS NULL",
+    )
+    .bind(name)
+    .fetch_one(pool)
+    .await?;
+    if invited > 0 {
+        return Err(AppError::Conflict(format!(
+            "The role '{}' is granted by {} pending invitation(s)",
+            name, invited
+        )));
+    }
+    if registration::default_role(pool).await? == name {
+        return Err(AppError::Conflict(format!(
+            "The role '{}' is given to new users by {}",
+            name,
+            registration::DEFAULT_ROLE_KEY
+        )));
+    }
+    if role_mapping::maps_to(pool, name).await? {
+        return Err(AppError::Conflict(format!(
+            "The role '{}' is given by the identity provider role mapping",
+            name
+        )));
+    }
+
+    sqlx::query("DELETE FROM roles WHERE name = ?")
+        .bind(name)
+        .execute(pool)
+        .await?;
+
+    audit::log_action(pool, user_id, "delete", "role", name, &json!({})).await?;
+
+    Ok(())
+}
+
+/// Assigns a role to a user. Users cannot change their own role, so an admin
+/// cannot lock themselves out. When the new role lacks any permission of the
+/// old one, the user's sessions are ended so they sign in again under it.
+pub async fn set_user_role(
+    pool: &SqlitePool,
+    id: &str,
+    role: &str,
+    acting_user: &User,
+) -> AppResult<User> {
+    if id == acting_user.id && role != acting_user.role {
+        return Err(AppError::BadRequest("You cannot change your own role.".to_owned()));
+    }
+    let new_role = get_role(pool, role)
+        .await
+        .map_err(|_| AppError::BadRequest(format!("Invalid role '{}'", role)))?;
+    let old_role = sqlx::query_scalar::<_, String>("SELECT role FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let demoted = is_demotion(pool, &old_role, &new_role.permissions).await?;
+
+    let updated = sqlx::query_as::<_, User>(
+        "UPDATE users SET role = ? WHERE id = ? \
+         RETURNING id, external_id, email, display_name, role, created_at, last_login, \
+                   is_service",
+    )
+    .bind(role)
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    audit::log_action(
+        pool,
+        &acting_user.id,
+        "update_role",
+        "user",
+        id,
+        &json!({ "role": role, "previous_role": old_role }),
+    )
+    .await?;
+
+    if demoted {
+        session::revoke_user_sessions(pool, id, None, &acting_user.id).await?;
+    }
+
+    Ok(updated)
+}
+
+/// Returns true when moving from `old_role` to a role with `new_permissions`
+/// takes any permission away. Callers sign demoted users out everywhere.
+pub(crate) async fn is_demotion(
+    pool: &SqlitePool,
+    old_role: &str,
+    new_permissions: &[String],
+) -> AppResult<bool> {
+    let old_permissions = role_permissions(pool, old_role).await?;
+    Ok(old_permissions.iter().any(|p| !new_permissions.contains(p)))
+}
+
+/// Returns true when the user signing in as `external_id` is the only admin
+/// left, so that taking their role would leave nobody able to manage the site.
+pub(crate) async fn is_last_admin(
+    conn: impl SqliteExecutor<'_>,
+    external_id: &str,
+) -> AppResult<bool> {
+    let last = sqlx::query_scalar::<_, bool>(
+        "SELECT NOT EXISTS ( \
+             SELECT 1 FROM users AS other \
+             WHERE other.role = users.role AND other.id != users.id AND NOT other.is_service \
+         ) \
+         FROM users WHERE external_id = ? AND role = ? AND NOT is_service",
+    )
+    .bind(external_id)
+    .bind(ADMIN_ROLE)
+    .fetch_optional(conn)
+    .await?;
+    Ok(last.unwrap_or(false))
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// Decodes stored permissions. The admin role always lists every permission,
+/// so clients see what it can do.
+fn decode(name: &str, stored: &str) -> Vec<String> {
+    if name == ADMIN_ROLE {
+        return permissions::ALL.iter().map(|(p, _)| p.to_string()).collect();
+    }
+    serde_json::from_str(stored).unwrap_or_default()
+}
+
+fn ensure_custom(role: &Role) -> AppResult<()> {
+    if role.builtin {
+        return Err(AppError::BadRequest(format!(
+            "The built-in role '{}' cannot be changed",
+            role.name
+        )));
+    }
+    Ok(())
+}
+
+/// Role names are short lowercase identifiers, as they appear on users.
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim().to_ascii_lowercase();
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(AppError::BadRequest(format!(
+            "Role names must be 1-{MAX_NAME_LEN} characters of a-z, 0-9, '-' and '_'"
+        )));
+    }
+    Ok(name)
+}
+
+/// Rejects unknown permissions and returns the rest sorted and deduplicated.
+fn validate_permissions(permissions: Vec<String>) -> AppResult<Vec<String>> {
+    let mut set = BTreeSet::new();
+    for permission in permissions {
+        if !permissions::is_known(&permission) {
+            return Err(AppError::BadRequest(format!("Unknown permission '{}'", permission)));
+        }
+        set.insert(permission);
+    }
+    Ok(set.into_iter().collect())
+}
@@ -0,0 +1,358 @@
+//! HTML sanitization policy for page and article content.
+//!
+//! Content is sanitized on write against an allowlist kept in site settings:
+//!
+//! - `sanitize_allowed_tags` — tag names, e.g. `p, a, img`;
+//! - `sanitize_allowed_attributes` — `name` allows an attribute on every
+//!   allowed tag, `tag[name]` on one tag only, e.g. `title, a[href], img[src]`;
+//! - `sanitize_url_schemes` — schemes permitted in URL attributes such as
+//!   `href` and `src`, e.g. `http, https, mailto`. Relative URLs always pass.
+//!
+//! Entries are separated by commas or whitespace; an empty or missing setting
+//! uses the built-in default. Whatever the settings say, `<script>` and
+//! `<style>` (removed together with their content), event-handler attributes
+//! and `javascript:` / `vbscript:` URLs are never allowed.
+//!
+//! Changing the policy does not touch stored rows; `report` lists the ones
+//! that would change, and saving them applies the current policy.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use crate::services::render;
+
+/// Settings keys holding the allowlist.
+pub const ALLOWED_TAGS_KEY: &str = "sanitize_allowed_tags";
+pub const ALLOWED_ATTRIBUTES_KEY: &str = "sanitize_allowed_attributes";
+pub const URL_SCHEMES_KEY: &str = "sanitize_url_schemes";
+
+/// Covers what the rich text editor and the Markdown renderer produce.
+const DEFAULT_TAGS: &str = "a abbr b blockquote br caption code del details div em figcaption \
+     figure h1 h2 h3 h4 h5 h6 hr i img input ins kbd li mark ol p pre s section small span \
+     strong sub summary sup table tbody td tfoot th thead tr u ul";
+const DEFAULT_ATTRIBUTES: &str = "class id lang title a[href] a[target] img[src] img[alt] \
+     img[width] img[height] ol[start] td[colspan] td[rowspan] th[colspan] th[rowspan] th[scope] \
+     input[type] input[checked] input[disabled]";
+const DEFAULT_URL_SCHEMES: &str = "http https mailto tel";
+
+/// The sanitizer drops these together with their content, so they cannot
+/// also be allowed.
+const FORBIDDEN_TAGS: &[&str] = &["script", "style"];
+const FORBIDDEN_URL_SCHEMES: &[&str] = &["javascript", "vbscript"];
+
+/// An attribute allowed on one tag only, as `(tag, attribute)`.
+type TagAttribute = (String, String);
+
+/// The allowlist content is sanitized against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizePolicy {
+    tags: Vec<String>,
+    generic_attributes: Vec<String>,
+    tag_attributes: Vec<TagAttribute>,
+    url_schemes: Vec<String>,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        let (generic_attributes, tag_attributes) = parse_attributes(DEFAULT_ATTRIBUTES)
+            .expect("default sanitize attributes are valid");
+        Self {
+            tags: parse_tags(DEFAULT_TAGS).expect("default sanitize tags are valid"),
+            generic_attributes,
+            tag_attributes,
+            url_schemes: parse_url_schemes(DEFAULT_URL_SCHEMES)
+                .expect("default sanitize URL schemes are valid"),
+        }
+    }
+}
+
+impl SanitizePolicy {
+    /// Removes everything the policy does not allow from an HTML fragment.
+    /// Links get `rel="noopener noreferrer"`.
+    pub fn clean(&self, html: &str) -> String {
+        let mut tag_attributes: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for (tag, attribute) in &self.tag_attributes {
+            tag_attributes.entry(tag).or_default().insert(attribute);
+        }
+
+        ammonia::Builder::default()
+            .tags(self.tags.iter().map(String::as_str).collect())
+            .generic_attributes(self.generic_attributes.iter().map(String::as_str).collect())
+            .tag_attributes(tag_attributes)
+            .url_schemes(self.url_schemes.iter().map(String::as_str).collect())
+            .clean(html)
+            .to_string()
+    }
+}
+
+/// Validates a sanitizer setting before it is stored. Other keys pass.
+pub fn validate_setting(key: &str, value: &str) -> AppResult<()> {
+    match key {
+        ALLOWED_TAGS_KEY => parse_tags(value).map(drop),
+        ALLOWED_ATTRIBUTES_KEY => parse_attributes(value).map(drop),
+        URL_SCHEMES_KEY => parse_url_schemes(value).map(drop),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the configured policy. Missing or empty settings — and, should
+/// one slip past validation, invalid ones — fall back to the defaults.
+pub async fn policy(pool: &SqlitePool) -> AppResult<SanitizePolicy> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        "SELECT key, value FROM site_settings WHERE key IN (?, ?, ?)",
+    )
+    .bind(ALLOWED_TAGS_KEY)
+    .bind(ALLOWED_ATTRIBUTES_KEY)
+    .bind(URL_SCHEMES_KEY)
+    .fetch_all(pool)
+    .await?;
+    let values: HashMap<String, String> = rows.into_iter().collect();
+
+    let mut policy = SanitizePolicy::default();
+    let configured = |key: &str| {
+        values
+            .get(key)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    };
+    let warn = |key: &str, e: AppError| {
+        tracing::warn!("Invalid {} setting ({}), using the default", key, e);
+    };
+
+    if let Some(value) = configured(ALLOWED_TAGS_KEY) {
+        match parse_tags(value) {
+            Ok(tags) => policy.tags = tags,
+            Err(e) => warn(ALLOWED_TAGS_KEY, e),
+        }
+    }
+    if let Some(value) = configured(ALLOWED_ATTRIBUTES_KEY) {
+        match parse_attributes(value) {
+            Ok((generic, per_tag)) => {
+                policy.generic_attributes = generic;
+                policy.tag_attributes = per_tag;
+            }
+            Err(e) => warn(ALLOWED_ATTRIBUTES_KEY, e),
+        }
+    }
+    if let Some(value) = configured(URL_SCHEMES_KEY) {
+        match parse_url_schemes(value) {
+            Ok(schemes) => policy.url_schemes = schemes,
+            Err(e) => warn(URL_SCHEMES_KEY, e),
+        }
+    }
+
+    Ok(policy)
+}
+
+/// A page or article whose stored content does not match what the current
+/// policy would produce.
+#[derive(Debug, Serialize)]
+pub struct ReportEntry {
+    pub entity_type: &'static str,
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    pub status: String,
+    pub content_format: String,
+    /// HTML source that the sanitizer would alter. Always false for
+    /// Markdown, whose source is stored as written.
+    pub content_changes: bool,
+    /// Rendered HTML that differs from a fresh render under the policy.
+    pub content_html_changes: bool,
+}
+
+/// Lists pages and articles, trashed ones included, whose stored content
+/// would change if it were saved again under the current policy.
+pub async fn report(pool: &SqlitePool) -> AppResult<Vec<ReportEntry>> {
+    let policy = policy(pool).await?;
+    let mut entries = Vec::new();
+
+    for (entity_type, table) in [("page", "pages"), ("article", "articles")] {
+        let rows = sqlx::query_as::<_, (String, String, String, String, String, String, String)>(
+            &format!(
+                "SELECT id, title, slug, status, content, content_format, content_html \
+                 FROM {table} ORDER BY title"
+            ),
+        )
+        .fetch_all(pool)
+        .await?;
+
+        for (id, title, slug, status, content, content_format, content_html) in rows {
+            let content_changes = render::sanitize_source(&content, &content_format, &policy)
+                != content;
+            let content_html_changes =
+                render::render(&content, &content_format, &policy) != content_html;
+            if content_changes || content_html_changes {
+                entries.push(ReportEntry {
+                    entity_type,
+                    id,
+                    title,
+                    slug,
+                    status,
+                    content_format,
+                    content_changes,
+                    content_html_changes,
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Renders `content_html` for pages and articles that have none although
+/// their source is not empty, as left by migration 025 for content that was
+/// never sanitized. Returns how many rows were rendered.
+pub async fn render_missing(pool: &SqlitePool) -> AppResult<usize> {
+    let policy = policy(pool).await?;
+    let mut rendered = 0;
+
+    for table in ["pages", "articles"] {
+        let rows = sqlx::query_as::<_, (String, String, String)>(&format!(
+            "SELECT id, content, content_format FROM {table} \
+             WHERE content_html = '' AND content != ''"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        for (id, content, content_format) in rows {
+            sqlx::query(&format!("UPDATE {table} SET content_html = ? WHERE id = ?"))
+                .bind(render::render(&content, &content_format, &policy))
+                .bind(&id)
+                .execute(pool)
+                .await?;
+            rendered += 1;
+        }
+    }
+
+    Ok(rendered)
+}
+
+// ─── Setting parsers ──────────────────────────────────────────────────────────
+
+fn entries(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_ascii_lowercase)
+}
+
+fn is_name(name: &str, extra: &[char]) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || extra.contains(&c))
+}
+
+fn parse_tags(value: &str) -> AppResult<Vec<String>> {
+    let mut tags = Vec::new();
+    for tag in entries(value) {
+        if !is_name(&tag, &[]) {
+            return Err(AppError::BadRequest(format!("Invalid tag name '{tag}'")));
+        }
+        if FORBIDDEN_TAGS.contains(&tag.as_str()) {
+            return Err(AppError::BadRequest(format!("<{tag}> cannot be allowed")));
+        }
+        tags.push(tag);
+    }
+    Ok(tags)
+}
+
+/// Splits attribute entries into generic ones and `(tag, attribute)` pairs.
+fn parse_attributes(value: &str) -> AppResult<(Vec<String>, Vec<TagAttribute>)> {
+    let mut generic = Vec::new();
+    let mut per_tag = Vec::new();
+    for entry in entries(value) {
+        let (tag, attribute) = match entry.strip_suffix(']').and_then(|e| e.split_once('[')) {
+            Some((tag, attribute)) => (Some(tag.to_owned()), attribute.to_owned()),
+            None => (None, entry.clone()),
+        };
+        if !is_name(&attribute, &['-', '_'])
+            || tag.as_deref().is_some_and(|tag| !is_name(tag, &[]))
+        {
+            return Err(AppError::BadRequest(format!(
+                "Invalid attribute entry '{entry}'; use 'name' or 'tag[name]'"
+            )));
+        }
+        if attribute.starts_with("on") {
+            return Err(AppError::BadRequest(format!(
+                "Event handler attribute '{attribute}' cannot be allowed"
+            )));
+        }
+        // Every link gets its own `rel`; the sanitizer refuses to also keep one
+        // from the content.
+        if attribute == "rel" && tag.as_deref().is_none_or(|tag| tag == "a") {
+            return Err(AppError::BadRequest(
+                "'rel' is set on links automatically and cannot be allowed".into(),
+            ));
+        }
+        match tag {
+            Some(tag) if FORBIDDEN_TAGS.contains(&tag.as_str()) => {
+                return Err(AppError::BadRequest(format!("<{tag}> cannot be allowed")));
+            }
+            Some(tag) => per_tag.push((tag, attribute)),
+            None => generic.push(attribute),
+        }
+    }
+    Ok((generic, per_tag))
+}
+
+fn parse_url_schemes(value: &str) -> AppResult<Vec<String>> {
+    let mut schemes = Vec::new();
+    for scheme in entries(value) {
+        let scheme = scheme.trim_end_matches(':').to_owned();
+        if !is_name(&scheme, &['+', '-', '.']) {
+            return Err(AppError::BadRequest(format!("Invalid URL scheme '{scheme}'")));
+        }
+        if FORBIDDEN_URL_SCHEMES.contains(&scheme.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "'{scheme}:' URLs cannot be allowed"
+            )));
+        }
+        schemes.push(scheme);
+    }
+    Ok(schemes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_strips_scripts_and_handlers() {
+        let policy = SanitizePolicy::default();
+        assert_eq!(
+            policy.clean(r#"<p onclick="x()">Hi<script>alert(1)</script></p>"#),
+            "<p>Hi</p>"
+        );
+        assert_eq!(
+            policy.clean(r#"<a href="javascript:alert(1)">x</a>"#),
+            r#"<a rel="noopener noreferrer">x</a>"#
+        );
+        assert_eq!(
+            policy.clean(r#"<img src="/uploads/a.png" alt="A">"#),
+            r#"<img src="/uploads/a.png" alt="A">"#
+        );
+    }
+
+    #[test]
+    fn attribute_entries_are_validated() {
+        let (generic, per_tag) = parse_attributes("title, a[href]  img[SRC]").unwrap();
+        assert_eq!(generic, vec!["title"]);
+        assert_eq!(
+            per_tag,
+            vec![
+                ("a".to_owned(), "href".to_owned()),
+                ("img".to_owned(), "src".to_owned())
+            ]
+        );
+        assert!(parse_attributes("onclick").is_err());
+        assert!(parse_attributes("a[rel]").is_err());
+        assert!(parse_attributes("a[href").is_err());
+        assert!(parse_tags("p script").is_err());
+        assert!(parse_url_schemes("https, javascript:").is_err());
+    }
+}
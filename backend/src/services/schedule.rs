@@ -0,0 +1,85 @@
+//! Site timezone and timestamp handling for scheduled publishing.
+//!
+//! Every timestamp is stored as UTC text in one canonical form,
+//! `YYYY-MM-DDTHH:MM:SSZ` — the same form `strftime('%Y-%m-%dT%H:%M:%SZ',
+//! 'now')` produces — so SQL comparisons against "now" are plain string
+//! comparisons that sort correctly. The `site_timezone` setting only affects
+//! how wall-clock times sent by editors are interpreted and how the admin UI
+//! displays them; it never changes what is stored.
+
+use chrono::{DateTime, LocalResult, SubsecRound, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::SqlitePool;
+
+use crate::db::models::ScheduleTime;
+use crate::error::{AppError, AppResult};
+
+/// Settings key holding the IANA timezone name, e.g. `Europe/Amsterdam`.
+pub const TIMEZONE_KEY: &str = "site_timezone";
+
+/// `strftime` format of stored timestamps.
+const DB_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Parses an IANA timezone name, rejecting unknown zones.
+pub fn parse_timezone(name: &str) -> AppResult<Tz> {
+    name.trim().parse::<Tz>().map_err(|_| {
+        AppError::BadRequest(format!("Unknown timezone: '{}'", name))
+    })
+}
+
+/// Returns the configured site timezone, falling back to UTC when the
+/// setting is missing or holds a name this build does not know.
+pub async fn site_timezone(pool: &SqlitePool) -> AppResult<Tz> {
+    let name: Option<String> =
+        sqlx::query_scalar("SELECT value FROM site_settings WHERE key = ?")
+            .bind(TIMEZONE_KEY)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(match name.as_deref().map(str::trim) {
+        None | Some("") => Tz::UTC,
+        Some(name) => name.parse().unwrap_or_else(|_| {
+            tracing::warn!("Invalid site_timezone '{}', using UTC", name);
+            Tz::UTC
+        }),
+    })
+}
+
+/// Converts a client-supplied time to a UTC instant, truncated to whole
+/// seconds to match the stored precision.
+///
+/// Wall-clock times are read in `tz`. A time that occurs twice (when clocks go
+/// back) resolves to the first occurrence; a time skipped when clocks go
+/// forward does not exist and is rejected.
+pub fn resolve(time: ScheduleTime, tz: Tz) -> AppResult<DateTime<Utc>> {
+    let at = match time {
+        ScheduleTime::Instant(at) => at,
+        ScheduleTime::Local(naive) => match tz.from_local_datetime(&naive) {
+            LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.with_timezone(&Utc),
+            LocalResult::None => {
+                return Err(AppError::BadRequest(format!(
+                    "{} does not exist in {} (skipped by a daylight saving change)",
+                    naive.format("%Y-%m-%d %H:%M"),
+                    tz
+                )));
+            }
+        },
+    };
+    Ok(at.trunc_subsecs(0))
+}
+
+/// Scheduled content needs a publish time — without one it would never go
+/// live.
+pub fn ensure_publish_time(status: &str, publish_at: Option<DateTime<Utc>>) -> AppResult<()> {
+    if status == "scheduled" && publish_at.is_none() {
+        return Err(AppError::BadRequest(
+            "publish_at is required for scheduled content".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Formats a UTC instant in the canonical stored form.
+pub fn db_timestamp(at: DateTime<Utc>) -> String {
+    at.format(DB_FORMAT).to_string()
+}
@@ -12,6 +12,7 @@ use sqlx::{Row, SqlitePool};
 
 use crate::db::models::SearchResult;
 use crate::error::AppResult;
+use crate::services::visibility::PUBLIC_FILTER;
 
 /// Searches published (and optionally unpublished) content across all entity
 /// types or a specific subset.
@@ -52,7 +53,7 @@ pub async fn search(
 
     if search_type.is_none() || search_type == Some("pages") {
         // The snippet() call highlights the matched term in the second indexed
-        // column (index 1 = content) using HTML <mark> tags. The FTS5 rowid
+        // column (index 1 = content_html) using HTML <mark> tags. The FTS5 rowid
         // equals the rowid of the source `pages` row, so a direct JOIN works.
         let sql = if include_unpublished {
             "SELECT p.id, p.title, p.slug,
@@ -62,20 +63,22 @@ pub async fn search(
              WHERE pages_fts MATCH ?
                AND p.trashed_at IS NULL
              ORDER BY bm25(pages_fts)
-             LIMIT 20"
+             LIMIT 20".to_owned()
         } else {
-            "SELECT p.id, p.title, p.slug,
+            format!(
+                "SELECT p.id, p.title, p.slug,
                     snippet(pages_fts, 1, '<mark>', '</mark>', '...', 32) AS snippet
              FROM pages_fts
              JOIN pages p ON p.rowid = pages_fts.rowid
              WHERE pages_fts MATCH ?
-               AND p.status = 'published'
                AND p.trashed_at IS NULL
+               AND {PUBLIC_FILTER}
              ORDER BY bm25(pages_fts)
              LIMIT 20"
+            )
         };
 
-        let rows = sqlx::query(sql)
+        let rows = sqlx::query(&sql)
             .bind(&fts_query)
             .fetch_all(pool)
             .await?;
@@ -94,8 +97,8 @@ pub async fn search(
     // ── Articles ──────────────────────────────────────────────────────────────
 
     if search_type.is_none() || search_type == Some("articles") {
-        // articles_fts indexes: 0 = title, 1 = short_text, 2 = content.
-        // Snippet is drawn from column 2 (content) for richer context.
+        // articles_fts indexes: 0 = title, 1 = short_text, 2 = content_html.
+        // Snippet is drawn from column 2 (content_html) for richer context.
         let sql = if include_unpublished {
             "SELECT a.id, a.title, a.slug,
                     snippet(articles_fts, 2, '<mark>', '</mark>', '...', 32) AS snippet
@@ -104,20 +107,22 @@ pub async fn search(
              WHERE articles_fts MATCH ?
                AND a.trashed_at IS NULL
              ORDER BY bm25(articles_fts)
-             LIMIT 20"
+             LIMIT 20".to_owned()
         } else {
-            "SELECT a.id, a.title, a.slug,
+            format!(
+                "SELECT a.id, a.title, a.slug,
                     snippet(articles_fts, 2, '<mark>', '</mark>', '...', 32) AS snippet
              FROM articles_fts
              JOIN articles a ON a.rowid = articles_fts.rowid
              WHERE articles_fts MATCH ?
-               AND a.status = 'published'
                AND a.trashed_at IS NULL
+               AND {PUBLIC_FILTER}
              ORDER BY bm25(articles_fts)
              LIMIT 20"
+            )
         };
 
-        let rows = sqlx::query(sql)
+        let rows = sqlx::query(&sql)
             .bind(&fts_query)
             .fetch_all(pool)
             .await?;
@@ -0,0 +1,324 @@
+//! Article series service.
+//!
+//! A series is an ordered list of articles — the parts of a multi-part
+//! tutorial, say — with its own title, slug and description. An article
+//! belongs to at most one series. The public side only ever sees published
+//! parts, numbered by their position among each other.
+
+use std::collections::HashSet;
+
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::models::{CreateSeries, Series, SeriesMembership, SeriesPart, SeriesSummary,
+    SeriesWithParts, UpdateSeries};
+use crate::error::{AppError, AppResult};
+use crate::helpers::slugify;
+use crate::services::audit;
+use crate::services::visibility::PUBLIC_FILTER;
+
+const SERIES_COLS: &str = "id, title, slug, description, created_at, updated_at";
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns all series with their article counts, ordered by title.
+pub async fn list_series(pool: &SqlitePool) -> AppResult<Vec<SeriesSummary>> {
+    let series = sqlx::query_as::<_, SeriesSummary>(&format!(
+        "SELECT {SERIES_COLS}, \
+                (SELECT COUNT(*) FROM series_articles sa WHERE sa.series_id = series.id) \
+                    AS article_count \
+         FROM series \
+         ORDER BY title COLLATE NOCASE ASC"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(series)
+}
+
+/// Fetches a single series by primary key. Returns `NotFound` if absent.
+pub async fn get_series(pool: &SqlitePool, id: &str) -> AppResult<Series> {
+    sqlx::query_as::<_, Series>(&format!("SELECT {SERIES_COLS} FROM series WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)
+}
+
+/// Fetches a series with all of its articles, whatever their status.
+pub async fn get_series_with_parts(pool: &SqlitePool, id: &str) -> AppResult<SeriesWithParts> {
+    let series = get_series(pool, id).await?;
+    let parts = parts(pool, &series.id, false).await?;
+    Ok(SeriesWithParts { series, parts })
+}
+
+/// Public table of contents: the series with `slug` and its published
+/// articles. Returns `NotFound` for unknown series and for series with no
+/// published part yet.
+pub async fn published_series(pool: &SqlitePool, slug: &str) -> AppResult<SeriesWithParts> {
+    let series = sqlx::query_as::<_, Series>(&format!(
+        "SELECT {SERIES_COLS} FROM series \
+         WHERE slug = ? \
+           AND id IN (SELECT series_id \
+                      FROM series_articles \
+                      JOIN articles ON articles.id = series_articles.article_id \
+                      WHERE {PUBLIC_FILTER} \
+                      GROUP BY series_id \
+                      HAVING COUNT(*) > 0)"
+    ))
+    .bind(slug)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    let parts = parts(pool, &series.id, true).await?;
+    Ok(SeriesWithParts { series, parts })
+}
+
+/// Returns where a published article sits in its series, with its published
+/// neighbours. `None` when the article is not part of a series.
+pub async fn membership(pool: &SqlitePool, article_id: &str) -> AppResult<Option<SeriesMembership>> {
+    let series_id =
+        sqlx::query_scalar::<_, String>("SELECT series_id FROM series_articles WHERE article_id = ?")
+            .bind(article_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some(series_id) = series_id else {
+        return Ok(None);
+    };
+
+    let series = get_series(pool, &series_id).await?;
+    let parts = parts(pool, &series_id, true).await?;
+    let Some(index) = parts.iter().position(|p| p.id == article_id) else {
+        return Ok(None);
+    };
+
+    Ok(Some(SeriesMembership {
+        id: series.id,
+        title: series.title,
+        slug: series.slug,
+        position: index as i64 + 1,
+        total: parts.len() as i64,
+        previous: index.checked_sub(1).map(|i| parts[i].clone()),
+        next: parts.get(index + 1).cloned(),
+    }))
+}
+
+/// Creates a new series with the given articles in order.
+///
+/// The slug is derived from the title when not supplied. Returns `Conflict`
+/// if the slug is taken or an article already belongs to another series.
+pub async fn create_series(
+    pool: &SqlitePool,
+    input: CreateSeries,
+    user_id: &str,
+) -> AppResult<SeriesWithParts> {
+    let title = validate_title(&input.title)?;
+    let slug = resolve_slug(input.slug.as_deref(), &title)?;
+    ensure_slug_unique(pool, &slug, None).await?;
+
+    let id = Uuid::new_v4().to_string();
+    validate_parts(pool, &id, &input.article_ids).await?;
+
+    sqlx::query("INSERT INTO series (id, title, slug, description) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&title)
+        .bind(&slug)
+        .bind(input.description.unwrap_or_default())
+        .execute(pool)
+        .await?;
+
+    set_parts(pool, &id, &input.article_ids).await?;
+
+    audit::log_action(pool, user_id, "create", "series", &id, &json!({ "title": title })).await?;
+
+    get_series_with_parts(pool, &id).await
+}
+
+/// Applies a partial update to a series. Fields absent from `input` keep
+/// their current values; `article_ids` replaces the whole list.
+pub async fn update_series(
+    pool: &SqlitePool,
+    id: &str,
+    input: UpdateSeries,
+    user_id: &str,
+) -> AppResult<SeriesWithParts> {
+    let existing = get_series(pool, id).await?;
+
+    let title = match input.title {
+        Some(ref title) => validate_title(title)?,
+        None => existing.title.clone(),
+    };
+    let slug = match input.slug {
+        Some(ref slug) => resolve_slug(Some(slug), &title)?,
+        None => existing.slug.clone(),
+    };
+    if slug != existing.slug {
+        ensure_slug_unique(pool, &slug, Some(id)).await?;
+    }
+    if let Some(ref article_ids) = input.article_ids {
+        validate_parts(pool, id, article_ids).await?;
+    }
+
+    sqlx::query(
+        "UPDATE series \
+         SET title = ?, slug = ?, description = ?, \
+             updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+         WHERE id = ?",
+    )
+    .bind(&title)
+    .bind(&slug)
+    .bind(input.description.unwrap_or(existing.description))
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    if let Some(ref article_ids) = input.article_ids {
+        set_parts(pool, id, article_ids).await?;
+    }
+
+    audit::log_action(pool, user_id, "update", "series", id, &json!({ "title": title })).await?;
+
+    get_series_with_parts(pool, id).await
+}
+
+/// Deletes a series. Its articles are kept; they just no longer form a series.
+pub async fn delete_series(pool: &SqlitePool, id: &str, user_id: &str) -> AppResult<()> {
+    let series = get_series(pool, id).await?;
+
+    sqlx::query("DELETE FROM series WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    audit::log_action(pool, user_id, "delete", "series", id, &json!({ "title": series.title }))
+        .await?;
+
+    Ok(())
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// The articles of a series in order, optionally only the publicly visible
+/// ones. Positions are renumbered from 1 so gaps left by hidden parts do not
+/// show.
+async fn parts(pool: &SqlitePool, series_id: &str, public_only: bool) -> AppResult<Vec<SeriesPart>> {
+    let visibility = if public_only {
+        format!("AND {PUBLIC_FILTER}")
+    } else {
+        String::new()
+    };
+    let mut parts = sqlx::query_as::<_, SeriesPart>(&format!(
+        "SELECT id, title, slug, short_text, status, position \
+         FROM articles JOIN series_articles ON series_articles.article_id = articles.id \
+         WHERE series_id = ? {visibility} \
+         ORDER BY position ASC"
+    ))
+    .bind(series_id)
+    .fetch_all(pool)
+    .await?;
+
+    for (index, part) in parts.iter_mut().enumerate() {
+        part.position = index as i64 + 1;
+    }
+    Ok(parts)
+}
+
+/// Checks an article list before anything is written: every article must
+/// exist, appear once, and not belong to a series other than `series_id`.
+async fn validate_parts(pool: &SqlitePool, series_id: &str, article_ids: &[String]) -> AppResult<()> {
+    let mut seen = HashSet::new();
+    for article_id in article_ids {
+        if !seen.insert(article_id.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Article '{}' appears more than once",
+                article_id
+            )));
+        }
+
+        let title = sqlx::query_scalar::<_, String>("SELECT title FROM articles WHERE id = ?")
+            .bind(article_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest(format!("Article '{}' does not exist", article_id))
+            })?;
+
+        let other = sqlx::query_scalar::<_, String>(
+            "SELECT s.title FROM series_articles sa JOIN series s ON s.id = sa.series_id \
+             WHERE sa.article_id = ? AND sa.series_id != ?",
+        )
+        .bind(article_id)
+        .bind(series_id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(other) = other {
+            return Err(AppError::Conflict(format!(
+                "'{}' is already part of the series '{}'",
+                title, other
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the articles of a series with `article_ids`, in that order.
+async fn set_parts(pool: &SqlitePool, series_id: &str, article_ids: &[String]) -> AppResult<()> {
+    sqlx::query("DELETE FROM series_articles WHERE series_id = ?")
+        .bind(series_id)
+        .execute(pool)
+        .await?;
+
+    for (position, article_id) in article_ids.iter().enumerate() {
+        sqlx::query("INSERT INTO series_articles (article_id, series_id, position) VALUES (?, ?, ?)")
+            .bind(article_id)
+            .bind(series_id)
+            .bind(position as i64)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+fn validate_title(title: &str) -> AppResult<String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(AppError::BadRequest("Series title must not be empty".into()));
+    }
+    Ok(title.to_owned())
+}
+
+/// Returns the slug to store: the supplied one, normalised, or one derived
+/// from the title.
+fn resolve_slug(slug: Option<&str>, title: &str) -> AppResult<String> {
+    let slug = match slug.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(slug) => slugify(slug),
+        None => slugify(title),
+    };
+    if slug.is_empty() {
+        return Err(AppError::BadRequest("Series slug must not be empty".into()));
+    }
+    Ok(slug)
+}
+
+/// Returns `Conflict` if `slug` is already used by a series other than
+/// `exclude_id`.
+async fn ensure_slug_unique(
+    pool: &SqlitePool,
+    slug: &str,
+    exclude_id: Option<&str>,
+) -> AppResult<()> {
+    let taken = sqlx::query_scalar::<_, String>("SELECT id FROM series WHERE slug = ? AND id IS NOT ?")
+        .bind(slug)
+        .bind(exclude_id)
+        .fetch_optional(pool)
+        .await?;
+
+    match taken {
+        Some(_) => Err(AppError::Conflict(format!(
+            "A series with slug '{}' already exists",
+            slug
+        ))),
+        None => Ok(()),
+    }
+}
@@ -7,10 +7,13 @@
 
 use std::collections::HashMap;
 
-use sqlx::SqlitePool;
+use chrono::{DateTime, Utc};
+use sqlx::{SqliteExecutor, SqlitePool};
 
 use crate::error::AppResult;
-use crate::services::audit;
+use crate::services::{
+    audit, concurrency, registration, revisions, role_mapping, sanitize, schedule,
+};
 
 /// Keys that are safe to expose without authentication.
 const PUBLIC_KEYS: &[&str] = &[
@@ -21,6 +24,7 @@ const PUBLIC_KEYS: &[&str] = &[
     "apps_per_page",
     "app_catalogue_intro",
     "dark_mode_default",
+    "site_timezone",
 ];
 
 /// All known settings keys. Updates with unrecognised keys are rejected.
@@ -32,6 +36,18 @@ const ALLOWED_KEYS: &[&str] = &[
     "apps_per_page",
     "app_catalogue_intro",
     "dark_mode_default",
+    "site_timezone",
+    "revision_keep_all_days",
+    "revision_keep_daily_days",
+    "revision_keep_weekly_days",
+    "sanitize_allowed_tags",
+    "sanitize_allowed_attributes",
+    "sanitize_url_schemes",
+    "registration_policy",
+    "registration_allowed_domains",
+    "registration_default_role",
+    "idp_role_mappings",
+    "idp_role_mapping_required",
 ];
 
 // ─── Public service functions ─────────────────────────────────────────────────
@@ -45,6 +61,18 @@ pub async fn get_all_settings(pool: &SqlitePool) -> AppResult<HashMap<String, St
     Ok(rows.into_iter().collect())
 }
 
+/// Returns the version of the settings as a whole: the latest `updated_at` of
+/// any key.
+pub async fn settings_version(db: impl SqliteExecutor<'_>) -> AppResult<DateTime<Utc>> {
+    let version = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT MAX(strftime('%Y-%m-%dT%H:%M:%fZ', updated_at)) FROM site_settings",
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(version.unwrap_or(DateTime::UNIX_EPOCH))
+}
+
 /// Returns only the settings that are safe to expose publicly.
 ///
 /// Keys that have no stored value are omitted rather than returned as empty
@@ -76,11 +104,15 @@ pub async fn get_public_settings(pool: &SqlitePool) -> AppResult<HashMap<String,
 /// Each entry is written individually in a loop. SQLite is local so the
 /// round-trip cost is negligible, and this keeps the code simple and
 /// easy to extend with per-key validation later.
+///
+/// When `expected_updated_at` is given and any setting has changed since,
+/// nothing is written. Returns the new settings version.
 pub async fn update_settings(
     pool: &SqlitePool,
     updates: HashMap<String, String>,
+    expected_updated_at: Option<DateTime<Utc>>,
     user_id: &str,
-) -> AppResult<()> {
+) -> AppResult<DateTime<Utc>> {
     // Reject unrecognised keys to prevent settings table pollution.
     for key in updates.keys() {
         if !ALLOWED_KEYS.contains(&key.as_str()) {
@@ -90,18 +122,51 @@ pub async fn update_settings(
             )));
         }
     }
+    if let Some(tz) = updates.get(schedule::TIMEZONE_KEY) {
+        schedule::parse_timezone(tz)?;
+    }
+    for key in [
+        revisions::KEEP_ALL_DAYS_KEY,
+        revisions::KEEP_DAILY_DAYS_KEY,
+        revisions::KEEP_WEEKLY_DAYS_KEY,
+    ] {
+        if let Some(value) = updates.get(key) {
+            revisions::parse_days(key, value)?;
+        }
+    }
+    for (key, value) in &updates {
+        sanitize::validate_setting(key, value)?;
+        registration::validate_setting(key, value)?;
+        role_mapping::validate_setting(key, value)?;
+    }
+    if let Some(value) = updates.get(role_mapping::MAPPINGS_KEY) {
+        role_mapping::check_roles(pool, value).await?;
+    }
+    if let Some(value) = updates.get(registration::DEFAULT_ROLE_KEY) {
+        registration::check_default_role(pool, value).await?;
+    }
+
+    // The check and the writes share one transaction, so concurrent saves
+    // cannot both pass it.
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let version = settings_version(&mut *tx).await?;
+    if concurrency::is_stale(expected_updated_at, version) {
+        let current = get_all_settings(pool).await?;
+        return Err(concurrency::conflict(&current, version));
+    }
 
     for (key, value) in &updates {
         sqlx::query(
             "INSERT INTO site_settings (key, value, updated_at) \
-             VALUES (?, ?, datetime('now')) \
+             VALUES (?, ?, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')) \
              ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
         )
         .bind(key)
         .bind(value)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
     }
+    tx.commit().await?;
 
     audit::log_action(
         pool,
@@ -113,5 +178,5 @@ pub async fn update_settings(
     )
     .await?;
 
-    Ok(())
+    settings_version(pool).await
 }
@@ -0,0 +1,66 @@
+//! Slug history shared by pages and articles.
+//!
+//! Whenever content changes its slug, the old slug is recorded here so the
+//! public resolvers can answer requests for it with a permanent redirect.
+//! Retired slugs stay reserved: `ensure_slug_unique` in the pages and articles
+//! services refuses to hand them to other content unless the caller explicitly
+//! asks to reclaim them, which breaks the old redirect.
+
+use sqlx::{SqliteExecutor, SqlitePool};
+
+use crate::error::AppResult;
+
+/// Returns the id of the entity that previously used `slug`, if any.
+pub async fn historic_owner(
+    pool: &SqlitePool,
+    entity_type: &str,
+    slug: &str,
+) -> AppResult<Option<String>> {
+    let owner = sqlx::query_scalar::<_, String>(
+        "SELECT entity_id FROM slug_history WHERE entity_type = ? AND slug = ?",
+    )
+    .bind(entity_type)
+    .bind(slug)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(owner)
+}
+
+/// Records that `entity_id` no longer uses `old_slug`.
+pub async fn record(
+    conn: impl SqliteExecutor<'_>,
+    entity_type: &str,
+    entity_id: &str,
+    old_slug: &str,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO slug_history (entity_type, slug, entity_id) VALUES (?, ?, ?) \
+         ON CONFLICT(entity_type, slug) DO UPDATE SET \
+             entity_id = excluded.entity_id, \
+             created_at = excluded.created_at",
+    )
+    .bind(entity_type)
+    .bind(old_slug)
+    .bind(entity_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Removes `slug` from the history because it is in active use again —
+/// either the original owner switched back to it or other content reclaimed it.
+pub async fn release(
+    conn: impl SqliteExecutor<'_>,
+    entity_type: &str,
+    slug: &str,
+) -> AppResult<()> {
+    sqlx::query("DELETE FROM slug_history WHERE entity_type = ? AND slug = ?")
+        .bind(entity_type)
+        .bind(slug)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
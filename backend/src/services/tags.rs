@@ -0,0 +1,285 @@
+//! Tags service.
+//!
+//! Tags are a free-form taxonomy next to categories. Editors assign them by
+//! name and unknown names are created on the spot, so the list grows with the
+//! content; admins tidy it up afterwards by renaming, merging and deleting.
+//! A tag is identified by the slug of its name, so "Rust" and "rust" are the
+//! same tag.
+
+use std::collections::HashSet;
+
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::models::{Page, PaginationParams, Tag, TagListing, TagUsage};
+use crate::error::{AppError, AppResult};
+use crate::helpers::slugify;
+use crate::services::articles::{self, ArticleFilter};
+use crate::services::pages::PAGE_COLS;
+use crate::services::visibility::PUBLIC_FILTER;
+
+const MAX_NAME_LEN: usize = 50;
+
+/// Join table and owner column for an entity type.
+fn join_table(entity_type: &str) -> (&'static str, &'static str) {
+    match entity_type {
+        "page" => ("page_tags", "page_id"),
+        _ => ("article_tags", "article_id"),
+    }
+}
+
+// ─── Public service functions ─────────────────────────────────────────────────
+
+/// Returns all tags with their usage counts, ordered alphabetically by name.
+pub async fn list_tags(pool: &SqlitePool) -> AppResult<Vec<TagUsage>> {
+    let tags = sqlx::query_as::<_, TagUsage>(
+        "SELECT t.id, t.name, t.slug, \
+                (SELECT COUNT(*) FROM page_tags pt WHERE pt.tag_id = t.id) AS page_count, \
+                (SELECT COUNT(*) FROM article_tags at WHERE at.tag_id = t.id) AS article_count \
+         FROM tags t \
+         ORDER BY t.name COLLATE NOCASE ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tags)
+}
+
+/// Fetches a single tag by primary key. Returns `NotFound` if absent.
+pub async fn get_tag(pool: &SqlitePool, id: &str) -> AppResult<Tag> {
+    sqlx::query_as::<_, Tag>("SELECT id, name, slug FROM tags WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)
+}
+
+/// Returns the tags assigned to a page or article, ordered by name.
+pub async fn tags_for(pool: &SqlitePool, entity_type: &str, entity_id: &str) -> AppResult<Vec<Tag>> {
+    let (table, owner) = join_table(entity_type);
+    let tags = sqlx::query_as::<_, Tag>(&format!(
+        "SELECT t.id, t.name, t.slug FROM tags t \
+         JOIN {table} j ON j.tag_id = t.id \
+         WHERE j.{owner} = ? \
+         ORDER BY t.name COLLATE NOCASE ASC"
+    ))
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tags)
+}
+
+/// Checks tag names before anything is written, so a bad name does not leave
+/// a half-applied update behind.
+pub fn validate_names(names: &[String]) -> AppResult<()> {
+    names.iter().try_for_each(|name| normalize_name(name).map(drop))
+}
+
+/// Returns whether `set_tags` with `names` would change the tags of a page or
+/// article.
+pub async fn would_change(
+    pool: &SqlitePool,
+    entity_type: &str,
+    entity_id: &str,
+    names: &[String],
+) -> AppResult<bool> {
+    let wanted = names
+        .iter()
+        .map(|name| normalize_name(name).map(|name| slugify(&name)))
+        .collect::<AppResult<HashSet<_>>>()?;
+    let current = tags_for(pool, entity_type, entity_id).await?;
+    Ok(wanted != current.into_iter().map(|tag| tag.slug).collect())
+}
+
+/// Replaces the tags of a page or article with `names`, creating tags that do
+/// not exist yet. Names are matched by slug; duplicates are ignored.
+pub async fn set_tags(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+    names: &[String],
+) -> AppResult<()> {
+    let mut tag_ids = Vec::new();
+    let mut seen = HashSet::new();
+    for name in names {
+        let name = normalize_name(name)?;
+        if !seen.insert(slugify(&name)) {
+            continue;
+        }
+        tag_ids.push(find_or_create(conn, &name).await?);
+    }
+
+    let (table, owner) = join_table(entity_type);
+    sqlx::query(&format!("DELETE FROM {table} WHERE {owner} = ?"))
+        .bind(entity_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for tag_id in &tag_ids {
+        sqlx::query(&format!("INSERT INTO {table} ({owner}, tag_id) VALUES (?, ?)"))
+            .bind(entity_id)
+            .bind(tag_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Renames a tag. The slug follows the new name unless one is given; it must
+/// not belong to another tag (merge the two instead).
+pub async fn rename_tag(
+    pool: &SqlitePool,
+    id: &str,
+    name: &str,
+    slug: Option<&str>,
+) -> AppResult<Tag> {
+    get_tag(pool, id).await?;
+    let name = normalize_name(name)?;
+    let slug = match slug.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(slug) => slugify(slug),
+        None => slugify(&name),
+    };
+    if slug.is_empty() {
+        return Err(AppError::BadRequest("Tag slug must not be empty".into()));
+    }
+
+    let taken = sqlx::query_scalar::<_, String>("SELECT id FROM tags WHERE slug = ? AND id != ?")
+        .bind(&slug)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    if taken.is_some() {
+        return Err(AppError::Conflict(format!(
+            "A tag with slug '{}' already exists; merge the tags instead",
+            slug
+        )));
+    }
+
+    sqlx::query("UPDATE tags SET name = ?, slug = ? WHERE id = ?")
+        .bind(&name)
+        .bind(&slug)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    get_tag(pool, id).await
+}
+
+/// Merges tag `id` into `into_id`: content tagged with the former carries the
+/// latter afterwards, and the former is deleted. Returns the surviving tag.
+pub async fn merge_tags(pool: &SqlitePool, id: &str, into_id: &str) -> AppResult<Tag> {
+    if id == into_id {
+        return Err(AppError::BadRequest("A tag cannot be merged into itself".into()));
+    }
+    get_tag(pool, id).await?;
+    let target = get_tag(pool, into_id).await?;
+
+    for entity_type in ["page", "article"] {
+        let (table, owner) = join_table(entity_type);
+        sqlx::query(&format!(
+            "INSERT OR IGNORE INTO {table} ({owner}, tag_id) \
+             SELECT {owner}, ? FROM {table} WHERE tag_id = ?"
+        ))
+        .bind(into_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    }
+
+    // The join rows of the merged tag go with it via ON DELETE CASCADE.
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(target)
+}
+
+/// Deletes a tag, removing it from all content.
+pub async fn delete_tag(pool: &SqlitePool, id: &str) -> AppResult<()> {
+    get_tag(pool, id).await?;
+
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Published pages and a page of published articles carrying the tag with
+/// `slug`. Returns `NotFound` for unknown tags.
+pub async fn published_listing(
+    pool: &SqlitePool,
+    slug: &str,
+    params: &PaginationParams,
+) -> AppResult<TagListing> {
+    let tag = sqlx::query_as::<_, Tag>("SELECT id, name, slug FROM tags WHERE slug = ?")
+        .bind(slug)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let pages = sqlx::query_as::<_, Page>(&format!(
+        "SELECT {PAGE_COLS} FROM pages \
+         WHERE id IN (SELECT page_id FROM page_tags WHERE tag_id = ?) \
+           AND {PUBLIC_FILTER} \
+         ORDER BY title COLLATE NOCASE ASC"
+    ))
+    .bind(&tag.id)
+    .fetch_all(pool)
+    .await?;
+
+    let filter = ArticleFilter {
+        tag: Some(&tag.slug),
+        ..Default::default()
+    };
+    let articles = articles::list_published_articles(pool, params, filter).await?;
+
+    Ok(TagListing {
+        tag,
+        pages,
+        articles,
+    })
+}
+
+// ─── Internal helpers ─────────────────────────────────────────────────────────
+
+/// Trims a tag name and collapses inner whitespace.
+fn normalize_name(name: &str) -> AppResult<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Tag names must be at most {MAX_NAME_LEN} characters"
+        )));
+    }
+    if slugify(&name).is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Tag name '{}' needs at least one letter or digit",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+/// Returns the id of the tag whose slug matches `name`, creating the tag when
+/// there is none.
+async fn find_or_create(conn: &mut SqliteConnection, name: &str) -> AppResult<String> {
+    let slug = slugify(name);
+    // A concurrent assignment may create the same tag first; the insert is a
+    // no-op then and the lookup below finds theirs.
+    sqlx::query("INSERT OR IGNORE INTO tags (id, name, slug) VALUES (?, ?, ?)")
+        .bind(Uuid::new_v4().to_string())
+        .bind(name)
+        .bind(&slug)
+        .execute(&mut *conn)
+        .await?;
+
+    let id = sqlx::query_scalar::<_, String>("SELECT id FROM tags WHERE slug = ?")
+        .bind(&slug)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(id)
+}
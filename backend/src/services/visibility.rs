@@ -0,0 +1,49 @@
+//! Public visibility rules shared by pages and articles.
+//!
+//! Content is served publicly while it is `published` and its optional
+//! `unpublish_at` has not passed. The background task archives expired
+//! content, but public queries apply the expiry themselves so nothing is
+//! served past its time while waiting for the next tick.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppError, AppResult};
+
+/// SQL condition selecting publicly visible rows. Column names are
+/// unqualified, so it can be spliced into any query over `pages` or
+/// `articles` (including FTS joins, where these columns are unambiguous).
+pub(crate) const PUBLIC_FILTER: &str =
+    "status = 'published' \
+     AND (unpublish_at IS NULL OR unpublish_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))";
+
+/// Rust-side equivalent of `PUBLIC_FILTER` for rows already loaded.
+pub fn is_public(status: &str, unpublish_at: Option<DateTime<Utc>>) -> bool {
+    status == "published" && unpublish_at.is_none_or(|at| at > Utc::now())
+}
+
+/// Checks that an expiry, when set, comes after the publish time.
+pub fn validate_window(
+    publish_at: Option<DateTime<Utc>>,
+    unpublish_at: Option<DateTime<Utc>>,
+) -> AppResult<()> {
+    if let (Some(publish_at), Some(unpublish_at)) = (publish_at, unpublish_at) {
+        if unpublish_at <= publish_at {
+            return Err(AppError::BadRequest(
+                "unpublish_at must be later than publish_at".to_owned(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects putting content live when its expiry has already passed — it
+/// would be archived again straight away.
+pub fn ensure_not_expired(status: &str, unpublish_at: Option<DateTime<Utc>>) -> AppResult<()> {
+    let going_live = status == "published" || status == "scheduled";
+    if going_live && unpublish_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(AppError::BadRequest(
+            "unpublish_at is in the past; clear or move it before publishing".to_owned(),
+        ));
+    }
+    Ok(())
+}
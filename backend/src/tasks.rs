@@ -1,61 +1,202 @@
-use sqlx::SqlitePool;
+use std::sync::Arc;
 use std::time::Duration;
 
-/// Spawns a long-lived tokio task that wakes up every 60 seconds and runs
-/// all scheduled maintenance work. Errors are logged but never fatal — a
-/// transient DB hiccup should not take the server down.
-pub fn spawn_background_tasks(pool: SqlitePool) {
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use tokio::sync::Notify;
+
+use crate::error::AppResult;
+use crate::services::redirects::RedirectCache;
+use crate::services::{audit, pages, revisions};
+
+/// Longest the scheduler sleeps without re-reading the next due time. A
+/// safety net for schedule changes made outside the API (or clock jumps);
+/// normal edits wake it immediately via `Scheduler::reschedule`.
+const MAX_SCHEDULER_SLEEP: Duration = Duration::from_secs(60 * 60);
+
+/// Back-off after a failed scheduler pass before trying again.
+const SCHEDULER_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// How often trash purging, session cleanup, revision pruning and storing
+/// redirect hit counts run. None of them is time-critical.
+const HOUSEKEEPING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Handle for waking the publish scheduler, kept in `AppState`.
+///
+/// The scheduler sleeps until the next `publish_at` / `unpublish_at` it knows
+/// about. Any write that can change those times must call `reschedule` so it
+/// recomputes its deadline instead of sleeping past the new one.
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    wake: Arc<Notify>,
+}
+
+impl Scheduler {
+    /// Asks the scheduler to re-read the next due time. Cheap; wake-ups that
+    /// arrive while it is busy collapse into one.
+    pub fn reschedule(&self) {
+        self.wake.notify_one();
+    }
+}
+
+/// Spawns the long-lived background tasks:
+///
+/// - the publish scheduler, which sleeps until the exact moment the next
+///   item is due to be published or archived, and
+/// - hourly housekeeping (trash purge, expired sessions, revision retention,
+///   redirect hit counts).
+///
+/// Errors are logged but never fatal — a transient DB hiccup should not take
+/// the server down.
+pub fn spawn_background_tasks(pool: SqlitePool, scheduler: Scheduler, redirects: RedirectCache) {
+    let scheduler_pool = pool.clone();
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            let wait = match run_due_transitions(&scheduler_pool).await {
+                Ok(()) => match next_due(&scheduler_pool).await {
+                    Ok(Some(at)) => (at - Utc::now())
+                        .to_std()
+                        .unwrap_or(Duration::ZERO)
+                        .min(MAX_SCHEDULER_SLEEP),
+                    Ok(None) => MAX_SCHEDULER_SLEEP,
+                    Err(e) => {
+                        tracing::error!("Scheduler error: {:?}", e);
+                        SCHEDULER_RETRY_DELAY
+                    }
+                },
+                Err(e) => {
+                    tracing::error!("Scheduler error: {:?}", e);
+                    SCHEDULER_RETRY_DELAY
+                }
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = scheduler.wake.notified() => {}
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HOUSEKEEPING_INTERVAL);
         loop {
             interval.tick().await;
-            if let Err(e) = run_scheduled_tasks(&pool).await {
+            if let Err(e) = run_housekeeping(&pool).await {
                 tracing::error!("Background task error: {:?}", e);
             }
+            if let Err(e) = redirects.flush_hits(&pool).await {
+                tracing::error!("Failed to store redirect hit counts: {:?}", e);
+            }
         }
     });
 }
 
-/// Runs all periodic maintenance queries against the database.
-///
-/// Each step is intentionally independent: a failure in one query returns
-/// early, but the next invocation (60 s later) will retry cleanly.
-async fn run_scheduled_tasks(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-    // 1. Publish scheduled pages whose publish_at time has passed.
-    let published_pages = sqlx::query(
-        "UPDATE pages SET status = 'published', updated_at = datetime('now')
-         WHERE status = 'scheduled' AND publish_at <= datetime('now')",
+/// Returns the earliest pending `publish_at` or `unpublish_at`, or `None`
+/// when nothing is waiting. The conditions mirror `run_due_transitions`.
+async fn next_due(pool: &SqlitePool) -> AppResult<Option<DateTime<Utc>>> {
+    let next = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT MIN(at) FROM ( \
+             SELECT publish_at AS at FROM pages WHERE status = 'scheduled' \
+             UNION ALL \
+             SELECT publish_at FROM articles WHERE status = 'scheduled' \
+             UNION ALL \
+             SELECT unpublish_at FROM pages \
+             WHERE status = 'published' AND unpublish_at IS NOT NULL \
+             UNION ALL \
+             SELECT unpublish_at FROM articles \
+             WHERE status = 'published' AND unpublish_at IS NOT NULL \
+         )",
     )
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
-    if published_pages.rows_affected() > 0 {
-        tracing::info!("Published {} scheduled pages", published_pages.rows_affected());
-    }
+    Ok(next)
+}
 
-    // 2. Publish scheduled articles whose publish_at time has passed.
-    let published_articles = sqlx::query(
-        "UPDATE articles SET status = 'published', updated_at = datetime('now')
-         WHERE status = 'scheduled' AND publish_at <= datetime('now')",
-    )
-    .execute(pool)
-    .await?;
-    if published_articles.rows_affected() > 0 {
-        tracing::info!(
-            "Published {} scheduled articles",
-            published_articles.rows_affected()
-        );
+/// Publishes scheduled content and archives expired content whose time has
+/// come. Both steps are recorded in the audit log as system actions.
+///
+/// Timestamps are stored in canonical `YYYY-MM-DDTHH:MM:SSZ` form (see
+/// `services::schedule`), so comparing them with `strftime` output is exact.
+async fn run_due_transitions(pool: &SqlitePool) -> AppResult<()> {
+    for table in ["pages", "articles"] {
+        let entity_type = table.trim_end_matches('s');
+
+        // 1. Publish scheduled content whose publish_at has passed.
+        let published = sqlx::query_scalar::<_, String>(&format!(
+            "UPDATE {table} \
+             SET status = 'published', \
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+             WHERE status = 'scheduled' \
+               AND publish_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             RETURNING id"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        for id in &published {
+            audit::log_system_action(
+                pool,
+                "publish",
+                entity_type,
+                id,
+                &json!({ "reason": "scheduled" }),
+            )
+            .await?;
+        }
+        if !published.is_empty() {
+            tracing::info!("Published {} scheduled {}", published.len(), table);
+        }
+
+        // 2. Archive published content whose unpublish_at has passed. Public
+        //    queries already hide it; this makes the state visible to editors
+        //    and records who (the system) took it offline.
+        let expired = sqlx::query_scalar::<_, String>(&format!(
+            "UPDATE {table} \
+             SET status = 'archived', \
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') \
+             WHERE status = 'published' \
+               AND unpublish_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             RETURNING id"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        for id in &expired {
+            audit::log_system_action(
+                pool,
+                "archive",
+                entity_type,
+                id,
+                &json!({ "reason": "expired" }),
+            )
+            .await?;
+        }
+        if !expired.is_empty() {
+            tracing::info!("Archived {} expired {}", expired.len(), table);
+        }
     }
 
-    // 3. Permanently delete trashed content older than 30 days.
+    Ok(())
+}
+
+/// Runs periodic cleanup queries against the database.
+///
+/// Each step is intentionally independent: a failure in one query returns
+/// early, but the next invocation will retry cleanly.
+async fn run_housekeeping(pool: &SqlitePool) -> AppResult<()> {
+    // 1. Permanently delete trashed content older than 30 days.
     //    The 30-day window gives admins a reasonable recovery window without
     //    letting the database grow unbounded.
     let deleted_pages = sqlx::query(
-        "DELETE FROM pages WHERE status = 'trashed' AND trashed_at < datetime('now', '-30 days')",
+        "DELETE FROM pages WHERE status = 'trashed' \
+         AND trashed_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-30 days')",
     )
     .execute(pool)
     .await?;
     let deleted_articles = sqlx::query(
-        "DELETE FROM articles WHERE status = 'trashed' AND trashed_at < datetime('now', '-30 days')",
+        "DELETE FROM articles WHERE status = 'trashed' \
+         AND trashed_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-30 days')",
     )
     .execute(pool)
     .await?;
@@ -66,11 +207,22 @@ async fn run_scheduled_tasks(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             deleted_articles.rows_affected()
         );
     }
+    if deleted_pages.rows_affected() > 0 {
+        // Orphaned children were promoted to the top level; fix their paths.
+        pages::refresh_all_paths(pool).await?;
+    }
 
-    // 4. Remove expired sessions so the sessions table stays lean.
-    sqlx::query("DELETE FROM sessions WHERE expires_at < datetime('now')")
+    // 2. Remove expired sessions so the sessions table stays lean.
+    sqlx::query("DELETE FROM sessions WHERE expires_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now')")
         .execute(pool)
         .await?;
 
+    // 3. Thin out old unpinned revisions according to the retention policy.
+    let policy = revisions::retention_policy(pool).await?;
+    let pruned = revisions::prune(pool, policy).await?;
+    if pruned > 0 {
+        tracing::info!("Pruned {} old revisions", pruned);
+    }
+
     Ok(())
 }
@@ -1,7 +1,11 @@
 import type {
-  User, Page, PageRevision, Article, ArticleRevision,
-  Category, Media, App, MenuItem, Menu, AuditLogEntry,
-  SearchResult, PaginatedResponse, MenuResponse
+  User, Page, PageRevision, PageWithDraft, Article, ArticleRevision, ArticleWithDraft,
+  Category, CategoryInput, Media, App, MenuItem, Menu, AuditLogEntry,
+  SearchResult, PaginatedResponse, MenuResponse, RevisionDiff, SanitizationReportEntry,
+  PublicArticle, PublishedArticle, PublicPage, CategoryUsage, Tag, TagUsage, TagListing, SeriesSummary, SeriesWithParts,
+  PublicAuthor, AuthorOption, AuthorProfile, AuthorLink, Collaborator,
+  Role, PermissionInfo, Invitation, ScopeInfo, ApiToken, NewApiToken,
+  SessionInfo
 } from './types';
 
 class ApiError extends Error {
@@ -29,10 +33,22 @@ async function fetchApi<T>(path: string, options?: RequestInit): Promise<T> {
 
 export const api = {
   // Public
-  getPage: (slug: string) => fetchApi<Page>(`/pages/${slug}`),
-  listArticles: (page = 1, perPage = 20) => fetchApi<PaginatedResponse<Article>>(`/articles?page=${page}&per_page=${perPage}`),
-  getArticle: (slug: string) => fetchApi<Article>(`/articles/${slug}`),
-  getRelatedArticles: (slug: string) => fetchApi<Article[]>(`/articles/${slug}/related`),
+  getPage: (slug: string) => fetchApi<PublicPage>(`/pages/${slug}`),
+  listArticles: (page = 1, perPage = 20) => fetchApi<PaginatedResponse<PublishedArticle>>(`/articles?page=${page}&per_page=${perPage}`),
+  getArticle: (slug: string) => fetchApi<PublicArticle>(`/articles/${slug}`),
+  listCategories: () => fetchApi<CategoryUsage[]>('/categories'),
+  getCategory: (slug: string) => fetchApi<Category>(`/categories/${slug}`),
+  listCategoryArticles: (slug: string, page = 1) =>
+    fetchApi<PaginatedResponse<PublishedArticle>>(`/categories/${slug}/articles?page=${page}`),
+  listCategoryPages: (slug: string, page = 1) =>
+    fetchApi<PaginatedResponse<Page>>(`/categories/${slug}/pages?page=${page}`),
+  listAuthors: () => fetchApi<PublicAuthor[]>('/authors'),
+  getAuthor: (slug: string) => fetchApi<PublicAuthor>(`/authors/${slug}`),
+  listAuthorArticles: (slug: string, page = 1) =>
+    fetchApi<PaginatedResponse<PublishedArticle>>(`/authors/${slug}/articles?page=${page}`),
+  getSeries: (slug: string) => fetchApi<SeriesWithParts>(`/series/${slug}`),
+  getTag: (slug: string, page = 1) => fetchApi<TagListing>(`/tags/${slug}?page=${page}`),
+  getRelatedArticles: (slug: string) => fetchApi<PublishedArticle[]>(`/articles/${slug}/related`),
   listApps: (page = 1) => fetchApi<PaginatedResponse<App>>(`/apps?page=${page}`),
   getMenu: (name: string) => fetchApi<MenuResponse>(`/menus/${name}`),
   getPublicSettings: () => fetchApi<Record<string, string>>(`/settings/public`),
@@ -46,40 +62,68 @@ export const api = {
     // Pages
     listPages: (page = 1, status?: string) =>
       fetchApi<PaginatedResponse<Page>>(`/admin/pages?page=${page}${status ? `&status=${status}` : ''}`),
-    getPage: (id: string) => fetchApi<Page>(`/admin/pages/${id}`),
-    createPage: (data: Partial<Page> & { category_ids?: string[] }) =>
+    getPage: (id: string) => fetchApi<PageWithDraft>(`/admin/pages/${id}`),
+    createPage: (data: Partial<Page> & { category_ids?: string[]; tags?: string[] }) =>
       fetchApi<Page>('/admin/pages', { method: 'POST', body: JSON.stringify(data) }),
-    updatePage: (id: string, data: Partial<Page> & { category_ids?: string[] }) =>
-      fetchApi<Page>(`/admin/pages/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
+    updatePage: (id: string, data: Partial<Page> & { category_ids?: string[]; tags?: string[]; message?: string; expected_updated_at?: string }) =>
+      fetchApi<PageWithDraft>(`/admin/pages/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
     deletePage: (id: string) =>
       fetchApi<{ ok: boolean }>(`/admin/pages/${id}`, { method: 'DELETE' }),
     publishPage: (id: string) =>
       fetchApi<Page>(`/admin/pages/${id}/publish`, { method: 'POST' }),
     restorePage: (id: string) =>
       fetchApi<Page>(`/admin/pages/${id}/restore`, { method: 'POST' }),
+    discardPageDraft: (id: string) =>
+      fetchApi<PageWithDraft>(`/admin/pages/${id}/draft`, { method: 'DELETE' }),
     getPageRevisions: (id: string) =>
       fetchApi<PageRevision[]>(`/admin/pages/${id}/revisions`),
     restorePageRevision: (pageId: string, revId: string) =>
-      fetchApi<Page>(`/admin/pages/${pageId}/revisions/${revId}/restore`, { method: 'POST' }),
+      fetchApi<PageWithDraft>(`/admin/pages/${pageId}/revisions/${revId}/restore`, { method: 'POST' }),
+    pinPageRevision: (pageId: string, revId: string, name: string) =>
+      fetchApi<PageRevision>(`/admin/pages/${pageId}/revisions/${revId}/pin`, { method: 'PUT', body: JSON.stringify({ name }) }),
+    unpinPageRevision: (pageId: string, revId: string) =>
+      fetchApi<PageRevision>(`/admin/pages/${pageId}/revisions/${revId}/pin`, { method: 'DELETE' }),
+    listPageCollaborators: (id: string) =>
+      fetchApi<Collaborator[]>(`/admin/pages/${id}/collaborators`),
+    addPageCollaborator: (id: string, userId: string) =>
+      fetchApi<Collaborator[]>(`/admin/pages/${id}/collaborators`, { method: 'POST', body: JSON.stringify({ user_id: userId }) }),
+    removePageCollaborator: (id: string, userId: string) =>
+      fetchApi<Collaborator[]>(`/admin/pages/${id}/collaborators/${userId}`, { method: 'DELETE' }),
+    diffPageRevisions: (pageId: string, from: string, to: string, params = '') =>
+      fetchApi<RevisionDiff>(`/admin/pages/${pageId}/revisions/${from}/diff/${to}${params ? '?' + params : ''}`),
 
     // Articles
     listArticles: (page = 1, status?: string) =>
       fetchApi<PaginatedResponse<Article>>(`/admin/articles?page=${page}${status ? `&status=${status}` : ''}`),
-    getArticle: (id: string) => fetchApi<Article>(`/admin/articles/${id}`),
-    createArticle: (data: Partial<Article> & { category_ids?: string[] }) =>
+    getArticle: (id: string) => fetchApi<ArticleWithDraft>(`/admin/articles/${id}`),
+    createArticle: (data: Partial<Article> & { category_ids?: string[]; tags?: string[]; author_ids?: string[] }) =>
       fetchApi<Article>('/admin/articles', { method: 'POST', body: JSON.stringify(data) }),
-    updateArticle: (id: string, data: Partial<Article> & { category_ids?: string[] }) =>
-      fetchApi<Article>(`/admin/articles/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
+    updateArticle: (id: string, data: Partial<Article> & { category_ids?: string[]; tags?: string[]; author_ids?: string[]; message?: string; expected_updated_at?: string }) =>
+      fetchApi<ArticleWithDraft>(`/admin/articles/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
     deleteArticle: (id: string) =>
       fetchApi<{ ok: boolean }>(`/admin/articles/${id}`, { method: 'DELETE' }),
     publishArticle: (id: string) =>
       fetchApi<Article>(`/admin/articles/${id}/publish`, { method: 'POST' }),
     restoreArticle: (id: string) =>
       fetchApi<Article>(`/admin/articles/${id}/restore`, { method: 'POST' }),
+    discardArticleDraft: (id: string) =>
+      fetchApi<ArticleWithDraft>(`/admin/articles/${id}/draft`, { method: 'DELETE' }),
     getArticleRevisions: (id: string) =>
       fetchApi<ArticleRevision[]>(`/admin/articles/${id}/revisions`),
     restoreArticleRevision: (articleId: string, revId: string) =>
-      fetchApi<Article>(`/admin/articles/${articleId}/revisions/${revId}/restore`, { method: 'POST' }),
+      fetchApi<ArticleWithDraft>(`/admin/articles/${articleId}/revisions/${revId}/restore`, { method: 'POST' }),
+    pinArticleRevision: (articleId: string, revId: string, name: string) =>
+      fetchApi<ArticleRevision>(`/admin/articles/${articleId}/revisions/${revId}/pin`, { method: 'PUT', body: JSON.stringify({ name }) }),
+    unpinArticleRevision: (articleId: string, revId: string) =>
+      fetchApi<ArticleRevision>(`/admin/articles/${articleId}/revisions/${revId}/pin`, { method: 'DELETE' }),
+    listArticleCollaborators: (id: string) =>
+      fetchApi<Collaborator[]>(`/admin/articles/${id}/collaborators`),
+    addArticleCollaborator: (id: string, userId: string) =>
+      fetchApi<Collaborator[]>(`/admin/articles/${id}/collaborators`, { method: 'POST', body: JSON.stringify({ user_id: userId }) }),
+    removeArticleCollaborator: (id: string, userId: string) =>
+      fetchApi<Collaborator[]>(`/admin/articles/${id}/collaborators/${userId}`, { method: 'DELETE' }),
+    diffArticleRevisions: (articleId: string, from: string, to: string, params = '') =>
+      fetchApi<RevisionDiff>(`/admin/articles/${articleId}/revisions/${from}/diff/${to}${params ? '?' + params : ''}`),
 
     // Media
     listMedia: (page = 1, filter?: string) =>
@@ -103,31 +147,69 @@ export const api = {
     getApp: (id: string) => fetchApi<App>(`/admin/apps/${id}`),
     createApp: (data: Partial<App>) =>
       fetchApi<App>('/admin/apps', { method: 'POST', body: JSON.stringify(data) }),
-    updateApp: (id: string, data: Partial<App>) =>
+    updateApp: (id: string, data: Partial<App> & { expected_updated_at?: string }) =>
       fetchApi<App>(`/admin/apps/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
     deleteApp: (id: string) =>
       fetchApi<{ ok: boolean }>(`/admin/apps/${id}`, { method: 'DELETE' }),
     reorderApps: (ids: string[]) =>
       fetchApi<{ ok: boolean }>('/admin/apps/reorder', { method: 'PUT', body: JSON.stringify(ids) }),
 
+    // Authors
+    listAuthorOptions: () => fetchApi<AuthorOption[]>('/admin/authors'),
+    getProfile: () => fetchApi<AuthorProfile>('/admin/profile'),
+    updateProfile: (data: { slug?: string; bio?: string; avatar_id?: string; links?: AuthorLink[] }) =>
+      fetchApi<AuthorProfile>('/admin/profile', { method: 'PUT', body: JSON.stringify(data) }),
+    getUserProfile: (id: string) => fetchApi<AuthorProfile>(`/admin/users/${id}/profile`),
+    updateUserProfile: (id: string, data: { slug?: string; bio?: string; avatar_id?: string; links?: AuthorLink[] }) =>
+      fetchApi<AuthorProfile>(`/admin/users/${id}/profile`, { method: 'PUT', body: JSON.stringify(data) }),
+
+    // Series
+    listSeries: () => fetchApi<SeriesSummary[]>('/admin/series'),
+    getSeries: (id: string) => fetchApi<SeriesWithParts>(`/admin/series/${id}`),
+    createSeries: (data: { title: string; slug?: string; description?: string; article_ids?: string[] }) =>
+      fetchApi<SeriesWithParts>('/admin/series', { method: 'POST', body: JSON.stringify(data) }),
+    updateSeries: (id: string, data: { title?: string; slug?: string; description?: string; article_ids?: string[] }) =>
+      fetchApi<SeriesWithParts>(`/admin/series/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
+    deleteSeries: (id: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/series/${id}`, { method: 'DELETE' }),
+
+    // Tags
+    listTags: () => fetchApi<TagUsage[]>('/admin/tags'),
+    renameTag: (id: string, name: string) =>
+      fetchApi<Tag>(`/admin/tags/${id}`, { method: 'PUT', body: JSON.stringify({ name }) }),
+    mergeTag: (id: string, intoId: string) =>
+      fetchApi<Tag>(`/admin/tags/${id}/merge`, { method: 'POST', body: JSON.stringify({ into_id: intoId }) }),
+    deleteTag: (id: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/tags/${id}`, { method: 'DELETE' }),
+
     // Categories
     listCategories: () => fetchApi<Category[]>('/admin/categories'),
-    createCategory: (data: { name: string; slug?: string }) =>
+    createCategory: (data: CategoryInput) =>
       fetchApi<Category>('/admin/categories', { method: 'POST', body: JSON.stringify(data) }),
-    updateCategory: (id: string, data: { name?: string; slug?: string }) =>
+    updateCategory: (id: string, data: CategoryInput) =>
       fetchApi<Category>(`/admin/categories/${id}`, { method: 'PUT', body: JSON.stringify(data) }),
-    deleteCategory: (id: string) =>
-      fetchApi<{ ok: boolean }>(`/admin/categories/${id}`, { method: 'DELETE' }),
+    deleteCategory: (id: string, reassignTo?: string) =>
+      fetchApi<{ ok: boolean }>(
+        `/admin/categories/${id}${reassignTo ? `?reassign_to=${reassignTo}` : ''}`,
+        { method: 'DELETE' }
+      ),
+    mergeCategory: (id: string, intoId: string) =>
+      fetchApi<Category>(`/admin/categories/${id}/merge`, { method: 'POST', body: JSON.stringify({ into_id: intoId }) }),
 
     // Menus
     getMenu: (name: string) => fetchApi<MenuResponse>(`/admin/menus/${name}`),
-    updateMenu: (name: string, items: Partial<MenuItem>[]) =>
-      fetchApi<{ ok: boolean }>(`/admin/menus/${name}`, { method: 'PUT', body: JSON.stringify({ items }) }),
+    updateMenu: (name: string, items: Partial<MenuItem>[], expectedUpdatedAt?: string) =>
+      fetchApi<{ ok: boolean; updated_at: string }>(`/admin/menus/${name}`, {
+        method: 'PUT',
+        body: JSON.stringify({ items, expected_updated_at: expectedUpdatedAt }),
+      }),
 
     // Settings
     getSettings: () => fetchApi<Record<string, string>>('/admin/settings'),
     updateSettings: (settings: Record<string, string>) =>
-      fetchApi<{ ok: boolean }>('/admin/settings', { method: 'PUT', body: JSON.stringify(settings) }),
+      fetchApi<{ ok: boolean; updated_at: string }>('/admin/settings', { method: 'PUT', body: JSON.stringify(settings) }),
+    getSanitizationReport: () =>
+      fetchApi<SanitizationReportEntry[]>('/admin/settings/sanitization-report'),
 
     // Trash
     listTrash: () => fetchApi<{ pages: Page[]; articles: Article[] }>('/admin/trash'),
@@ -141,6 +223,48 @@ export const api = {
     updateUserRole: (id: string, role: string) =>
       fetchApi<User>(`/admin/users/${id}/role`, { method: 'PUT', body: JSON.stringify({ role }) }),
 
+    // Roles
+    listPermissions: () => fetchApi<PermissionInfo[]>('/admin/permissions'),
+    listRoles: () => fetchApi<Role[]>('/admin/roles'),
+    createRole: (data: { name: string; description?: string; permissions: string[] }) =>
+      fetchApi<Role>('/admin/roles', { method: 'POST', body: JSON.stringify(data) }),
+    updateRole: (name: string, data: { description?: string; permissions?: string[] }) =>
+      fetchApi<Role>(`/admin/roles/${name}`, { method: 'PUT', body: JSON.stringify(data) }),
+    deleteRole: (name: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/roles/${name}`, { method: 'DELETE' }),
+
+    // Sessions
+    listOwnSessions: () => fetchApi<SessionInfo[]>('/admin/me/sessions'),
+    revokeOtherOwnSessions: () =>
+      fetchApi<{ ok: boolean; revoked: number }>('/admin/me/sessions', { method: 'DELETE' }),
+    revokeOwnSession: (id: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/me/sessions/${id}`, { method: 'DELETE' }),
+    listUserSessions: (userId: string) => fetchApi<SessionInfo[]>(`/admin/users/${userId}/sessions`),
+    revokeUserSessions: (userId: string) =>
+      fetchApi<{ ok: boolean; revoked: number }>(`/admin/users/${userId}/sessions`, { method: 'DELETE' }),
+
+    // API tokens and service accounts
+    listScopes: () => fetchApi<ScopeInfo[]>('/admin/scopes'),
+    listOwnTokens: () => fetchApi<ApiToken[]>('/admin/me/tokens'),
+    createOwnToken: (data: { name: string; scopes: string[]; expires_at?: string }) =>
+      fetchApi<NewApiToken>('/admin/me/tokens', { method: 'POST', body: JSON.stringify(data) }),
+    revokeOwnToken: (id: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/me/tokens/${id}`, { method: 'DELETE' }),
+    createServiceAccount: (displayName: string, role: string) =>
+      fetchApi<User>('/admin/service-accounts', { method: 'POST', body: JSON.stringify({ display_name: displayName, role }) }),
+    listUserTokens: (userId: string) => fetchApi<ApiToken[]>(`/admin/users/${userId}/tokens`),
+    createUserToken: (userId: string, data: { name: string; scopes: string[]; expires_at?: string }) =>
+      fetchApi<NewApiToken>(`/admin/users/${userId}/tokens`, { method: 'POST', body: JSON.stringify(data) }),
+    revokeUserToken: (userId: string, tokenId: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/users/${userId}/tokens/${tokenId}`, { method: 'DELETE' }),
+
+    // Invitations
+    listInvitations: () => fetchApi<Invitation[]>('/admin/invitations'),
+    createInvitation: (email: string, role: string) =>
+      fetchApi<Invitation>('/admin/invitations', { method: 'POST', body: JSON.stringify({ email, role }) }),
+    deleteInvitation: (id: string) =>
+      fetchApi<{ ok: boolean }>(`/admin/invitations/${id}`, { method: 'DELETE' }),
+
     // Search
     search: (q: string, type?: string) =>
       fetchApi<SearchResult[]>(`/admin/search?q=${encodeURIComponent(q)}${type ? `&type=${type}` : ''}`),
@@ -1,25 +1,124 @@
+export type ContentFormat = 'html' | 'markdown';
+
 export interface User {
   id: string;
   external_id: string;
   email: string;
   display_name: string;
-  role: 'admin' | 'editor';
+  role: string;
   created_at: string;
   last_login: string | null;
+  /** Service accounts authenticate with API tokens only. */
+  is_service: boolean;
+  /** Permissions granted by the user's role. */
+  permissions: string[];
+  /** Present when the request was made with an API token. */
+  scopes?: string[];
+}
+
+/** A signed-in browser session. */
+export interface SessionInfo {
+  id: string;
+  user_agent: string;
+  ip: string;
+  created_at: string;
+  last_seen_at: string;
+  expires_at: string;
+  /** True for the session this request was made with. */
+  current: boolean;
+}
+
+export interface ScopeInfo {
+  name: string;
+  description: string;
+}
+
+/** An API token; the secret itself is only returned once, on creation. */
+export interface ApiToken {
+  id: string;
+  user_id: string;
+  name: string;
+  scopes: string[];
+  created_by: string | null;
+  created_at: string;
+  expires_at: string;
+  last_used_at: string | null;
+}
+
+export interface NewApiToken extends ApiToken {
+  token: string;
+}
+
+/** An email address invited to join with a role, claimed on first login. */
+export interface Invitation {
+  id: string;
+  email: string;
+  role: string;
+  invited_by: string | null;
+  created_at: string;
+  claimed_by: string | null;
+  claimed_at: string | null;
+}
+
+export interface PermissionInfo {
+  name: string;
+  description: string;
+}
+
+/** A named set of permissions; built-in roles cannot be changed. */
+export interface Role {
+  name: string;
+  description: string;
+  permissions: string[];
+  builtin: boolean;
+  user_count: number;
+  created_at: string;
+  updated_at: string;
 }
 
 export interface Page {
   id: string;
   title: string;
   slug: string;
+  /** Full URL path including ancestor slugs, e.g. `docs/setup`. */
+  path: string;
   content: string;
-  status: 'draft' | 'published' | 'scheduled' | 'trashed';
+  content_format: ContentFormat;
+  /** `content` rendered and sanitized by the server; what the public site shows. */
+  content_html: string;
+  status: 'draft' | 'in_review' | 'published' | 'scheduled' | 'archived' | 'trashed';
   publish_at: string | null;
+  unpublish_at: string | null;
   author_id: string;
   created_at: string;
   updated_at: string;
   trashed_at: string | null;
   template: 'default' | 'project';
+  review_comment: string | null;
+}
+
+/** Unpublished edits to a published page; the live fields stay on the page itself. */
+export interface PageDraft {
+  page_id: string;
+  title: string;
+  content: string;
+  content_format: ContentFormat;
+  author_id: string;
+  updated_at: string;
+  in_review: boolean;
+  review_comment: string | null;
+}
+
+export interface PageWithDraft extends Page {
+  draft: PageDraft | null;
+  categories: Category[];
+  tags: Tag[];
+}
+
+/** A published page as the public detail endpoint returns it. */
+export interface PublicPage extends Page {
+  categories: Category[];
+  tags: Tag[];
 }
 
 export interface PageRevision {
@@ -27,8 +126,11 @@ export interface PageRevision {
   page_id: string;
   title: string;
   content: string;
+  content_format: ContentFormat;
   author_id: string;
   created_at: string;
+  message: string | null;
+  version_name: string | null;
 }
 
 export interface Article {
@@ -37,14 +139,129 @@ export interface Article {
   slug: string;
   short_text: string;
   content: string;
-  status: 'draft' | 'published' | 'scheduled' | 'trashed';
+  content_format: ContentFormat;
+  /** `content` rendered and sanitized by the server; what the public site shows. */
+  content_html: string;
+  status: 'draft' | 'in_review' | 'published' | 'scheduled' | 'archived' | 'trashed';
   publish_at: string | null;
+  unpublish_at: string | null;
   author_id: string;
   created_at: string;
   updated_at: string;
   trashed_at: string | null;
   cover_image_id: string | null;
   reading_time_minutes: number;
+  review_comment: string | null;
+}
+
+/** Unpublished edits to a published article; the live fields stay on the article itself. */
+export interface ArticleDraft {
+  article_id: string;
+  title: string;
+  short_text: string;
+  content: string;
+  content_format: ContentFormat;
+  cover_image_id: string | null;
+  author_id: string;
+  updated_at: string;
+  in_review: boolean;
+  review_comment: string | null;
+}
+
+export interface ArticleWithDraft extends Article {
+  draft: ArticleDraft | null;
+  categories: Category[];
+  tags: Tag[];
+  /** Credited authors in byline order. */
+  authors: AuthorOption[];
+}
+
+/** An article as the public endpoints return it: without its creator's user id. */
+export type PublishedArticle = Omit<Article, 'author_id' | 'trashed_at' | 'review_comment'>;
+
+/** A published article as the public detail endpoint returns it. */
+export interface PublicArticle extends PublishedArticle {
+  authors: PublicAuthor[];
+  categories: Category[];
+  tags: Tag[];
+  series: SeriesMembership | null;
+}
+
+export interface AuthorLink {
+  label: string;
+  url: string;
+}
+
+/** A user's author profile, as its owner and admins see it. */
+export interface AuthorProfile {
+  user_id: string;
+  display_name: string;
+  slug: string;
+  bio: string;
+  avatar_id: string | null;
+  links: AuthorLink[];
+  updated_at: string;
+}
+
+/** What the public API shows of a credited author. */
+export interface PublicAuthor {
+  slug: string;
+  display_name: string;
+  bio: string;
+  avatar_id: string | null;
+  links: AuthorLink[];
+}
+
+/** A user that can be credited as an author. */
+export interface AuthorOption {
+  id: string;
+  display_name: string;
+}
+
+export interface Collaborator {
+  user_id: string;
+  display_name: string;
+  granted_by: string | null;
+  created_at: string;
+}
+
+export interface Series {
+  id: string;
+  title: string;
+  slug: string;
+  description: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SeriesSummary extends Series {
+  article_count: number;
+}
+
+/** One article of a series; `position` is 1-based. */
+export interface SeriesPart {
+  id: string;
+  title: string;
+  slug: string;
+  short_text: string;
+  status: string;
+  position: number;
+}
+
+/** A series with its articles in reading order. */
+export interface SeriesWithParts extends Series {
+  parts: SeriesPart[];
+}
+
+/** Where an article sits in its series. */
+export interface SeriesMembership {
+  id: string;
+  title: string;
+  slug: string;
+  position: number;
+  total: number;
+  previous: SeriesPart | null;
+  next: SeriesPart | null;
 }
 
 export interface ArticleRevision {
@@ -53,14 +270,69 @@ export interface ArticleRevision {
   title: string;
   short_text: string;
   content: string;
+  content_format: ContentFormat;
+  author_id: string;
+  created_at: string;
+  message: string | null;
+  version_name: string | null;
+}
+
+export interface DiffSegment {
+  op: 'equal' | 'insert' | 'delete';
+  text: string;
+}
+
+export interface FieldDiff {
+  changed: boolean;
+  segments: DiffSegment[];
+}
+
+export interface ContentDiff {
+  title: FieldDiff;
+  short_text?: FieldDiff;
+  content: FieldDiff;
+}
+
+export interface DiffSide {
+  revision_id: string | null;
   author_id: string;
   created_at: string;
 }
 
+export interface RevisionDiff {
+  format: 'text' | 'html';
+  granularity: 'word' | 'block';
+  from: DiffSide;
+  to: DiffSide;
+  current: DiffSide;
+  changes: ContentDiff;
+  against_current: ContentDiff;
+}
+
 export interface Category {
   id: string;
   name: string;
   slug: string;
+  parent_id: string | null;
+  description: string;
+  cover_image_id: string | null;
+  sort_order: number;
+}
+
+/** Writable fields of a category; empty strings clear `parent_id` and `cover_image_id`. */
+export interface CategoryInput {
+  name: string;
+  slug?: string;
+  parent_id?: string;
+  description?: string;
+  cover_image_id?: string;
+  sort_order?: number;
+}
+
+/** A category with its published page and article counts. */
+export interface CategoryUsage extends Category {
+  page_count: number;
+  article_count: number;
 }
 
 export interface Media {
@@ -93,6 +365,7 @@ export interface App {
 export interface Menu {
   id: string;
   name: string;
+  updated_at: string;
 }
 
 export interface MenuItem {
@@ -107,7 +380,7 @@ export interface MenuItem {
 
 export interface AuditLogEntry {
   id: string;
-  user_id: string;
+  user_id: string | null;
   action: string;
   entity_type: string;
   entity_id: string;
@@ -134,3 +407,33 @@ export interface MenuResponse {
   menu: Menu;
   items: MenuItem[];
 }
+
+/** A page or article whose stored content the current sanitization policy would change. */
+export interface SanitizationReportEntry {
+  entity_type: 'page' | 'article';
+  id: string;
+  title: string;
+  slug: string;
+  status: string;
+  content_format: ContentFormat;
+  content_changes: boolean;
+  content_html_changes: boolean;
+}
+
+export interface Tag {
+  id: string;
+  name: string;
+  slug: string;
+}
+
+export interface TagUsage extends Tag {
+  page_count: number;
+  article_count: number;
+}
+
+/** Published content carrying a tag. */
+export interface TagListing {
+  tag: Tag;
+  pages: Page[];
+  articles: PaginatedResponse<PublishedArticle>;
+}
@@ -50,6 +50,30 @@ export function formatFileSize(bytes: number): string {
   return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
 }
 
+/**
+ * Converts a UTC ISO timestamp to the `YYYY-MM-DDTHH:MM` wall-clock value a
+ * `datetime-local` input expects, as seen in the given IANA timezone.
+ * The backend interprets such values in the site timezone, so they can be
+ * sent back unchanged.
+ */
+export function toZonedInput(isoString: string | null | undefined, timeZone: string): string {
+  if (!isoString) return '';
+  const parts = Object.fromEntries(
+    new Intl.DateTimeFormat('en-CA', {
+      timeZone,
+      year: 'numeric',
+      month: '2-digit',
+      day: '2-digit',
+      hour: '2-digit',
+      minute: '2-digit',
+      hourCycle: 'h23',
+    })
+      .formatToParts(new Date(isoString))
+      .map((p) => [p.type, p.value])
+  );
+  return `${parts.year}-${parts.month}-${parts.day}T${parts.hour}:${parts.minute}`;
+}
+
 /**
  * Formats a date to a locale-aware short datetime string.
  */
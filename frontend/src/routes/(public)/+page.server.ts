@@ -1,5 +1,5 @@
 import type { PageServerLoad } from './$types';
-import type { Page, Article, App, PaginatedResponse } from '$lib/api/types';
+import type { Page, PublishedArticle, App, PaginatedResponse } from '$lib/api/types';
 
 export const load: PageServerLoad = async ({ fetch, url }) => {
   const settingsRes = await fetch('/api/settings/public');
@@ -29,7 +29,7 @@ export const load: PageServerLoad = async ({ fetch, url }) => {
     fetch('/api/apps?page=1&per_page=3'),
   ]);
 
-  const articles: PaginatedResponse<Article> = articlesRes.ok
+  const articles: PaginatedResponse<PublishedArticle> = articlesRes.ok
     ? await articlesRes.json()
     : { data: [], total: 0, page: 1, per_page: 6 };
 
@@ -1,9 +1,11 @@
 import type { PageServerLoad } from './$types';
-import { error } from '@sveltejs/kit';
+import { error, redirect } from '@sveltejs/kit';
 import type { Page } from '$lib/api/types';
 
 export const load: PageServerLoad = async ({ fetch, params }) => {
-  const res = await fetch(`/api/pages/${params.slug}`);
+  // Nested pages live at their full path, e.g. /about/team.
+  const path = params.path.split('/').map(encodeURIComponent).join('/');
+  const res = await fetch(`/api/pages/${path}`);
 
   if (res.status === 404) {
     throw error(404, 'Page not found');
@@ -13,6 +15,13 @@ export const load: PageServerLoad = async ({ fetch, params }) => {
     throw error(res.status, 'Failed to load page');
   }
 
+  // The API answers old slugs with a 301 that fetch follows transparently;
+  // pass the permanent redirect on to the visitor.
+  if (res.redirected) {
+    const target = new URL(res.url).pathname.replace(/^\/api\/pages\//, '');
+    throw redirect(301, `/${target}`);
+  }
+
   const pageData: Page = await res.json();
 
   // Only show published pages to public visitors
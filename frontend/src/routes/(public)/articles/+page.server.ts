@@ -1,13 +1,47 @@
 import type { PageServerLoad } from './$types';
-import type { Article, PaginatedResponse } from '$lib/api/types';
+import { error } from '@sveltejs/kit';
+import type { Category, PaginatedResponse, PublishedArticle, TagListing } from '$lib/api/types';
 
 export const load: PageServerLoad = async ({ fetch, url }) => {
   const pageNum = Math.max(1, parseInt(url.searchParams.get('page') ?? '1', 10) || 1);
+  const tagSlug = url.searchParams.get('tag');
+  const categorySlug = url.searchParams.get('category');
+
+  // `?tag=` narrows the list to one tag, along with any pages carrying it.
+  if (tagSlug) {
+    const res = await fetch(`/api/tags/${encodeURIComponent(tagSlug)}?page=${pageNum}&per_page=10`);
+    if (res.status === 404) {
+      throw error(404, 'Tag not found');
+    }
+    if (!res.ok) {
+      throw error(res.status, 'Failed to load tag');
+    }
+    const listing: TagListing = await res.json();
+    return { articles: listing.articles, tag: listing.tag, category: null, taggedPages: listing.pages };
+  }
+
+  // `?category=` narrows the list to one category.
+  if (categorySlug) {
+    const slug = encodeURIComponent(categorySlug);
+    const [categoryRes, articlesRes] = await Promise.all([
+      fetch(`/api/categories/${slug}`),
+      fetch(`/api/categories/${slug}/articles?page=${pageNum}&per_page=10`)
+    ]);
+    if (categoryRes.status === 404) {
+      throw error(404, 'Category not found');
+    }
+    if (!categoryRes.ok || !articlesRes.ok) {
+      throw error(articlesRes.ok ? categoryRes.status : articlesRes.status, 'Failed to load category');
+    }
+    const category: Category = await categoryRes.json();
+    const articles: PaginatedResponse<PublishedArticle> = await articlesRes.json();
+    return { articles, tag: null, category, taggedPages: [] };
+  }
 
   const res = await fetch(`/api/articles?page=${pageNum}&per_page=10`);
-  const articles: PaginatedResponse<Article> = res.ok
+  const articles: PaginatedResponse<PublishedArticle> = res.ok
     ? await res.json()
     : { data: [], total: 0, page: 1, per_page: 10 };
 
-  return { articles };
+  return { articles, tag: null, category: null, taggedPages: [] };
 };
@@ -1,6 +1,6 @@
 import type { PageServerLoad } from './$types';
-import { error } from '@sveltejs/kit';
-import type { Article } from '$lib/api/types';
+import { error, redirect } from '@sveltejs/kit';
+import type { PublicArticle, PublishedArticle } from '$lib/api/types';
 import { highlightCodeBlocks } from '$lib/highlight';
 
 export const load: PageServerLoad = async ({ fetch, params }) => {
@@ -14,7 +14,14 @@ export const load: PageServerLoad = async ({ fetch, params }) => {
     throw error(res.status, 'Failed to load article');
   }
 
-  const article: Article = await res.json();
+  // The API answers old slugs with a 301 that fetch follows transparently;
+  // pass the permanent redirect on to the visitor.
+  if (res.redirected) {
+    const slug = new URL(res.url).pathname.replace(/^\/api\/articles\//, '');
+    throw redirect(301, `/articles/${slug}`);
+  }
+
+  const article: PublicArticle = await res.json();
 
   if (article.status !== 'published') {
     throw error(404, 'Article not found');
@@ -22,20 +29,20 @@ export const load: PageServerLoad = async ({ fetch, params }) => {
 
   // Fetch related articles in parallel
   const relatedRes = await fetch(`/api/articles/${params.slug}/related`);
-  const related: Article[] = relatedRes.ok ? await relatedRes.json() : [];
+  const related: PublishedArticle[] = relatedRes.ok ? await relatedRes.json() : [];
 
   // Extract headings for TOC
   const headingRegex = /<h([23])[^>]*>(.*?)<\/h\1>/gi;
   const toc: { level: number; text: string; id: string }[] = [];
   let match;
-  while ((match = headingRegex.exec(article.content)) !== null) {
+  while ((match = headingRegex.exec(article.content_html)) !== null) {
     const text = match[2].replace(/<[^>]*>/g, '');
     const id = text.toLowerCase().replace(/[^a-z0-9]+/g, '-').replace(/^-|-$/g, '');
     toc.push({ level: parseInt(match[1]), text, id });
   }
 
   // Inject IDs into headings for anchor links
-  let processedContent = article.content;
+  let processedContent = article.content_html;
   for (const heading of toc) {
     // Strip any characters that could break out of the attribute
     const safeId = heading.id.replace(/[^a-z0-9-]/g, '');
@@ -49,5 +56,5 @@ export const load: PageServerLoad = async ({ fetch, params }) => {
   // Apply syntax highlighting to code blocks
   processedContent = await highlightCodeBlocks(processedContent);
 
-  return { article: { ...article, content: processedContent }, related, toc };
+  return { article: { ...article, content_html: processedContent }, related, toc };
 };
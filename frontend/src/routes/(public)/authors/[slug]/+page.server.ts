@@ -0,0 +1,25 @@
+import type { PageServerLoad } from './$types';
+import { error } from '@sveltejs/kit';
+import type { PaginatedResponse, PublicAuthor, PublishedArticle } from '$lib/api/types';
+
+export const load: PageServerLoad = async ({ fetch, params, url }) => {
+  const pageNum = Math.max(1, parseInt(url.searchParams.get('page') ?? '1', 10) || 1);
+  const slug = encodeURIComponent(params.slug);
+
+  const [authorRes, articlesRes] = await Promise.all([
+    fetch(`/api/authors/${slug}`),
+    fetch(`/api/authors/${slug}/articles?page=${pageNum}&per_page=10`)
+  ]);
+
+  if (authorRes.status === 404) {
+    throw error(404, 'Author not found');
+  }
+
+  if (!authorRes.ok || !articlesRes.ok) {
+    throw error(authorRes.ok ? articlesRes.status : authorRes.status, 'Failed to load author');
+  }
+
+  const author: PublicAuthor = await authorRes.json();
+  const articles: PaginatedResponse<PublishedArticle> = await articlesRes.json();
+  return { author, articles };
+};
@@ -0,0 +1,18 @@
+import type { PageServerLoad } from './$types';
+import { error } from '@sveltejs/kit';
+import type { SeriesWithParts } from '$lib/api/types';
+
+export const load: PageServerLoad = async ({ fetch, params }) => {
+  const res = await fetch(`/api/series/${params.slug}`);
+
+  if (res.status === 404) {
+    throw error(404, 'Series not found');
+  }
+
+  if (!res.ok) {
+    throw error(res.status, 'Failed to load series');
+  }
+
+  const series: SeriesWithParts = await res.json();
+  return { series };
+};
@@ -6,7 +6,14 @@ export const load: LayoutServerLoad = async ({ fetch }) => {
     const res = await fetch('/api/admin/me');
     if (!res.ok) throw new Error('Not authenticated');
     const user = await res.json();
-    return { user };
+    // Schedule times in the editors are shown and entered in the site timezone.
+    let timezone = 'UTC';
+    const settingsRes = await fetch('/api/settings/public');
+    if (settingsRes.ok) {
+      const settings = (await settingsRes.json()) as Record<string, string>;
+      timezone = settings['site_timezone'] || 'UTC';
+    }
+    return { user, timezone };
   } catch {
     throw redirect(302, '/api/auth/login');
   }
@@ -3,6 +3,8 @@ import type { Page } from '$lib/api/types';
 
 export const load: PageServerLoad = async ({ fetch }) => {
   let settings: Record<string, string> = {};
+  // Settings version from the ETag, sent back on save to detect concurrent edits.
+  let version: string | undefined;
   let pages: Page[] = [];
 
   try {
@@ -12,6 +14,7 @@ export const load: PageServerLoad = async ({ fetch }) => {
     ]);
     if (settingsRes.status === 'fulfilled' && settingsRes.value.ok) {
       settings = await settingsRes.value.json();
+      version = settingsRes.value.headers.get('etag')?.replaceAll('"', '');
     }
     if (pagesRes.status === 'fulfilled' && pagesRes.value.ok) {
       const data = await pagesRes.value.json();
@@ -21,5 +24,5 @@ export const load: PageServerLoad = async ({ fetch }) => {
     // Non-fatal
   }
 
-  return { settings, pages };
+  return { settings, pages, version };
 };